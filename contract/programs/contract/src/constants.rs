@@ -3,6 +3,18 @@ pub const MIN_OPERATE_AMOUNT: u64 = 1000;
 
 /// Kamino VaultState discriminator
 pub const VAULT_STATE_DISCRIMINATOR: [u8; 8] = [228, 196, 82, 165, 98, 210, 235, 152];
+/// Kamino lending Reserve discriminator
+pub const RESERVE_DISCRIMINATOR: [u8; 8] = [43, 242, 204, 202, 26, 247, 59, 127];
+/// JupLend liquidity TokenReserve discriminator
+pub const TOKEN_RESERVE_DISCRIMINATOR: [u8; 8] = [21, 18, 59, 135, 120, 20, 31, 12];
+/// marginfi Bank discriminator
+pub const MARGINFI_BANK_DISCRIMINATOR: [u8; 8] = [142, 49, 166, 242, 50, 66, 97, 188];
+/// marginfi MarginfiAccount discriminator
+pub const MARGINFI_ACCOUNT_DISCRIMINATOR: [u8; 8] = [67, 178, 130, 109, 126, 114, 28, 42];
+/// Kamino farms FarmState discriminator
+pub const FARM_STATE_DISCRIMINATOR: [u8; 8] = [198, 102, 216, 74, 63, 66, 163, 190];
+/// Kamino farms UserState discriminator
+pub const USER_STATE_DISCRIMINATOR: [u8; 8] = [72, 177, 85, 249, 76, 167, 186, 126];
 /// Slots per year for interest calculation (2 slots/sec * 60 * 60 * 24 * 365)
 pub const SLOTS_PER_YEAR: u128 = 63_072_000;
 
@@ -13,4 +25,18 @@ pub const WAD: u128 = 1_000_000_000_000_000_000;
 
 pub const EXCHANGE_PRICES_PRECISION: u128 = 1000000000000;
 pub const SECONDS_PER_YEAR: u128 = 31536000;
-pub const MAX_REWARDS_RATE: u128 = 50000000000000;
\ No newline at end of file
+pub const MAX_REWARDS_RATE: u128 = 50000000000000;
+/// JupLend rewards rate and return precision (1e12 = 1%, 1e14 = 100%)
+pub const REWARDS_RATE_PRECISION: u128 = 100000000000000;
+/// JupLend utilization and rate precision (1e4 = 100%)
+pub const UTILIZATION_PRECISION: u128 = 10000;
+/// Share price precision (1e12 = 1 USDC per cUSDC)
+pub const SHARE_PRICE_PRECISION: u128 = 1_000_000_000_000;
+/// Current `AggregatorConfig` layout version
+pub const CONFIG_VERSION: u8 = 7;
+/// Kamino farm shares kept unstaked when a config is created or migrated (5%)
+pub const DEFAULT_FARM_LIQUID_BUFFER_BPS: u16 = 500;
+/// Shortest interest window the Kamino realized APY is computed over (1 hour)
+pub const MIN_REALIZED_APY_WINDOW_SECONDS: u64 = 3600;
+/// Age at which `rebalance` moves the Kamino interest snapshot forward (7 days)
+pub const REALIZED_APY_SNAPSHOT_INTERVAL_SECONDS: u64 = 604800;
@@ -25,4 +25,48 @@ pub enum AggregatorError {
     AccountReloadFailed,
     #[msg("Invalid shares amount")]
     InvalidShares,
-}
\ No newline at end of file
+    #[msg("Allocation drift is within the configured threshold")]
+    DriftWithinThreshold,
+    #[msg("Rebalance cooldown has not elapsed")]
+    RebalanceCooldown,
+    #[msg("Invalid allocation bounds")]
+    InvalidAllocationBounds,
+    #[msg("Swap CPI failed")]
+    SwapFailed,
+    #[msg("Swap returned less than the minimum amount out")]
+    SlippageExceeded,
+    #[msg("Invalid swap accounts count")]
+    InvalidSwapAccounts,
+    #[msg("Operator allowance exceeded")]
+    AllowanceExceeded,
+    #[msg("Operator approval is missing or does not match owner and operator")]
+    InvalidOperatorApproval,
+    #[msg("Wallet is not allowed to deposit into this vault")]
+    NotAllowlisted,
+    #[msg("Deposit is still within the minimum holding period")]
+    HoldingPeriodActive,
+    #[msg("Share token name, symbol or URI is empty or too long")]
+    InvalidShareMetadata,
+    #[msg("Config is already at the current version")]
+    ConfigAlreadyMigrated,
+    #[msg("Reserve accounts don't match the Kamino vault allocation")]
+    InvalidReserveAccount,
+    #[msg("Kamino farm shares are still in their withdrawal cooldown")]
+    FarmCooldownActive,
+    #[msg("Kamino farm liquid buffer is already at its target")]
+    NothingToUnstake,
+    #[msg("MarginFi account doesn't match the one the config owns")]
+    InvalidMarginfiAccount,
+    #[msg("Config already owns a marginfi account and lends to a bank")]
+    MarginfiAccountAlreadyInitialized,
+    #[msg("klend reserve accounts don't match the reserve the config holds cTokens of")]
+    InvalidKlendReserve,
+    #[msg("Config already has a klend reserve")]
+    KlendReserveAlreadyInitialized,
+    #[msg("Save reserve accounts don't match the reserve the config holds cTokens of")]
+    InvalidSaveReserve,
+    #[msg("Config already has a Save reserve")]
+    SaveReserveAlreadyInitialized,
+    #[msg("MarginFi bank doesn't match the USDC bank the config lends to")]
+    InvalidMarginfiBank,
+}
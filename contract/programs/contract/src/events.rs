@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
 
+use crate::states::{AccessMode, AllocationBounds, AllocationMode};
+
 
 #[event]
 pub struct DepositEvent {
     pub user: Pubkey,
+    pub receiver: Pubkey,
     pub amount: u64,
     pub cusdc_minted: u64,
 }
@@ -11,6 +14,8 @@ pub struct DepositEvent {
 #[event]
 pub struct WithdrawEvent {
     pub user: Pubkey,
+    pub owner: Pubkey,
+    pub receiver: Pubkey,
     pub cusdc_burned: u64,
     pub usdc_returned: u64,
 }
@@ -19,16 +24,142 @@ pub struct WithdrawEvent {
 pub struct RebalanceEvent {
     pub juplend_balance: u64,
     pub kamino_balance: u64,
+    pub marginfi_balance: u64,
+    pub klend_balance: u64,
+    pub save_balance: u64,
+    /// Zero in Fixed mode, where APYs aren't read
+    pub juplend_apy_bps: u64,
+    pub kamino_apy_bps: u64,
 }
 
 #[event]
 pub struct AllocationUpdateEvent {
     pub juplend_bps: u16,
     pub kamino_bps: u16,
+    pub marginfi_bps: u16,
+    pub klend_bps: u16,
+    pub save_bps: u16,
+}
+
+#[event]
+pub struct MarginfiAccountInitEvent {
+    pub marginfi_account: Pubkey,
+    pub bank: Pubkey,
+}
+
+#[event]
+pub struct KlendReserveInitEvent {
+    pub reserve: Pubkey,
+    pub ctoken_account: Pubkey,
+}
+
+#[event]
+pub struct SaveReserveInitEvent {
+    pub reserve: Pubkey,
+    pub ctoken_account: Pubkey,
+}
+
+#[event]
+pub struct AllocationModeUpdateEvent {
+    pub allocation_mode: AllocationMode,
+    pub juplend_bounds: AllocationBounds,
+    pub kamino_bounds: AllocationBounds,
+}
+
+#[event]
+pub struct RebalanceParamsUpdateEvent {
+    pub drift_threshold_bps: u16,
+    pub rebalance_cooldown_slots: u64,
+    pub keeper_tip: u64,
+}
+
+#[event]
+pub struct KeeperTipEvent {
+    pub keeper: Pubkey,
+    pub drift_bps: u16,
+    pub tip: u64,
+}
+
+#[event]
+pub struct HarvestEvent {
+    pub reward_mint: Pubkey,
+    pub reward_harvested: u64,
+    pub reward_swapped: u64,
+    pub usdc_received: u64,
+}
+
+#[event]
+pub struct SwapProgramUpdateEvent {
+    pub swap_program: Pubkey,
 }
 
 #[event]
 pub struct ViewEvent {
     pub user: Pubkey,
     pub user_yeild: u64,
-}
\ No newline at end of file
+    /// Kamino farm rewards the aggregator has earned but not harvested yet
+    pub unclaimed_rewards: Vec<UnclaimedReward>,
+    pub juplend_rewards_apr_bps: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnclaimedReward {
+    pub reward_mint: Pubkey,
+    /// Unclaimed by the whole aggregator
+    pub amount: u64,
+    /// The user's share of `amount`, pro rata to their cUSDC
+    pub user_amount: u64,
+}
+#[event]
+pub struct OperatorApprovalEvent {
+    pub owner: Pubkey,
+    pub operator: Pubkey,
+    pub allowance: u64,
+}
+
+#[event]
+pub struct AccessModeUpdateEvent {
+    pub access_mode: AccessMode,
+    pub merkle_root: [u8; 32],
+}
+
+#[event]
+pub struct AllowlistUpdateEvent {
+    pub wallet: Pubkey,
+    pub allowed: bool,
+}
+
+#[event]
+pub struct HoldingPeriodUpdateEvent {
+    pub min_holding_slots: u64,
+}
+
+#[event]
+pub struct ShareMetadataUpdateEvent {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[event]
+pub struct ConfigMigrationEvent {
+    pub from_version: u8,
+    pub to_version: u8,
+    pub account_size: u64,
+}
+
+#[event]
+pub struct FarmBufferUpdateEvent {
+    pub farm_liquid_buffer_bps: u16,
+}
+
+#[event]
+pub struct FarmUnstakeEvent {
+    /// Shares moved back into the config's shares ATA after their cooldown
+    pub shares_withdrawn: u64,
+    /// Shares unstaked now, withdrawable once the cooldown is over
+    pub shares_unstaked: u64,
+    /// Unstaked shares above the liquid buffer staked back into the farm
+    pub shares_restaked: u64,
+    pub withdrawal_cooldown: u64,
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+
+use crate::{
+    errors::AggregatorError,
+    states::{AccessMode, AggregatorConfig, Allowlist},
+};
+
+
+/*
+   Checks that `wallet` may deposit or receive cUSDC under the config's access mode.
+
+   - Permissionless: always allowed.
+   - Allowlist: `allowlist` must be the wallet's PDA (seeds are checked by the accounts struct).
+   - MerkleRoot: `proof` must lead from sha256(wallet) to `config.merkle_root`.
+*/
+pub fn check_access(
+    config: &AggregatorConfig,
+    wallet: &Pubkey,
+    allowlist: Option<&Allowlist>,
+    proof: &[[u8; 32]],
+) -> Result<()> {
+    let allowed = match config.access_mode {
+        AccessMode::Permissionless => true,
+        AccessMode::Allowlist => allowlist.is_some_and(|entry| entry.wallet == *wallet),
+        AccessMode::MerkleRoot => verify_merkle_proof(proof, &config.merkle_root, wallet_leaf(wallet)),
+    };
+
+    if !allowed {
+        msg!("Wallet {} is not allowed in {:?} mode", wallet, config.access_mode);
+        return Err(AggregatorError::NotAllowlisted.into());
+    }
+
+    Ok(())
+}
+
+
+pub fn wallet_leaf(wallet: &Pubkey) -> [u8; 32] {
+    hashv(&[wallet.as_ref()]).to_bytes()
+}
+
+
+/// Pairs are hashed in sorted order, so proofs don't carry left/right flags
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+
+    computed == *root
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&a, &b]).to_bytes()
+        } else {
+            hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn test_merkle_proof() {
+        let wallets: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets.iter().map(wallet_leaf).collect();
+
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        assert!(verify_merkle_proof(&[leaves[1], right], &root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[2], left], &root, leaves[3]));
+
+        // Wrong sibling or a wallet outside the tree
+        assert!(!verify_merkle_proof(&[leaves[2], right], &root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1], right], &root, wallet_leaf(&Pubkey::new_unique())));
+    }
+}
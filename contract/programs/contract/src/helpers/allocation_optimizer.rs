@@ -0,0 +1,322 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{BPS_BASE, EXCHANGE_PRICES_PRECISION, MIN_OPERATE_AMOUNT, UTILIZATION_PRECISION},
+    errors::AggregatorError,
+    helpers::kamino::get_kamino_apy::interpolate_borrow_rate,
+    states::kamino::CurvePoint,
+};
+
+
+/// Rates in this module are scaled so that 1e12 = 100%
+pub const RATE_PRECISION: u128 = EXCHANGE_PRICES_PRECISION;
+
+
+/*
+   JupLend supply rate as a function of how much we hold.
+
+   The rate model account isn't decoded, so the borrow rate is taken as linear in utilization
+   through the current (utilization, borrow_rate) point. Rewards are a fixed yearly amount
+   spread over the fToken supply, so they shrink as supply grows.
+*/
+#[derive(Debug, Clone)]
+pub struct JupLendRateModel {
+    pub total_supply: u128,
+    pub total_borrow: u128,
+    /// 1e4 = 100%
+    pub borrow_rate: u16,
+    /// 1e4 = 100%
+    pub utilization: u16,
+    /// 1e4 = 100%
+    pub fee_on_interest: u16,
+    /// Rewards rate at the current supply, RATE_PRECISION scaled
+    pub rewards_rate: u128,
+    /// What we hold in JupLend now, already part of `total_supply`
+    pub current_balance: u64,
+}
+
+impl JupLendRateModel {
+    pub fn supply_rate_at(&self, balance: u64) -> u128 {
+        let total_supply = self
+            .total_supply
+            .saturating_sub(self.current_balance as u128)
+            .saturating_add(balance as u128);
+
+        if total_supply == 0 {
+            return 0;
+        }
+
+        let utilization = (self.total_borrow.saturating_mul(RATE_PRECISION) / total_supply).min(RATE_PRECISION);
+
+        let borrow_rate = if self.utilization == 0 {
+            self.borrow_rate as u128 * RATE_PRECISION / UTILIZATION_PRECISION
+        } else {
+            // borrow_rate / utilization is the slope at the current point
+            (self.borrow_rate as u128).saturating_mul(utilization) / self.utilization as u128
+        };
+
+        let supply_rate = borrow_rate.saturating_mul(utilization) / RATE_PRECISION
+            * UTILIZATION_PRECISION.saturating_sub(self.fee_on_interest as u128)
+            / UTILIZATION_PRECISION;
+
+        let rewards_rate = self.rewards_rate.saturating_mul(self.total_supply) / total_supply;
+
+        supply_rate.saturating_add(rewards_rate)
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct KaminoReserveModel {
+    pub available_amount: u64,
+    pub borrowed_amount: u128,
+    pub protocol_take_rate_pct: u8,
+    pub borrow_rate_curve: [CurvePoint; 11],
+    /// USDC the Kamino vault holds in this reserve
+    pub invested: u64,
+}
+
+impl KaminoReserveModel {
+    pub fn supply_rate_with(&self, change: i128) -> u128 {
+        let total_liquidity = (self.available_amount as i128)
+            .saturating_add(self.borrowed_amount as i128)
+            .saturating_add(change)
+            .max(self.borrowed_amount as i128) as u128;
+
+        if total_liquidity == 0 {
+            return 0;
+        }
+
+        let utilization = self.borrowed_amount.saturating_mul(RATE_PRECISION) / total_liquidity;
+        let borrow_rate = interpolate_borrow_rate(&self.borrow_rate_curve, utilization, RATE_PRECISION);
+
+        borrow_rate.saturating_mul(utilization) / RATE_PRECISION
+            * 100u128.saturating_sub(self.protocol_take_rate_pct as u128)
+            / 100
+    }
+}
+
+
+/*
+   Kamino vault supply rate as a function of how much we hold.
+
+   The vault spreads a change in deposits over its reserves in proportion to what it already
+   holds in each (evenly when it holds nothing), and idle `token_available` earns nothing.
+   The rate is what depositors keep after the vault's performance and management fees.
+*/
+#[derive(Debug, Clone)]
+pub struct KaminoVaultRateModel {
+    pub token_available: u64,
+    pub reserves: Vec<KaminoReserveModel>,
+    /// What we hold in the Kamino vault now
+    pub current_balance: u64,
+    /// 1e4 = 100%, of the interest
+    pub performance_fee_bps: u64,
+    /// 1e4 = 100%, of AUM per year
+    pub management_fee_bps: u64,
+}
+
+impl KaminoVaultRateModel {
+    pub fn supply_rate_at(&self, balance: u64) -> u128 {
+        let gross_rate = self.gross_supply_rate_at(balance);
+
+        (gross_rate * (BPS_BASE as u128).saturating_sub(self.performance_fee_bps as u128) / BPS_BASE as u128)
+            .saturating_sub(self.management_fee_bps as u128 * RATE_PRECISION / BPS_BASE as u128)
+    }
+
+    fn gross_supply_rate_at(&self, balance: u64) -> u128 {
+        if self.reserves.is_empty() {
+            return 0;
+        }
+
+        let change = balance as i128 - self.current_balance as i128;
+        let total_invested: u128 = self.reserves.iter().map(|reserve| reserve.invested as u128).sum();
+
+        let mut weighted_rate: u128 = 0;
+        let mut total_weight: u128 = self.token_available as u128;
+
+        for reserve in self.reserves.iter() {
+            let reserve_change = if total_invested == 0 {
+                change / self.reserves.len() as i128
+            } else {
+                change * reserve.invested as i128 / total_invested as i128
+            };
+
+            let invested = (reserve.invested as i128 + reserve_change).max(0) as u128;
+            weighted_rate = weighted_rate.saturating_add(invested.saturating_mul(reserve.supply_rate_with(reserve_change)));
+            total_weight = total_weight.saturating_add(invested);
+        }
+
+        if total_weight == 0 {
+            // Nothing invested yet, a deposit lands evenly on the reserves
+            return self.reserves.iter().map(|reserve| reserve.supply_rate_with(0)).sum::<u128>()
+                / self.reserves.len() as u128;
+        }
+
+        weighted_rate / total_weight
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptimalAllocation {
+    pub juplend_amount: u64,
+    pub kamino_amount: u64,
+    pub juplend_bps: u16,
+    pub juplend_apy_bps: u64,
+    pub kamino_apy_bps: u64,
+}
+
+
+/*
+   Splits `total_usdc` between JupLend and Kamino so that the supply rates after our own
+   move are as close as possible. Putting more into one side lowers its rate and raises
+   the other's, so the gap is monotone in the split and a binary search finds the crossing.
+
+   `juplend_bps_range` bounds the JupLend share (see `juplend_bps_range` in protocol_apy).
+   Pure, so clients can build the models from fetched accounts and preview the split.
+*/
+pub fn optimize_allocation(
+    total_usdc: u64,
+    juplend: &JupLendRateModel,
+    kamino: &KaminoVaultRateModel,
+    juplend_bps_range: (u16, u16),
+) -> Result<OptimalAllocation> {
+    let (lower_bps, upper_bps) = juplend_bps_range;
+    require!(
+        lower_bps <= upper_bps && upper_bps <= BPS_BASE,
+        AggregatorError::InvalidAllocationBounds
+    );
+
+    let to_amount = |bps: u16| -> Result<u64> {
+        Ok((total_usdc as u128)
+            .checked_mul(bps as u128)
+            .ok_or(AggregatorError::MathOverflow)?
+            .checked_div(BPS_BASE as u128)
+            .ok_or(AggregatorError::MathOverflow)? as u64)
+    };
+
+    let juplend_is_better = |juplend_amount: u64| {
+        juplend.supply_rate_at(juplend_amount) >= kamino.supply_rate_at(total_usdc - juplend_amount)
+    };
+
+    let mut low = to_amount(lower_bps)?;
+    let mut high = to_amount(upper_bps)?;
+
+    // Largest JupLend amount at which JupLend still pays at least as much as Kamino
+    let juplend_amount = if !juplend_is_better(low) {
+        low
+    } else if juplend_is_better(high) {
+        high
+    } else {
+        while high - low > MIN_OPERATE_AMOUNT {
+            let mid = low + (high - low) / 2;
+            if juplend_is_better(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    };
+
+    let kamino_amount = total_usdc - juplend_amount;
+
+    let juplend_bps = if total_usdc == 0 {
+        lower_bps
+    } else {
+        ((juplend_amount as u128 * BPS_BASE as u128 / total_usdc as u128) as u16).clamp(lower_bps, upper_bps)
+    };
+
+    Ok(OptimalAllocation {
+        juplend_amount,
+        kamino_amount,
+        juplend_bps,
+        juplend_apy_bps: to_bps(juplend.supply_rate_at(juplend_amount)),
+        kamino_apy_bps: to_bps(kamino.supply_rate_at(kamino_amount)),
+    })
+}
+
+
+fn to_bps(rate: u128) -> u64 {
+    (rate * BPS_BASE as u128 / RATE_PRECISION) as u64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve() -> [CurvePoint; 11] {
+        // 0% -> 0, kink at 80% -> 10%, 100% -> 50%
+        let mut points = [CurvePoint { utilization_rate_bps: 10_000, borrow_rate_bps: 5_000 }; 11];
+        points[0] = CurvePoint { utilization_rate_bps: 0, borrow_rate_bps: 0 };
+        points[1] = CurvePoint { utilization_rate_bps: 8_000, borrow_rate_bps: 1_000 };
+        points
+    }
+
+    fn juplend(borrow_rate: u16) -> JupLendRateModel {
+        JupLendRateModel {
+            total_supply: 10_000_000,
+            total_borrow: 5_000_000,
+            borrow_rate,
+            utilization: 5_000,
+            fee_on_interest: 0,
+            rewards_rate: 0,
+            current_balance: 0,
+        }
+    }
+
+    fn kamino() -> KaminoVaultRateModel {
+        KaminoVaultRateModel {
+            token_available: 0,
+            reserves: vec![KaminoReserveModel {
+                available_amount: 5_000_000,
+                borrowed_amount: 5_000_000,
+                protocol_take_rate_pct: 0,
+                borrow_rate_curve: curve(),
+                invested: 0,
+            }],
+            current_balance: 0,
+            performance_fee_bps: 0,
+            management_fee_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_supply_rate_drops_as_we_add_liquidity() {
+        let juplend = juplend(1_000);
+        assert!(juplend.supply_rate_at(5_000_000) < juplend.supply_rate_at(0));
+
+        let kamino = kamino();
+        assert!(kamino.supply_rate_at(5_000_000) < kamino.supply_rate_at(0));
+    }
+
+    #[test]
+    fn test_split_equalizes_rates() {
+        let allocation = optimize_allocation(4_000_000, &juplend(1_000), &kamino(), (0, BPS_BASE)).unwrap();
+
+        assert_eq!(allocation.juplend_amount + allocation.kamino_amount, 4_000_000);
+        // Neither side is maxed out and the post-move rates meet
+        assert!(allocation.juplend_amount > 0 && allocation.kamino_amount > 0);
+        assert!(allocation.juplend_apy_bps.abs_diff(allocation.kamino_apy_bps) <= 1);
+    }
+
+    #[test]
+    fn test_kamino_rate_is_net_of_vault_fees() {
+        let gross = kamino();
+        let net = KaminoVaultRateModel { performance_fee_bps: 1_000, management_fee_bps: 50, ..gross.clone() };
+
+        // 10% of the interest and 0.5% of AUM a year go to the vault
+        let expected = gross.supply_rate_at(1_000_000) * 9 / 10 - 50 * RATE_PRECISION / BPS_BASE as u128;
+        assert_eq!(net.supply_rate_at(1_000_000), expected);
+    }
+
+    #[test]
+    fn test_split_respects_bounds() {
+        // JupLend pays far more, but may hold at most 60%
+        let allocation = optimize_allocation(4_000_000, &juplend(5_000), &kamino(), (2_000, 6_000)).unwrap();
+        assert_eq!(allocation.juplend_bps, 6_000);
+        assert_eq!(allocation.juplend_amount, 2_400_000);
+    }
+}
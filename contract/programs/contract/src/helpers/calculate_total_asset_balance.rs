@@ -1,7 +1,7 @@
 use std::slice::Iter;
 
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount};
 
 use crate::{ 
     errors::AggregatorError, 
@@ -9,13 +9,28 @@ use crate::{
         juplend::get_juplend_balance::get_juplend_balance, 
         kamino::{
             get_kamino_balance::get_kamino_balance, 
-            get_kamino_farm_active_balance
-        }
+            get_kamino_farm_active_balance,
+            kamino_reserve_accounts::{get_reserve_account_infos, KLEND_PROGRAM_INDEX},
+        },
+        klend::{
+            get_klend_balance::get_klend_balance,
+            klend_reserve_accounts::{check_klend_reserve_accounts, parse_klend_reserve_accounts},
+        },
+        save::{
+            get_save_balance::get_save_balance,
+            save_reserve_accounts::{check_save_reserve_accounts, parse_save_reserve_accounts},
+        },
+        marginfi::{
+            get_marginfi_balance::get_marginfi_balance,
+            marginfi_accounts::{check_marginfi_accounts, parse_marginfi_accounts},
+        },
+        refresh_protocol_state::{refresh_protocol_state, refresh_save_reserve},
+        segment_offsets::SegmentOffsets,
     }, 
     states::{
-        ReserveWithdrawAccounts, 
         lending::Lending, 
-        lending_rewards_rate_model::LendingRewardsRateModel
+        lending_rewards_rate_model::LendingRewardsRateModel,
+        AggregatorConfig,
     }
 };
 
@@ -28,12 +43,26 @@ pub fn skip_accounts<'info>(account_iter: &mut Iter<'info, AccountInfo<'info>>,
 }
 
 
+/*
+   USDC held in each protocol, in [JupLend, Kamino, MarginFi, klend reserve, Save reserve] order.
+
+   The MarginFi segment is only priced when it holds the marginfi account and bank pinned in
+   the config, and reads as 0 while the config has no marginfi account. Same for the klend
+   and Save segments, which have to hold the config's reserve and cToken ATA. Save's interest
+   can't be estimated forward, so its reserve is refreshed here when the refresh block didn't
+   and the reserve is writable, view simulations pass it read-only and lag.
+*/
 pub fn calculate_total_asset_balance<'info>(
+    config: &AggregatorConfig,
     remaining_accounts: &'info [AccountInfo<'info>]
 ) -> Result<Vec<u64>> {
     
     msg!("calculate_total_asset_balance: Starting with {} remaining accounts", remaining_accounts.len());
 
+    // Exact prices when the caller opted in with the refresh accounts, estimates otherwise
+    let refreshed = refresh_protocol_state(remaining_accounts)?;
+    msg!("Pricing mode: {}", if refreshed { "refreshed" } else { "estimated" });
+
     let mut account_iter = remaining_accounts.iter();
     
 
@@ -44,7 +73,7 @@ pub fn calculate_total_asset_balance<'info>(
     let jup_rewards_acc = account_iter.next().ok_or(AggregatorError::MissingAccount)?;
     let jup_lending_rewards_rate_model = LendingRewardsRateModel::try_deserialize(&mut &jup_rewards_acc.try_borrow_data()?[..])?;
     
-    skip_accounts(&mut account_iter, 1)?;
+    let jup_f_token_mint = InterfaceAccount::<'info, Mint>::try_from(account_iter.next().ok_or(AggregatorError::MissingAccount)?)?;
     let jup_vault_ftokens = InterfaceAccount::<'info, TokenAccount>::try_from(account_iter.next().ok_or(AggregatorError::MissingAccount)?)?;
     
     skip_accounts(&mut account_iter, 1)?;
@@ -60,35 +89,21 @@ pub fn calculate_total_asset_balance<'info>(
     
     let kamino_user_shares_ata = InterfaceAccount::<'info, TokenAccount>::try_from(account_iter.next().ok_or(AggregatorError::MissingAccount)?)?;
     
-    skip_accounts(&mut account_iter, 1)?;
+    // One group per reserve in the vault's allocation strategy, from index 33 on
+    let reserve_accounts = get_reserve_account_infos(remaining_accounts)?;
 
-    // Reserve 1 (7 accounts)
-    let reserve_1 = ReserveWithdrawAccounts {
-        reserve: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        ctoken_vault: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        lending_market: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        lending_market_authority: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        reserve_liquidity_supply: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        reserve_collateral_mint: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        reserve_collateral_token_program: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-    };
-    
-    // Reserve 2 (7 accounts)
-    let reserve_2 = ReserveWithdrawAccounts {
-        reserve: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        ctoken_vault: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        lending_market: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        lending_market_authority: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        reserve_liquidity_supply: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        reserve_collateral_mint: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        reserve_collateral_token_program: account_iter.next().ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-    };
+    // Then the MarginFi, klend reserve and Save reserve segments
+    let offsets = SegmentOffsets::new(reserve_accounts.len());
+    let marginfi_accounts = parse_marginfi_accounts(remaining_accounts, &offsets)?;
+    let klend_accounts = parse_klend_reserve_accounts(remaining_accounts, &offsets)?;
+    let save_accounts = parse_save_reserve_accounts(remaining_accounts, &offsets)?;
 
     let juplend_balance = get_juplend_balance(
         jup_supply_token_reserves_liquidity,
         &jup_lending,
         &jup_lending_rewards_rate_model,
         &jup_vault_ftokens,
+        jup_f_token_mint.supply,
     )?;
 
     msg!("Calculated Juplend balance: {}", juplend_balance);
@@ -103,7 +118,7 @@ pub fn calculate_total_asset_balance<'info>(
     let kamino_balance = get_kamino_balance(
         kamino_vault_state,
         kamino_farm_active_balance,
-        &[reserve_1.reserve.clone(), reserve_2.reserve.clone()],
+        &reserve_accounts,
         Some(current_slot),
     )?;
 
@@ -112,8 +127,39 @@ pub fn calculate_total_asset_balance<'info>(
     msg!("Calculated Kamino balance: {}", kamino_balance);
     msg!("Calculated Kamino farm active balance: {}", kamino_farm_active_balance);
 
+    let marginfi_balance = if check_marginfi_accounts(config, &marginfi_accounts)? {
+        get_marginfi_balance(&marginfi_accounts.marginfi_account, &marginfi_accounts.bank)?
+    } else {
+        0
+    };
+
+    msg!("Calculated MarginFi balance: {}", marginfi_balance);
+
+    let klend_program = remaining_accounts.get(KLEND_PROGRAM_INDEX).ok_or(AggregatorError::MissingAccount)?;
+    let klend_balance = if check_klend_reserve_accounts(config, &klend_accounts, klend_program)? {
+        get_klend_balance(&klend_accounts.ctoken_account, &klend_accounts.reserve, Some(current_slot))?
+    } else {
+        0
+    };
+
+    msg!("Calculated klend reserve balance: {}", klend_balance);
+
+    let save_balance = if check_save_reserve_accounts(config, &save_accounts)? {
+        if !refreshed && save_accounts.reserve.is_writable {
+            refresh_save_reserve(&save_accounts.save_program, &save_accounts.reserve, &save_accounts.oracles)?;
+        }
+        get_save_balance(&save_accounts.ctoken_account, &save_accounts.reserve)?
+    } else {
+        0
+    };
+
+    msg!("Calculated Save reserve balance: {}", save_balance);
+
     Ok(vec![
         juplend_balance,
         kamino_balance,
+        marginfi_balance,
+        klend_balance,
+        save_balance,
     ])
 }
\ No newline at end of file
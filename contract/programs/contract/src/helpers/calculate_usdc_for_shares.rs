@@ -1,14 +1,16 @@
+use anchor_lang::prelude::*;
 
+use crate::helpers::share_conversion::mul_div;
+
+
+/// USDC paid out for `shares`, rounded down in favour of the vault
 pub fn calculate_usdc_for_shares(
     shares: u64,
     total_shares: u64,
     total_deposits: u64,
-) -> u64 {
+) -> Result<u64> {
     if total_shares == 0 {
-        return 0;
+        return Ok(0);
     }
-    shares
-        .checked_mul(total_deposits)
-        .and_then(|v| v.checked_div(total_shares))
-        .unwrap_or(0)
-}
\ No newline at end of file
+    mul_div(shares, total_deposits, total_shares)
+}
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CONFIG_VERSION, DEFAULT_FARM_LIQUID_BUFFER_BPS},
+    errors::AggregatorError,
+    states::AggregatorConfig,
+};
+
+
+/// Size of the config account at the current layout, discriminator included
+pub const CONFIG_ACCOUNT_SIZE: usize = 8 + AggregatorConfig::INIT_SPACE;
+
+
+/*
+   Brings a config read from an older layout up to CONFIG_VERSION and returns the version
+   it started from.
+
+   Fields are only ever appended or carved out of `reserved`, so once the account has been
+   resized with zeroed bytes an older config deserializes with every new field set to 0.
+   Each step replaces those zeroes with the field's default, where 0 isn't one.
+*/
+pub fn apply_config_migrations(config: &mut AggregatorConfig) -> Result<u8> {
+    let from_version = config.version;
+    require!(from_version < CONFIG_VERSION, AggregatorError::ConfigAlreadyMigrated);
+
+    if config.version < 1 {
+        // v1 added `version` and `reserved`, zero is already the default for both
+        config.version = 1;
+    }
+
+    if config.version < 2 {
+        // v2 carved `farm_liquid_buffer_bps` out of `reserved`
+        config.farm_liquid_buffer_bps = DEFAULT_FARM_LIQUID_BUFFER_BPS;
+        config.version = 2;
+    }
+
+    if config.version < 3 {
+        // v3 carved `kamino_interest_snapshot` out of `reserved`, zeroes mean no snapshot yet
+        config.version = 3;
+    }
+
+    if config.version < 4 {
+        // v4 carved `marginfi_account` and `marginfi_allocation_bps` out of `reserved`,
+        // zeroes mean no marginfi account and no MarginFi target
+        config.version = 4;
+    }
+
+    if config.version < 5 {
+        // v5 carved `klend_reserve` and `klend_allocation_bps` out of `reserved`,
+        // zeroes mean no klend reserve and no target for it
+        config.version = 5;
+    }
+
+    if config.version < 6 {
+        // v6 carved `save_reserve` and `save_allocation_bps` out of `reserved` and grew it
+        // back to 64 bytes, zeroes mean no Save reserve and no target for it
+        config.version = 6;
+    }
+
+    if config.version < 7 {
+        // v7 carved `marginfi_bank` out of `reserved`, zeroes mean no bank pinned. A config that
+        // already owns a marginfi account can't price it until `init_marginfi_account` pins one
+        config.version = 7;
+    }
+
+    Ok(from_version)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::{AccessMode, AllocationBounds, AllocationMode, KaminoInterestSnapshot};
+
+    /// v7 carved `marginfi_bank` out of `reserved`, the size didn't change
+    const LEGACY_CONFIG_SIZE_V6: usize = CONFIG_ACCOUNT_SIZE;
+    /// v6 grew `reserved` back to 64 bytes
+    const LEGACY_CONFIG_SIZE_V5: usize = LEGACY_CONFIG_SIZE_V6 - 64;
+    /// v2 to v5 only carved fields out of `reserved`
+    const LEGACY_CONFIG_SIZE_V4: usize = LEGACY_CONFIG_SIZE_V5;
+    const LEGACY_CONFIG_SIZE_V3: usize = LEGACY_CONFIG_SIZE_V4;
+    const LEGACY_CONFIG_SIZE_V2: usize = LEGACY_CONFIG_SIZE_V3;
+    const LEGACY_CONFIG_SIZE_V1: usize = LEGACY_CONFIG_SIZE_V2;
+    /// v1 appended `version` and 128 bytes of `reserved`, an unversioned account ends at `min_holding_slots`
+    const LEGACY_CONFIG_SIZE_V0: usize = LEGACY_CONFIG_SIZE_V1 - 1 - 128;
+
+    const LEGACY_CONFIG_SIZES: [usize; CONFIG_VERSION as usize] = [
+        LEGACY_CONFIG_SIZE_V0,
+        LEGACY_CONFIG_SIZE_V1,
+        LEGACY_CONFIG_SIZE_V2,
+        LEGACY_CONFIG_SIZE_V3,
+        LEGACY_CONFIG_SIZE_V4,
+        LEGACY_CONFIG_SIZE_V5,
+        LEGACY_CONFIG_SIZE_V6,
+    ];
+
+    fn config() -> AggregatorConfig {
+        AggregatorConfig {
+            authority: Pubkey::new_unique(),
+            usdc_mint: Pubkey::new_unique(),
+            cusdc_mint: Pubkey::new_unique(),
+            vault_usdc: Pubkey::new_unique(),
+            juplend_allocation_bps: 6000,
+            kamino_allocation_bps: 4000,
+            total_deposits: 0,
+            bump: 255,
+            drift_threshold_bps: 500,
+            rebalance_cooldown_slots: 0,
+            last_rebalance_slot: 0,
+            keeper_tip: 0,
+            allocation_mode: AllocationMode::Fixed,
+            juplend_bounds: AllocationBounds { min_bps: 0, max_bps: 10000 },
+            kamino_bounds: AllocationBounds { min_bps: 0, max_bps: 10000 },
+            swap_program: Pubkey::new_unique(),
+            access_mode: AccessMode::Permissionless,
+            merkle_root: [0; 32],
+            min_holding_slots: 150,
+            version: CONFIG_VERSION,
+            farm_liquid_buffer_bps: DEFAULT_FARM_LIQUID_BUFFER_BPS,
+            kamino_interest_snapshot: KaminoInterestSnapshot::default(),
+            marginfi_account: Pubkey::new_unique(),
+            marginfi_allocation_bps: 1000,
+            klend_reserve: Pubkey::new_unique(),
+            klend_allocation_bps: 500,
+            save_reserve: Pubkey::new_unique(),
+            save_allocation_bps: 500,
+            marginfi_bank: Pubkey::new_unique(),
+            reserved: [0; 32],
+        }
+    }
+
+    #[test]
+    fn test_migrate_unversioned_config() {
+        let current = config();
+        let mut data = Vec::new();
+        current.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), CONFIG_ACCOUNT_SIZE);
+
+        // Resize zero-extends the unversioned account
+        data.truncate(LEGACY_CONFIG_SIZE_V0);
+        data.resize(CONFIG_ACCOUNT_SIZE, 0);
+
+        let mut legacy = AggregatorConfig::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(legacy.version, 0);
+        assert_eq!(legacy.min_holding_slots, 150);
+
+        assert_eq!(apply_config_migrations(&mut legacy).unwrap(), 0);
+        assert_eq!(legacy.version, CONFIG_VERSION);
+        assert_eq!(legacy.farm_liquid_buffer_bps, DEFAULT_FARM_LIQUID_BUFFER_BPS);
+        assert_eq!(legacy.marginfi_account, Pubkey::default());
+        assert_eq!(legacy.marginfi_allocation_bps, 0);
+        assert_eq!(legacy.klend_reserve, Pubkey::default());
+        assert_eq!(legacy.klend_allocation_bps, 0);
+        assert_eq!(legacy.save_reserve, Pubkey::default());
+        assert_eq!(legacy.save_allocation_bps, 0);
+        assert_eq!(legacy.marginfi_bank, Pubkey::default());
+        assert!(apply_config_migrations(&mut legacy).is_err());
+    }
+
+    /// `config()` as written at `version`, fields added later were still zeroed `reserved` bytes
+    fn legacy_config(version: u8) -> AggregatorConfig {
+        let mut config = config();
+        config.version = version;
+        config.farm_liquid_buffer_bps = 700;
+        if version < 2 {
+            config.farm_liquid_buffer_bps = 0;
+        }
+        if version < 3 {
+            config.kamino_interest_snapshot = KaminoInterestSnapshot::default();
+        }
+        if version < 4 {
+            config.marginfi_account = Pubkey::default();
+            config.marginfi_allocation_bps = 0;
+        }
+        if version < 5 {
+            config.klend_reserve = Pubkey::default();
+            config.klend_allocation_bps = 0;
+        }
+        if version < 6 {
+            config.save_reserve = Pubkey::default();
+            config.save_allocation_bps = 0;
+        }
+        if version < 7 {
+            config.marginfi_bank = Pubkey::default();
+        }
+        config
+    }
+
+    #[test]
+    fn test_migrate_each_legacy_version() {
+        for (version, size) in LEGACY_CONFIG_SIZES.iter().enumerate() {
+            let version = version as u8;
+            let legacy = legacy_config(version);
+            let mut data = Vec::new();
+            legacy.try_serialize(&mut data).unwrap();
+            data.truncate(*size);
+            data.resize(CONFIG_ACCOUNT_SIZE, 0);
+
+            let mut migrated = AggregatorConfig::try_deserialize(&mut &data[..]).unwrap();
+            assert_eq!(migrated.version, version);
+            assert_eq!(apply_config_migrations(&mut migrated).unwrap(), version);
+
+            // Only the v2 buffer default replaces zeroes, every other field comes through as written
+            let mut expected = legacy;
+            expected.version = CONFIG_VERSION;
+            if version < 2 {
+                expected.farm_liquid_buffer_bps = DEFAULT_FARM_LIQUID_BUFFER_BPS;
+            }
+
+            let (mut migrated_data, mut expected_data) = (Vec::new(), Vec::new());
+            migrated.try_serialize(&mut migrated_data).unwrap();
+            expected.try_serialize(&mut expected_data).unwrap();
+            assert_eq!(migrated_data, expected_data, "migrating from v{}", version);
+        }
+    }
+}
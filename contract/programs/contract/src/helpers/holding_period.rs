@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::AggregatorError, states::UserPosition};
+
+
+/*
+   Interest is extrapolated up to the current slot when assets are priced, so a deposit made
+   right before a JupLend exchange price jump or a Kamino accrual could be withdrawn right
+   after with yield it never earned. Freshly minted cUSDC is locked for `min_holding_slots`.
+
+   The lock sits on the receiver's position and only covers the shares minted while it runs,
+   so depositing for a second wallet doesn't dodge it and a deposit from a stranger never
+   freezes shares the receiver already held. cUSDC transferred to another wallet still leaves
+   the lock behind, this bounds casual round trips rather than every path.
+*/
+pub fn lock_minted_shares(position: &mut UserPosition, minted: u64, min_holding_slots: u64, current_slot: u64) -> Result<()> {
+    let locked = locked_shares(position, min_holding_slots, current_slot);
+
+    position.locked_shares = locked.checked_add(minted).ok_or(AggregatorError::MathOverflow)?;
+    position.last_deposit_slot = current_slot;
+
+    Ok(())
+}
+
+
+/// Shares of the position that can't be burned yet, 0 once the holding period has passed
+pub fn locked_shares(position: &UserPosition, min_holding_slots: u64, current_slot: u64) -> u64 {
+    let unlock_slot = position.last_deposit_slot.saturating_add(min_holding_slots);
+
+    if min_holding_slots > 0 && current_slot < unlock_slot {
+        position.locked_shares
+    } else {
+        0
+    }
+}
+
+
+/// Burning `shares` out of `balance` has to leave the locked shares in place
+pub fn check_holding_period(
+    position: &UserPosition,
+    min_holding_slots: u64,
+    current_slot: u64,
+    balance: u64,
+    shares: u64,
+) -> Result<()> {
+    let locked = locked_shares(position, min_holding_slots, current_slot);
+
+    if shares > balance.saturating_sub(locked) {
+        msg!(
+            "{} cUSDC minted at slot {} is locked until slot {}",
+            locked,
+            position.last_deposit_slot,
+            position.last_deposit_slot.saturating_add(min_holding_slots)
+        );
+        return Err(AggregatorError::HoldingPeriodActive.into());
+    }
+
+    Ok(())
+}
+
+
+/// The position PDA, None when the owner never received a deposit
+pub fn read_user_position(position_account: &AccountInfo) -> Result<Option<UserPosition>> {
+    if position_account.owner != &crate::ID || position_account.data_is_empty() {
+        return Ok(None);
+    }
+
+    UserPosition::try_deserialize(&mut &position_account.try_borrow_data()?[..]).map(Some)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(last_deposit_slot: u64, locked_shares: u64) -> UserPosition {
+        UserPosition { owner: Pubkey::new_unique(), last_deposit_slot, bump: 255, locked_shares }
+    }
+
+    #[test]
+    fn test_holding_period() {
+        // Same slot round trip is rejected, the first slot after the period is fine
+        assert!(check_holding_period(&position(100, 500), 10, 100, 500, 500).is_err());
+        assert!(check_holding_period(&position(100, 500), 10, 109, 500, 1).is_err());
+        assert!(check_holding_period(&position(100, 500), 10, 110, 500, 500).is_ok());
+
+        // Disabled
+        assert!(check_holding_period(&position(100, 500), 0, 100, 500, 500).is_ok());
+    }
+
+    #[test]
+    fn test_lock_leaves_earlier_shares_free() {
+        // 1_000 held before a stranger deposits 500 for the owner, only those 500 are locked
+        let mut position = position(0, 0);
+        lock_minted_shares(&mut position, 500, 10, 100).unwrap();
+        assert!(check_holding_period(&position, 10, 100, 1_500, 1_000).is_ok());
+        assert!(check_holding_period(&position, 10, 100, 1_500, 1_001).is_err());
+    }
+
+    #[test]
+    fn test_lock_accumulates_until_it_expires() {
+        let mut position = position(0, 0);
+        lock_minted_shares(&mut position, 500, 10, 100).unwrap();
+        lock_minted_shares(&mut position, 300, 10, 105).unwrap();
+        assert_eq!(locked_shares(&position, 10, 114), 800);
+        assert_eq!(locked_shares(&position, 10, 115), 0);
+
+        // Shares from an expired lock don't carry over
+        lock_minted_shares(&mut position, 200, 10, 120).unwrap();
+        assert_eq!(locked_shares(&position, 10, 120), 200);
+    }
+}
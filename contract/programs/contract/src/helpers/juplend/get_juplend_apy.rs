@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{BPS_BASE, EXCHANGE_PRICES_PRECISION, REWARDS_RATE_PRECISION, UTILIZATION_PRECISION},
+    errors::AggregatorError,
+    helpers::{
+        juplend::get_juplend_balance::get_rewards_rate,
+        token_reserve_helper::load_token_reserve,
+    },
+    states::juplend::{lending::Lending, lending_rewards_rate_model::LendingRewardsRateModel},
+};
+
+
+/*
+   Supply APY of the JupLend fToken in bps.
+
+   supply_rate = borrow_rate * utilization * (1 - fee_on_interest)   (all in 1e4 precision)
+   plus the fToken rewards rate, which the lending program pays on top of the liquidity yield.
+*/
+pub fn get_juplend_supply_apy_bps(
+    token_reserve: &AccountInfo,
+    lending: &Lending,
+    rewards_rate_model: &LendingRewardsRateModel,
+    f_token_supply: u64,
+) -> Result<u64> {
+    let (borrow_rate, fee_on_interest, utilization) = {
+        let data = token_reserve.try_borrow_data()?;
+        let reserve = load_token_reserve(&data)?;
+        (reserve.borrow_rate, reserve.fee_on_interest, reserve.last_utilization)
+    };
+
+    let supply_rate_bps = calculate_juplend_supply_rate_bps(borrow_rate, fee_on_interest, utilization)?;
+
+    let rewards_rate_bps = get_juplend_rewards_apr_bps(lending, rewards_rate_model, f_token_supply)?;
+
+    msg!("JupLend supply rate: {} bps, rewards rate: {} bps", supply_rate_bps, rewards_rate_bps);
+
+    supply_rate_bps
+        .checked_add(rewards_rate_bps)
+        .ok_or(AggregatorError::MathOverflow.into())
+}
+
+
+/// fToken rewards APR in bps, paid by the lending program on top of the liquidity yield
+pub fn get_juplend_rewards_apr_bps(
+    lending: &Lending,
+    rewards_rate_model: &LendingRewardsRateModel,
+    f_token_supply: u64,
+) -> Result<u64> {
+    let total_assets = (lending.token_exchange_price as u128)
+        .checked_mul(f_token_supply as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(EXCHANGE_PRICES_PRECISION)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    let rewards_rate = get_rewards_rate(
+        u64::try_from(total_assets).unwrap_or(u64::MAX),
+        rewards_rate_model,
+        Clock::get()?.unix_timestamp as u64,
+    )?;
+
+    calculate_rewards_rate_bps(rewards_rate.rate)
+}
+
+
+pub fn calculate_juplend_supply_rate_bps(borrow_rate: u16, fee_on_interest: u16, utilization: u16) -> Result<u64> {
+    let supply_rate = (borrow_rate as u128)
+        .checked_mul(utilization as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_mul(UTILIZATION_PRECISION.saturating_sub(fee_on_interest as u128))
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(UTILIZATION_PRECISION * UTILIZATION_PRECISION)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    Ok(supply_rate as u64)
+}
+
+
+/// Rewards rate is scaled by REWARDS_RATE_PRECISION (1e12 = 1%)
+pub fn calculate_rewards_rate_bps(rewards_rate: u128) -> Result<u64> {
+    let rate_bps = rewards_rate
+        .checked_mul(BPS_BASE as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(REWARDS_RATE_PRECISION)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    Ok(rate_bps as u64)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supply_rate() {
+        // 8% borrow at 75% utilization with a 10% fee -> 5.4%
+        assert_eq!(calculate_juplend_supply_rate_bps(800, 1000, 7500).unwrap(), 540);
+        assert_eq!(calculate_juplend_supply_rate_bps(800, 1000, 0).unwrap(), 0);
+    }
+}
@@ -4,30 +4,34 @@ use anchor_spl::token_interface::{TokenAccount};
 use crate::{
     errors::AggregatorError,
     helpers::{
-        token_reserve_helper::get_supply_exchange_price,
+        token_reserve_helper::load_token_reserve,
     },
     states::juplend::{lending::Lending, lending_rewards_rate_model::LendingRewardsRateModel}
 };
 
-use crate::constants::{EXCHANGE_PRICES_PRECISION, SECONDS_PER_YEAR, MAX_REWARDS_RATE};
+use crate::constants::{EXCHANGE_PRICES_PRECISION, SECONDS_PER_YEAR, MAX_REWARDS_RATE, REWARDS_RATE_PRECISION};
 
 
 
+/// `f_token_supply` is the fToken mint supply, the lending prices rewards on its total assets
 pub fn get_juplend_balance<'info>(
     token_reserve: &AccountInfo<'info>,
     lending: &Lending,
     rewards_rate_model: &LendingRewardsRateModel,
     fusdc_token_account: &InterfaceAccount<'info, TokenAccount>,
+    f_token_supply: u64,
 ) -> Result<u64> {
-    let supply_exchange_price = get_supply_exchange_price(&token_reserve.data.borrow());
+    let supply_exchange_price = load_token_reserve(&token_reserve.try_borrow_data()?)?.supply_exchange_price;
     msg!("Supply exchange price: {}", supply_exchange_price);
     let program_ftoken = fusdc_token_account.amount;
     msg!("Program ftoken: {}", program_ftoken);
     let juplend_balance = convert_to_asset(
         program_ftoken,
+        f_token_supply,
         supply_exchange_price,
         lending,
-        rewards_rate_model
+        rewards_rate_model,
+        Clock::get()?.unix_timestamp as u64,
     )?;
     
     Ok(juplend_balance as u64)
@@ -40,35 +44,42 @@ pub struct RewardsRate {
     pub rewards_start_time: u64,
 }
 
+/*
+   Mirrors the rewards rate model's `get_rate`. The rate is in REWARDS_RATE_PRECISION (1e12 = 1%):
+
+   - below `start_tvl` there are no rewards
+   - after `start_time + duration` the rewards ended
+   - rate = yearly_reward * 1e14 / total_assets, and the lending pays nothing above MAX_REWARDS_RATE
+*/
 pub fn get_rewards_rate<'info>(
     total_assets: u64,
     rewards_rate_model: &LendingRewardsRateModel,
+    current_timestamp: u64,
 ) -> Result<RewardsRate> {
-    if total_assets > rewards_rate_model.start_tvl {
-        return Ok(RewardsRate {
-            rate: 0,
-            rewards_ended: false,
-            rewards_start_time: rewards_rate_model.start_time,
-        })
-    }
-    
+    let no_rewards = |rewards_ended: bool| RewardsRate {
+        rate: 0,
+        rewards_ended,
+        rewards_start_time: rewards_rate_model.start_time,
+    };
+
     // Return 0 rate if total_assets is 0 to avoid division by zero
-    if total_assets == 0 {
-        return Ok(RewardsRate {
-            rate: 0,
-            rewards_ended: false,
-            rewards_start_time: rewards_rate_model.start_time,
-        })
+    if total_assets < rewards_rate_model.start_tvl || total_assets == 0 {
+        return Ok(no_rewards(false));
+    }
+
+    let end_time = rewards_rate_model.start_time.saturating_add(rewards_rate_model.duration);
+    if current_timestamp > end_time {
+        return Ok(no_rewards(true));
     }
     
     msg!("Total assets: {}", total_assets);
     msg!("Yearly reward: {}", rewards_rate_model.yearly_reward);
     
-    // Calculate rate = (yearly_reward * EXCHANGE_PRICES_PRECISION) / total_assets
+    // Calculate rate = (yearly_reward * REWARDS_RATE_PRECISION) / total_assets
     let yearly_reward_u128 = rewards_rate_model.yearly_reward as u128;
     
     let rate = yearly_reward_u128
-                     .checked_mul(EXCHANGE_PRICES_PRECISION)
+                     .checked_mul(REWARDS_RATE_PRECISION)
                      .ok_or(AggregatorError::MathOverflow)?
                      .checked_div(total_assets as u128)
                      .ok_or(AggregatorError::MathOverflow)?;
@@ -76,12 +87,8 @@ pub fn get_rewards_rate<'info>(
     msg!("Calculated rate: {}", rate);
     
     if rate > MAX_REWARDS_RATE {
-        msg!("Rate {} exceeds MAX_REWARDS_RATE {}, capping to max", rate, MAX_REWARDS_RATE);
-        return Ok(RewardsRate {
-            rate: MAX_REWARDS_RATE,
-            rewards_ended: false,
-            rewards_start_time: rewards_rate_model.start_time,
-        })
+        msg!("Rate {} exceeds MAX_REWARDS_RATE {}, no rewards", rate, MAX_REWARDS_RATE);
+        return Ok(no_rewards(false));
     }
     Ok(RewardsRate {
         rate,
@@ -93,45 +100,44 @@ pub fn get_rewards_rate<'info>(
 
 pub fn get_new_exchange_price<'info>(
     supply_exchange_price: u64,
-    total_supply_ctoken: u64,
+    f_token_supply: u64,
     lending: &Lending,
     rewards_rate_model: &LendingRewardsRateModel,
+    current_timestamp: u64,
 ) -> Result<u128> {
     let old_token_exchange_price = lending.token_exchange_price as u128;
 
+    // Updated this second (e.g. by `refresh_protocol_state`), the stored price is exact
+    if lending.last_update_timestamp >= current_timestamp {
+        return Ok(old_token_exchange_price);
+    }
+
     let old_liquidity_exchange_price = lending.liquidity_exchange_price as u128;
     msg!("Old liquidity exchange price: {}", old_liquidity_exchange_price);
 
     
 
     let total_assets = old_token_exchange_price
-                            .checked_mul(total_supply_ctoken as u128)
+                            .checked_mul(f_token_supply as u128)
                             .ok_or(AggregatorError::MathOverflow)?
                             .checked_div(EXCHANGE_PRICES_PRECISION)
                             .ok_or(AggregatorError::MathOverflow)?;
 
     msg!("Total assets: {}", total_assets);
 
-    let mut rewards_rate = get_rewards_rate(total_assets as u64, rewards_rate_model)?;
-
-    if rewards_rate.rate > MAX_REWARDS_RATE {
-        rewards_rate.rate = 0u128;
-    }
+    let rewards_rate = get_rewards_rate(
+        u64::try_from(total_assets).unwrap_or(u64::MAX),
+        rewards_rate_model,
+        current_timestamp,
+    )?;
 
     let mut last_update_time = lending.last_update_timestamp;
     if last_update_time < rewards_rate.rewards_start_time {
         last_update_time = rewards_rate.rewards_start_time;
     }
 
-    let current_timestamp = Clock::get()?.unix_timestamp as u128;
-
-
     let mut total_return_percent = rewards_rate.rate
-                                .checked_mul(
-                                    current_timestamp
-                                    .checked_sub(last_update_time as u128)
-                                    .ok_or(AggregatorError::MathOverflow)?
-                                )
+                                .checked_mul(current_timestamp.saturating_sub(last_update_time) as u128)
                                 .ok_or(AggregatorError::MathOverflow)?
                                 .checked_div(SECONDS_PER_YEAR)
                                 .ok_or(AggregatorError::MathOverflow)?;
@@ -152,7 +158,7 @@ pub fn get_new_exchange_price<'info>(
 
         // Add the gain to total_return_percent
         let delta_percent = delta
-            .checked_mul(100000000000000)
+            .checked_mul(REWARDS_RATE_PRECISION)
             .ok_or(AggregatorError::MathOverflow)?
             .checked_div(old_liquidity_exchange_price)
             .ok_or(AggregatorError::MathOverflow)?;
@@ -169,7 +175,7 @@ pub fn get_new_exchange_price<'info>(
 
         // Subtract the loss from total_return_percent
         let delta_percent = delta
-            .checked_mul(100000000000000)
+            .checked_mul(REWARDS_RATE_PRECISION)
             .ok_or(AggregatorError::MathOverflow)?
             .checked_div(old_liquidity_exchange_price)
             .ok_or(AggregatorError::MathOverflow)?;
@@ -181,7 +187,7 @@ pub fn get_new_exchange_price<'info>(
 
     let new_token_exchange_price = old_token_exchange_price.checked_add(
         old_token_exchange_price.checked_mul(total_return_percent).ok_or(AggregatorError::MathOverflow)?
-        .checked_div(100000000000000)
+        .checked_div(REWARDS_RATE_PRECISION)
         .ok_or(AggregatorError::MathOverflow)?
     ).ok_or(AggregatorError::MathOverflow)?;
     msg!("New token exchange price: {}", new_token_exchange_price);
@@ -193,11 +199,19 @@ pub fn get_new_exchange_price<'info>(
 
 pub fn convert_to_asset<'info>(
     fusdc_amount: u64,
+    f_token_supply: u64,
     supply_exchange_price: u64,
     lending: &Lending,
     rewards_rate_model: &LendingRewardsRateModel,
+    current_timestamp: u64,
 ) -> Result<u64> {
-    let new_exchange_price = get_new_exchange_price(supply_exchange_price, fusdc_amount, lending, rewards_rate_model)?;
+    let new_exchange_price = get_new_exchange_price(
+        supply_exchange_price,
+        f_token_supply,
+        lending,
+        rewards_rate_model,
+        current_timestamp,
+    )?;
 
     let usdc_assets = (fusdc_amount as u128).checked_mul(new_exchange_price)
         .ok_or(AggregatorError::MathOverflow)?
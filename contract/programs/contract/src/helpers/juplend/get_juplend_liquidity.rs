@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{EXCHANGE_PRICES_PRECISION, UTILIZATION_PRECISION},
+    errors::AggregatorError,
+    helpers::token_reserve_helper::load_token_reserve,
+};
+
+/*
+   Amount of the underlying token that can currently leave the JupLend liquidity layer.
+
+   total_supply = supply_with_interest * supply_exchange_price / 1e12 + supply_interest_free
+   total_borrow = borrow_with_interest * borrow_exchange_price / 1e12 + borrow_interest_free
+
+   A withdrawal must leave enough supply behind to keep utilization at or below
+   max_utilization (1e4 = 100%), so the withdrawable amount is
+   total_supply - total_borrow * 1e4 / max_utilization
+*/
+pub fn get_juplend_available_liquidity(token_reserve: &AccountInfo) -> Result<u64> {
+    let data = token_reserve.try_borrow_data()?;
+
+    let (total_supply, total_borrow) = get_juplend_totals(&data)?;
+
+    let max_utilization = load_token_reserve(&data)?.max_utilization as u128;
+
+    // Supply that has to stay in the pool to back the current borrows
+    let locked_supply = if max_utilization == 0 || max_utilization >= UTILIZATION_PRECISION {
+        total_borrow
+    } else {
+        total_borrow
+            .checked_mul(UTILIZATION_PRECISION)
+            .ok_or(AggregatorError::MathOverflow)?
+            .div_ceil(max_utilization)
+    };
+
+    let available = total_supply.saturating_sub(locked_supply);
+
+    msg!("JupLend supply: {}, borrow: {}, available: {}", total_supply, total_borrow, available);
+
+    Ok(u64::try_from(available).unwrap_or(u64::MAX))
+}
+
+/// (total_supply, total_borrow) of the liquidity layer in underlying token units
+pub fn get_juplend_totals(data: &[u8]) -> Result<(u128, u128)> {
+    let reserve = load_token_reserve(data)?;
+
+    let total_supply = to_normal_amount(
+        reserve.total_supply_with_interest,
+        reserve.supply_exchange_price,
+        reserve.total_supply_interest_free,
+    )?;
+
+    let total_borrow = to_normal_amount(
+        reserve.total_borrow_with_interest,
+        reserve.borrow_exchange_price,
+        reserve.total_borrow_interest_free,
+    )?;
+
+    Ok((total_supply, total_borrow))
+}
+
+fn to_normal_amount(raw_with_interest: u64, exchange_price: u64, interest_free: u64) -> Result<u128> {
+    (raw_with_interest as u128)
+        .checked_mul(exchange_price as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(EXCHANGE_PRICES_PRECISION)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_add(interest_free as u128)
+        .ok_or(AggregatorError::MathOverflow.into())
+}
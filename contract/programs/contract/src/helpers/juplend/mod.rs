@@ -4,4 +4,8 @@ pub mod withdraw_from_juplend;
 
 pub mod get_juplend_balance;
 
-pub mod token_reserve_helper;
\ No newline at end of file
+pub mod token_reserve_helper;
+
+pub mod get_juplend_liquidity;
+
+pub mod get_juplend_apy;
@@ -16,7 +16,15 @@ use anchor_spl::{
     }
 };
 
-use crate::{errors::AggregatorError, states::{AggregatorConfig, ReserveWithdrawAccounts}};
+use crate::{
+    constants::USER_STATE_DISCRIMINATOR,
+    errors::AggregatorError,
+    helpers::kamino::{
+        kamino_reserve_accounts::{parse_reserve_accounts, KAMINO_RESERVES_START},
+        user_state_helper::load_user_state,
+    },
+    states::{AggregatorConfig, ReserveWithdrawAccounts},
+};
 use anchor_lang::solana_program::program_pack::Pack;
 
 
@@ -94,33 +102,11 @@ impl<'info> KaminoVault<'info> {
         // Skip first 13 accounts (JupLend accounts) 
         let number_of_juplend_accounts = 13; 
         
-        let mut reserve_accounts: Vec<ReserveWithdrawAccounts<'info>> = Vec::with_capacity(2);
-        
-        let reserve_idx = number_of_juplend_accounts + 9 + 7 + 4;
-        
-        // Reserve 1 (7 accounts starting at reserve_idx)
-        reserve_accounts.push(ReserveWithdrawAccounts {
-            reserve: remaining_accounts.get(reserve_idx).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            ctoken_vault: remaining_accounts.get(reserve_idx + 1).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            lending_market: remaining_accounts.get(reserve_idx + 2).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            lending_market_authority: remaining_accounts.get(reserve_idx + 3).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            reserve_liquidity_supply: remaining_accounts.get(reserve_idx + 4).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            reserve_collateral_mint: remaining_accounts.get(reserve_idx + 5).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            reserve_collateral_token_program: remaining_accounts.get(reserve_idx + 6).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        });
+        // One group per reserve in the vault's allocation strategy
+        let reserve_accounts = parse_reserve_accounts(remaining_accounts)?;
         
-        // Reserve 2 (7 accounts starting at reserve_idx + 7)
-        let reserve_idx_2 = reserve_idx + 7;
-        reserve_accounts.push(ReserveWithdrawAccounts {
-            reserve: remaining_accounts.get(reserve_idx_2).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            ctoken_vault: remaining_accounts.get(reserve_idx_2 + 1).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            lending_market: remaining_accounts.get(reserve_idx_2 + 2).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            lending_market_authority: remaining_accounts.get(reserve_idx_2 + 3).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            reserve_liquidity_supply: remaining_accounts.get(reserve_idx_2 + 4).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            reserve_collateral_mint: remaining_accounts.get(reserve_idx_2 + 5).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-            reserve_collateral_token_program: remaining_accounts.get(reserve_idx_2 + 6).ok_or(AggregatorError::MissingAccount)?.to_account_info(),
-        });
-        let global_config = remaining_accounts.get(reserve_idx - 1).ok_or(AggregatorError::MissingAccount)?.to_account_info();
+        // Last Kamino vault account, just before the reserve groups
+        let global_config = remaining_accounts.get(KAMINO_RESERVES_START - 1).ok_or(AggregatorError::MissingAccount)?.to_account_info();
         // Directly construct the Box to avoid large stack allocations
         Ok(Box::new(KaminoVault {
             signer: signer.to_account_info(),
@@ -372,7 +358,7 @@ impl<'info> KaminoVault<'info> {
     }
 
 
-    fn stake_in_farm_by_config(&self, shares_amount: u64, config_bump: u8) -> Result<()> {
+    pub fn stake_in_farm_by_config(&self, shares_amount: u64, config_bump: u8) -> Result<()> {
         let mut instruction_data = get_farm_stake_discriminator();
         instruction_data.extend_from_slice(&shares_amount.to_le_bytes());
 
@@ -458,6 +444,22 @@ impl<'info> KaminoVault<'info> {
             return Ok(());
         }
 
+        if shares_amount == 0 {
+            return Ok(());
+        }
+
+        // Once the config owns its farm user state every new share is staked directly
+        if self.config_owns_user_farm_state()? {
+            return self.stake_in_farm_by_config(shares_amount, config_bump);
+        }
+
+        // The first 1_000_000 shares prefund the farm user state below, smaller deposits
+        // stay unstaked until `crank_kamino_farm` restakes them
+        if shares_amount <= 1000000 {
+            msg!("Only {} new shares, keeping them unstaked", shares_amount);
+            return Ok(());
+        }
+
         // Check if user farm state account exists before initializing
         let user_farm_state_exists = self.user_farm_state.data_len() > 0 
             && self.user_farm_state.owner == self.farm_program.key;
@@ -498,6 +500,20 @@ impl<'info> KaminoVault<'info> {
     }
 
     
+    fn config_owns_user_farm_state(&self) -> Result<bool> {
+        if self.config_state.owner != self.farm_program.key {
+            return Ok(false);
+        }
+
+        let data = self.config_state.try_borrow_data()?;
+        Ok(data.starts_with(&USER_STATE_DISCRIMINATOR) && load_user_state(&data)?.owner == *self.config.key)
+    }
+
+    fn config_shares_amount(&self) -> Result<u64> {
+        let data = self.config_shares_ata.try_borrow_data()?;
+        Ok(SplTokenAccount::unpack(&data)?.amount)
+    }
+
     pub fn execute_complete_deposit(&self, amount: u64, config_bump: u8) -> Result<()> {
         // Step 1: Create shares ATA if needed
         self.create_shares_ata(
@@ -512,12 +528,11 @@ impl<'info> KaminoVault<'info> {
             &self.signer.to_account_info(),
         )?;
         // Step 2: Execute deposit
+        let shares_before = self.config_shares_amount()?;
         self.deposit_to_kamino(amount, config_bump)?;
 
-        let amount_to_stake = {
-            let data = self.config_shares_ata.try_borrow_data()?;
-            SplTokenAccount::unpack(&data)?.amount
-        };
+        // Only the new shares, the ATA also holds the liquid buffer kept by `crank_kamino_farm`
+        let amount_to_stake = self.config_shares_amount()?.saturating_sub(shares_before);
 
         msg!("Amount to stake: {}", amount_to_stake);
         // Step 3: Stake shares in farm
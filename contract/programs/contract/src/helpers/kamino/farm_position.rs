@@ -0,0 +1,205 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{
+    constants::{BPS_BASE, FARM_STATE_DISCRIMINATOR, USER_STATE_DISCRIMINATOR, WAD},
+    errors::AggregatorError,
+    helpers::kamino::{farm_state_helper::load_farm_state, get_kamino_balance::get_kamino_balance, user_state_helper::load_user_state},
+    states::kamino::TIME_UNIT_SLOTS,
+};
+
+
+/// Kamino vault shares (kTokens) held by the config, by where they sit in the farm
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FarmPosition {
+    /// In the config's shares ATA, redeemable right away
+    pub unstaked: u64,
+    /// Staked and earning farm rewards
+    pub active: u64,
+    /// Staked but still in the farm's deposit warmup
+    pub pending_deposit: u64,
+    /// Unstaked but still in the farm's withdrawal cooldown
+    pub pending_withdrawal: u64,
+    /// Whether the cooldown on `pending_withdrawal` is over
+    pub pending_withdrawal_ready: bool,
+}
+
+
+/// Farm clock and cooldown, both in the farm's `time_unit`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FarmTiming {
+    pub now: u64,
+    pub withdrawal_cooldown: u64,
+}
+
+
+impl FarmPosition {
+    pub fn total_shares(&self) -> Result<u64> {
+        self.unstaked
+            .checked_add(self.active)
+            .and_then(|acc| acc.checked_add(self.pending_deposit))
+            .and_then(|acc| acc.checked_add(self.pending_withdrawal))
+            .ok_or(AggregatorError::MathOverflow.into())
+    }
+
+    /*
+       Shares a withdraw can redeem in this transaction: the ATA, a pending unstake whose
+       cooldown is over, and the active stake only when the farm has no cooldown, since
+       unstaking and withdrawing the unstaked shares then happen back to back.
+    */
+    pub fn withdrawable_shares(&self, withdrawal_cooldown: u64) -> u64 {
+        let mut withdrawable = self.unstaked;
+        if self.pending_withdrawal_ready {
+            withdrawable = withdrawable.saturating_add(self.pending_withdrawal);
+        }
+        if withdrawal_cooldown == 0 {
+            withdrawable = withdrawable.saturating_add(self.active);
+        }
+        withdrawable
+    }
+
+    /// Active shares to unstake so that unstaked and unstaking shares reach `buffer_bps` of the total
+    pub fn shares_to_unstake(&self, buffer_bps: u16) -> Result<u64> {
+        let target = (self.total_shares()? as u128)
+            .checked_mul(buffer_bps as u128)
+            .ok_or(AggregatorError::MathOverflow)?
+            .checked_div(BPS_BASE as u128)
+            .ok_or(AggregatorError::MathOverflow)? as u64;
+
+        let liquid = self.unstaked.saturating_add(self.pending_withdrawal);
+        Ok(target.saturating_sub(liquid).min(self.active))
+    }
+
+    /// Unstaked shares to stake again so that unstaked and unstaking shares come down to `buffer_bps` of the total
+    pub fn shares_to_restake(&self, buffer_bps: u16) -> Result<u64> {
+        let target = (self.total_shares()? as u128)
+            .checked_mul(buffer_bps as u128)
+            .ok_or(AggregatorError::MathOverflow)?
+            .checked_div(BPS_BASE as u128)
+            .ok_or(AggregatorError::MathOverflow)? as u64;
+
+        let liquid = self.unstaked.saturating_add(self.pending_withdrawal);
+        Ok(liquid.saturating_sub(target).min(self.unstaked))
+    }
+}
+
+
+/// Data that isn't a farm (vault without a farm) reads as no cooldown, a truncated farm is an error
+pub fn read_farm_timing(farm_state_data: &[u8], clock: &Clock) -> Result<FarmTiming> {
+    if !farm_state_data.starts_with(&FARM_STATE_DISCRIMINATOR) {
+        return Ok(FarmTiming::default());
+    }
+
+    let farm = load_farm_state(farm_state_data)?;
+    let now = if farm.time_unit == TIME_UNIT_SLOTS {
+        clock.slot
+    } else {
+        clock.unix_timestamp.max(0) as u64
+    };
+
+    Ok(FarmTiming {
+        now,
+        withdrawal_cooldown: farm.withdrawal_cooldown_period as u64,
+    })
+}
+
+
+/// Data that isn't a user state (nothing staked yet) reads as only the unstaked shares
+pub fn read_farm_position(unstaked: u64, user_state_data: &[u8], now: u64) -> Result<FarmPosition> {
+    if !user_state_data.starts_with(&USER_STATE_DISCRIMINATOR) {
+        return Ok(FarmPosition { unstaked, ..FarmPosition::default() });
+    }
+
+    let user = load_user_state(user_state_data)?;
+
+    // Stake amounts are scaled by WAD (10^18)
+    let pending_withdrawal = (user.pending_withdrawal_unstake_scaled.get() / WAD) as u64;
+
+    Ok(FarmPosition {
+        unstaked,
+        active: (user.active_stake_scaled.get() / WAD) as u64,
+        pending_deposit: (user.pending_deposit_stake_scaled.get() / WAD) as u64,
+        pending_withdrawal,
+        pending_withdrawal_ready: pending_withdrawal > 0 && now >= user.pending_withdrawal_unstake_ts,
+    })
+}
+
+
+pub fn get_kamino_farm_position<'info>(
+    shares_ata: &InterfaceAccount<'info, TokenAccount>,
+    user_state_account: &AccountInfo<'info>,
+    farm_state_account: &AccountInfo<'info>,
+) -> Result<(FarmPosition, FarmTiming)> {
+    let timing = read_farm_timing(&farm_state_account.try_borrow_data()?, &Clock::get()?)?;
+    let position = read_farm_position(shares_ata.amount, &user_state_account.try_borrow_data()?, timing.now)?;
+
+    Ok((position, timing))
+}
+
+
+/*
+   USDC the vault can take out of Kamino in one transaction. Anything still staked behind
+   a cooldown has to be unstaked by `crank_kamino_farm` first, so withdrawals above this
+   are served by the other protocols.
+
+   Reads the Kamino vault state (13), farm state (23), config farm user state (30) and
+   config shares ATA (31) from remaining accounts.
+*/
+pub fn get_kamino_withdrawable_usdc<'info>(
+    remaining_accounts: &'info [AccountInfo<'info>],
+    reserve_accounts: &[AccountInfo<'info>],
+    current_slot: u64,
+) -> Result<u64> {
+    let account = |index: usize| remaining_accounts.get(index).ok_or(AggregatorError::MissingAccount);
+
+    let shares_ata = InterfaceAccount::<'info, TokenAccount>::try_from(account(31)?)?;
+    let (position, timing) = get_kamino_farm_position(&shares_ata, account(30)?, account(23)?)?;
+
+    get_kamino_balance(
+        account(13)?,
+        position.withdrawable_shares(timing.withdrawal_cooldown),
+        reserve_accounts,
+        Some(current_slot),
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position() -> FarmPosition {
+        FarmPosition {
+            unstaked: 100,
+            active: 800,
+            pending_deposit: 50,
+            pending_withdrawal: 50,
+            pending_withdrawal_ready: false,
+        }
+    }
+
+    #[test]
+    fn test_withdrawable_shares_respect_cooldown() {
+        let mut position = position();
+        assert_eq!(position.total_shares().unwrap(), 1_000);
+
+        assert_eq!(position.withdrawable_shares(0), 900);
+        assert_eq!(position.withdrawable_shares(3_600), 100);
+
+        position.pending_withdrawal_ready = true;
+        assert_eq!(position.withdrawable_shares(3_600), 150);
+    }
+
+    #[test]
+    fn test_shares_to_unstake_tops_up_buffer() {
+        // 20% of 1_000 liquid, 150 already unstaked or unstaking
+        assert_eq!(position().shares_to_unstake(2_000).unwrap(), 50);
+        assert_eq!(position().shares_to_unstake(1_000).unwrap(), 0);
+        assert_eq!(position().shares_to_unstake(10_000).unwrap(), 800);
+
+        // Buffer lowered to 5%, 150 liquid against a 50 target, only the ATA can go back
+        assert_eq!(position().shares_to_restake(500).unwrap(), 100);
+        assert_eq!(position().shares_to_restake(0).unwrap(), 100);
+        assert_eq!(position().shares_to_restake(2_000).unwrap(), 0);
+    }
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::FARM_STATE_DISCRIMINATOR, helpers::zero_copy_view::zero_copy_view, states::kamino::FarmState};
+
+
+/// Zero-copy view over a Kamino farm, the layout is size-checked in `states::kamino::farm_state`
+pub fn load_farm_state(data: &[u8]) -> Result<&FarmState> {
+    zero_copy_view(data, &FARM_STATE_DISCRIMINATOR)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::kamino::{PodU128, RewardInfo, FARM_STATE_SIZE};
+    use bytemuck::Zeroable;
+    use std::mem::offset_of;
+
+    #[test]
+    fn test_farm_state_layout() {
+        // Offsets past the discriminator, from the farms IDL
+        assert_eq!(offset_of!(FarmState, reward_infos), 184);
+        assert_eq!(offset_of!(FarmState, num_reward_tokens), 7224);
+        assert_eq!(offset_of!(FarmState, time_unit), 7352);
+        assert_eq!(offset_of!(FarmState, withdraw_authority), 7360);
+        assert_eq!(offset_of!(FarmState, deposit_warmup_period), 7392);
+        assert_eq!(offset_of!(FarmState, withdrawal_cooldown_period), 7396);
+        assert_eq!(offset_of!(RewardInfo, rewards_vault), 120);
+        assert_eq!(offset_of!(RewardInfo, reward_per_share_scaled), 512);
+    }
+
+    #[test]
+    fn test_farm_state_view() {
+        let mut farm = FarmState::zeroed();
+        farm.num_reward_tokens = 2;
+        farm.reward_infos[1].reward_per_share_scaled = PodU128::new(5 << 70);
+        farm.withdrawal_cooldown_period = 3_600;
+
+        let data = [FARM_STATE_DISCRIMINATOR.as_slice(), bytemuck::bytes_of(&farm)].concat();
+        assert_eq!(data.len(), 8 + FARM_STATE_SIZE);
+
+        let view = load_farm_state(&data).unwrap();
+        assert_eq!(view.num_reward_tokens, 2);
+        assert_eq!(view.reward_infos[1].reward_per_share_scaled.get(), 5 << 70);
+        assert_eq!(view.withdrawal_cooldown_period, 3_600);
+
+        // Short buffers are an error instead of a panic
+        assert!(load_farm_state(&data[..data.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::BPS_BASE,
+    errors::AggregatorError,
+    helpers::kamino::{
+        get_kamino_liquidity::get_invested_in_reserve,
+        kamino_account_reader::{read_reserve_borrow_rate_curve, read_reserve_fields, read_vault_state_fields, ReserveFields},
+    },
+    states::kamino::{CurvePoint, Fraction, FractionExtra},
+};
+
+
+/*
+   Supply APY of the Kamino vault in bps.
+
+   Each reserve pays suppliers  borrow_rate(utilization) * utilization * (1 - protocol_take_rate),
+   the vault APY is the average of those rates weighted by what the vault holds in each reserve.
+   USDC the vault keeps idle (`token_available`) earns nothing and dilutes the rate.
+*/
+pub fn get_kamino_supply_apy_bps<'info>(
+    vault_state_account: &AccountInfo<'info>,
+    reserve_accounts: &[AccountInfo<'info>],
+    current_slot: Option<u64>,
+) -> Result<u64> {
+    let vault_data = vault_state_account.try_borrow_data()?;
+
+    let token_available = read_vault_state_fields(&vault_data)?.token_available as u128;
+
+    let mut weighted_rate_sum: u128 = 0;
+    let mut total_weight: u128 = token_available;
+    let mut unweighted_rate_sum: u128 = 0;
+
+    for reserve_account in reserve_accounts.iter() {
+        let supply_rate_bps = {
+            let reserve_data = reserve_account.try_borrow_data()?;
+            let reserve = read_reserve_fields(&reserve_data)?;
+            let curve = read_reserve_borrow_rate_curve(&reserve_data)?;
+            calculate_reserve_supply_rate_bps(&reserve, &curve)?
+        };
+
+        let invested = get_invested_in_reserve(&vault_data, reserve_account, current_slot)? as u128;
+
+        weighted_rate_sum = weighted_rate_sum
+            .checked_add(invested.checked_mul(supply_rate_bps as u128).ok_or(AggregatorError::MathOverflow)?)
+            .ok_or(AggregatorError::MathOverflow)?;
+        total_weight = total_weight
+            .checked_add(invested)
+            .ok_or(AggregatorError::MathOverflow)?;
+        unweighted_rate_sum = unweighted_rate_sum
+            .checked_add(supply_rate_bps as u128)
+            .ok_or(AggregatorError::MathOverflow)?;
+    }
+
+    // Empty vault, new deposits are spread over the reserves
+    if weighted_rate_sum == 0 && token_available == 0 {
+        if reserve_accounts.is_empty() {
+            return Ok(0);
+        }
+        return Ok((unweighted_rate_sum / reserve_accounts.len() as u128) as u64);
+    }
+
+    if total_weight == 0 {
+        return Ok(0);
+    }
+
+    Ok((weighted_rate_sum / total_weight) as u64)
+}
+
+
+pub fn calculate_reserve_supply_rate_bps(reserve: &ReserveFields, curve: &[CurvePoint]) -> Result<u64> {
+    // borrowed_amount_sf is a Fraction with 60 fractional bits
+    let borrowed = reserve.borrowed_amount_sf >> 60;
+    let total_liquidity = (reserve.available_amount as u128)
+        .checked_add(borrowed)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    if total_liquidity == 0 || borrowed == 0 {
+        return Ok(0);
+    }
+
+    let utilization_bps = borrowed
+        .checked_mul(BPS_BASE as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(total_liquidity)
+        .ok_or(AggregatorError::MathOverflow)? as u32;
+
+    let borrow_rate_bps = interpolate_borrow_rate_bps(curve, utilization_bps) as u128;
+
+    // supply_rate = borrow_rate * borrowed / total_liquidity * (100 - take_rate) / 100
+    let supply_rate_bps = borrow_rate_bps
+        .checked_mul(borrowed)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_mul(100u128.saturating_sub(reserve.protocol_take_rate_pct as u128))
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(total_liquidity.checked_mul(100).ok_or(AggregatorError::MathOverflow)?)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    Ok(supply_rate_bps as u64)
+}
+
+
+pub fn interpolate_borrow_rate_bps(curve: &[CurvePoint], utilization_bps: u32) -> u32 {
+    interpolate_borrow_rate(curve, utilization_bps as u128, BPS_BASE as u128) as u32
+}
+
+
+/*
+   Borrow rate from Kamino's piecewise linear curve. Points are sorted by utilization
+   and the last used point sits at 100%, unused trailing points repeat it.
+
+   `utilization` and the returned rate are scaled so that `precision` = 100%.
+*/
+pub fn interpolate_borrow_rate(curve: &[CurvePoint], utilization: u128, precision: u128) -> u128 {
+    let to_precision = |bps: u32| bps as u128 * precision / BPS_BASE as u128;
+
+    let Some(first) = curve.first() else {
+        return 0;
+    };
+
+    if utilization <= to_precision(first.utilization_rate_bps) {
+        return to_precision(first.borrow_rate_bps);
+    }
+
+    for window in curve.windows(2) {
+        let (start_utilization, end_utilization) = (
+            to_precision(window[0].utilization_rate_bps),
+            to_precision(window[1].utilization_rate_bps),
+        );
+        if utilization > end_utilization {
+            continue;
+        }
+
+        let start_rate = to_precision(window[0].borrow_rate_bps);
+        let end_rate = to_precision(window[1].borrow_rate_bps);
+
+        let utilization_span = end_utilization.saturating_sub(start_utilization);
+        if utilization_span == 0 {
+            return end_rate;
+        }
+
+        let offset = utilization - start_utilization;
+
+        return if end_rate >= start_rate {
+            start_rate + (end_rate - start_rate) * offset / utilization_span
+        } else {
+            start_rate - (start_rate - end_rate) * offset / utilization_span
+        };
+    }
+
+    curve.last().map(|point| to_precision(point.borrow_rate_bps)).unwrap_or(0)
+}
+
+
+/*
+   klend's `BorrowRateCurve::get_borrow_rate`, in Fraction precision. This is the rate the
+   reserve compounds its debt with, so the interest estimates use it rather than the bps
+   interpolation above. The segment is picked on the rounded bps utilization, the rate is
+   interpolated on the exact one.
+*/
+pub fn get_borrow_rate(curve: &[CurvePoint], utilization: Fraction) -> Result<Fraction> {
+    let utilization = utilization.min(Fraction::ONE);
+    let utilization_bps: u32 = utilization.to_bps().ok_or(AggregatorError::MathOverflow)?;
+
+    let (start, end) = curve
+        .windows(2)
+        .map(|segment| (&segment[0], &segment[1]))
+        .find(|(start, end)| {
+            utilization_bps >= start.utilization_rate_bps && utilization_bps <= end.utilization_rate_bps
+        })
+        .ok_or(AggregatorError::InvalidAccountData)?;
+
+    if utilization_bps == end.utilization_rate_bps {
+        return Ok(Fraction::from_bps(end.borrow_rate_bps));
+    }
+
+    let slope_nom = end.borrow_rate_bps
+        .checked_sub(start.borrow_rate_bps)
+        .ok_or(AggregatorError::MathOverflow)?;
+    let slope_denom = end.utilization_rate_bps - start.utilization_rate_bps;
+
+    let coef = utilization
+        .checked_sub(Fraction::from_bps(start.utilization_rate_bps))
+        .ok_or(AggregatorError::MathOverflow)?;
+    let base_rate = coef
+        .checked_mul(Fraction::from_num(slope_nom))
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(Fraction::from_num(slope_denom))
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    base_rate
+        .checked_add(Fraction::from_bps(start.borrow_rate_bps))
+        .ok_or(AggregatorError::MathOverflow.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(utilization_rate_bps: u32, borrow_rate_bps: u32) -> CurvePoint {
+        CurvePoint { utilization_rate_bps, borrow_rate_bps }
+    }
+
+    fn curve() -> [CurvePoint; 11] {
+        // 0% -> 0, kink at 80% -> 10%, 100% -> 50%
+        let mut points = [point(10_000, 5_000); 11];
+        points[0] = point(0, 0);
+        points[1] = point(8_000, 1_000);
+        points
+    }
+
+    #[test]
+    fn test_interpolate_borrow_rate() {
+        let curve = curve();
+        assert_eq!(interpolate_borrow_rate_bps(&curve, 0), 0);
+        assert_eq!(interpolate_borrow_rate_bps(&curve, 4_000), 500);
+        assert_eq!(interpolate_borrow_rate_bps(&curve, 8_000), 1_000);
+        assert_eq!(interpolate_borrow_rate_bps(&curve, 9_000), 3_000);
+        assert_eq!(interpolate_borrow_rate_bps(&curve, 10_000), 5_000);
+    }
+
+    #[test]
+    fn test_reserve_supply_rate() {
+        // 50% utilization -> 6.25% borrow, 3.125% to suppliers before a 20% take rate
+        let reserve = ReserveFields {
+            last_update_slot: 0,
+            available_amount: 1_000_000,
+            borrowed_amount_sf: 1_000_000u128 << 60,
+            accumulated_protocol_fees_sf: 0,
+            accumulated_referrer_fees_sf: 0,
+            pending_referrer_fees_sf: 0,
+            mint_total_supply: 0,
+            protocol_take_rate_pct: 20,
+            host_fixed_interest_rate_bps: 0,
+        };
+        assert_eq!(calculate_reserve_supply_rate_bps(&reserve, &curve()).unwrap(), 250);
+    }
+}
@@ -2,12 +2,15 @@ use anchor_lang::prelude::*;
 
 use crate::{
     errors::AggregatorError,
-    helpers::kamino::kamino_account_reader::{
-        read_vault_state_fields, read_vault_allocation, read_reserve_fields,
-        vault_offsets,
+    helpers::kamino::{
+        get_kamino_apy::get_borrow_rate,
+        kamino_account_reader::{
+            read_vault_state_fields, read_vault_allocation, read_reserve_fields,
+            read_reserve_borrow_rate_curve,
+        },
     },
-    states::kamino::{Fraction, FractionExtra},
-    constants::{VAULT_STATE_DISCRIMINATOR, SLOTS_PER_YEAR},
+    states::kamino::{CurvePoint, Fraction, FractionExtra, MAX_RESERVES},
+    constants::SLOTS_PER_YEAR,
 };
 
 
@@ -40,10 +43,6 @@ pub fn get_kamino_balance<'info>(
 ) -> Result<u64> {
     let vault_data = vault_state_account.try_borrow_data()?;
 
-    if vault_data.len() < 8 || vault_data[0..8] != VAULT_STATE_DISCRIMINATOR {
-        return Err(AggregatorError::InvalidAccountData.into());
-    }
-
     let vault_fields = read_vault_state_fields(&vault_data)?;
 
     if vault_fields.shares_issued == 0 {
@@ -96,7 +95,7 @@ fn calculate_total_invested_with_exchange_rate(
 ) -> Result<Fraction> {
     let mut total_invested = Fraction::ZERO;
 
-    for i in 0..vault_offsets::MAX_RESERVES {
+    for i in 0..MAX_RESERVES {
         let allocation = read_vault_allocation(vault_data, i)?;
         
         // Skip empty allocations
@@ -176,16 +175,12 @@ pub fn get_kamino_shares_amount_from_usdc<'info>(
 
     All *_sf fields use Fraction type (2^60 scaling)
 */
-fn calculate_collateral_exchange_rate(
+pub fn calculate_collateral_exchange_rate(
     reserve_account: &AccountInfo,
     current_slot: Option<u64>,
 ) -> Result<Fraction> {
     let reserve_data = reserve_account.try_borrow_data()?;
 
-    if reserve_data.len() < 100 {
-        return Ok(INITIAL_COLLATERAL_RATE);
-    }
-
     let reserve = read_reserve_fields(&reserve_data)?;
     
 
@@ -193,7 +188,8 @@ fn calculate_collateral_exchange_rate(
 
     let total_supply = if let Some(slot) = current_slot {
         // Calculate estimated total supply with compounded interest
-        calculate_estimated_total_supply(&reserve, slot)?
+        let curve = read_reserve_borrow_rate_curve(&reserve_data)?;
+        calculate_estimated_total_supply(&reserve, &curve, slot)?
     } else {
         // Use stale total supply from last update
         calculate_total_supply(&reserve)?
@@ -213,7 +209,7 @@ fn calculate_collateral_exchange_rate(
 }
 
 
-fn calculate_total_supply(
+pub fn calculate_total_supply(
     reserve: &crate::helpers::kamino_account_reader::ReserveFields
 ) -> Result<Fraction> {
     let available_amount = Fraction::from(reserve.available_amount);
@@ -239,6 +235,7 @@ fn calculate_total_supply(
 
 fn calculate_estimated_total_supply(
     reserve: &crate::helpers::kamino_account_reader::ReserveFields,
+    borrow_rate_curve: &[CurvePoint],
     current_slot: u64
 ) -> Result<Fraction> {
     let slots_elapsed = current_slot.saturating_sub(reserve.last_update_slot);
@@ -248,7 +245,7 @@ fn calculate_estimated_total_supply(
     }
 
     let (new_debt, new_acc_protocol_fees, pending_referral_fees) =
-        compound_interest(reserve, slots_elapsed)?;
+        compound_interest(reserve, borrow_rate_curve, slots_elapsed)?;
 
     /*
     Calculate estimated total supply:
@@ -274,6 +271,7 @@ fn calculate_estimated_total_supply(
 
 fn compound_interest(
     reserve: &crate::helpers::kamino_account_reader::ReserveFields,
+    borrow_rate_curve: &[CurvePoint],
     slots_elapsed: u64,
 ) -> Result<(Fraction, Fraction, Fraction)> {
     let previous_debt = Fraction::from_bits(reserve.borrowed_amount_sf);
@@ -282,10 +280,11 @@ fn compound_interest(
     let protocol_take_rate = Fraction::from_percent(reserve.protocol_take_rate_pct as u64);
     let fixed_host_interest_rate = Fraction::from_bps(reserve.host_fixed_interest_rate_bps as u64);
 
-    let approximate_borrow_rate = Fraction::from_bps(500); // ~5% APY as placeholder
+    // The reserve compounds at the curve's rate for its utilization at the last refresh
+    let current_borrow_rate = get_borrow_rate(borrow_rate_curve, calculate_utilization_rate(reserve)?)?;
 
     let compounded_interest_rate = approximate_compounded_interest(
-        approximate_borrow_rate
+        current_borrow_rate
             .checked_add(fixed_host_interest_rate)
             .ok_or(AggregatorError::MathOverflow)?,
         slots_elapsed,
@@ -344,6 +343,21 @@ fn compound_interest(
 
 
 
+/// borrowed / total_supply, zero for an empty reserve
+fn calculate_utilization_rate(
+    reserve: &crate::helpers::kamino_account_reader::ReserveFields,
+) -> Result<Fraction> {
+    let total_supply = calculate_total_supply(reserve)?;
+    if total_supply == Fraction::ZERO {
+        return Ok(Fraction::ZERO);
+    }
+
+    Fraction::from_bits(reserve.borrowed_amount_sf)
+        .checked_div(total_supply)
+        .ok_or(AggregatorError::MathOverflow.into())
+}
+
+
 fn approximate_compounded_interest(rate: Fraction, elapsed_slots: u64) -> Result<Fraction> {
     let base = rate
         .checked_div(Fraction::from_num(SLOTS_PER_YEAR))
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{MAX_REWARDS_TOKENS, WAD},
+    errors::AggregatorError,
+    events::UnclaimedReward,
+    helpers::kamino::{farm_state_helper::load_farm_state, user_state_helper::load_user_state},
+    states::kamino::U256,
+};
+
+
+/*
+   Farm rewards owed to a farm user, per reward mint.
+
+   owed = rewards_issued_unclaimed + active_stake * reward_per_share - rewards_tally
+
+   The farm only bumps reward_per_share when it is refreshed, so rewards issued since the
+   last refresh (any farm instruction) are not counted yet. `user_amount` is left for the caller.
+*/
+pub fn get_kamino_unclaimed_rewards<'info>(
+    farm_state_account: &AccountInfo<'info>,
+    user_state_account: &AccountInfo<'info>,
+) -> Result<Vec<UnclaimedReward>> {
+    if farm_state_account.data_len() == 0 || user_state_account.data_len() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let farm_data = farm_state_account.try_borrow_data()?;
+    let user_data = user_state_account.try_borrow_data()?;
+
+    let farm = load_farm_state(&farm_data)?;
+    let user = load_user_state(&user_data)?;
+
+    require!(user.farm_state == farm_state_account.key(), AggregatorError::InvalidAccountData);
+
+    let num_reward_tokens = (farm.num_reward_tokens as usize).min(MAX_REWARDS_TOKENS);
+    let active_stake_scaled = user.active_stake_scaled.get();
+
+    let mut rewards = Vec::with_capacity(num_reward_tokens);
+    for (index, reward_info) in farm.reward_infos[..num_reward_tokens].iter().enumerate() {
+        let pending = calculate_pending_reward(
+            active_stake_scaled,
+            reward_info.reward_per_share_scaled.get(),
+            user.rewards_tally_scaled[index].get(),
+        )?;
+
+        let amount = user.rewards_issued_unclaimed[index]
+            .checked_add(pending)
+            .ok_or(AggregatorError::MathOverflow)?;
+
+        rewards.push(UnclaimedReward {
+            reward_mint: reward_info.token.mint,
+            amount,
+            user_amount: 0,
+        });
+    }
+
+    Ok(rewards)
+}
+
+
+/// floor(active_stake * reward_per_share - rewards_tally), all three WAD scaled
+pub fn calculate_pending_reward(
+    active_stake_scaled: u128,
+    reward_per_share_scaled: u128,
+    rewards_tally_scaled: u128,
+) -> Result<u64> {
+    let new_tally_scaled = U256::from(active_stake_scaled) * U256::from(reward_per_share_scaled) / U256::from(WAD);
+    let pending = new_tally_scaled.saturating_sub(U256::from(rewards_tally_scaled)) / U256::from(WAD);
+
+    if pending > U256::from(u64::MAX) {
+        return Err(AggregatorError::MathOverflow.into());
+    }
+
+    Ok(pending.as_u64())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_reward() {
+        // 1_000 shares staked, 2.5 reward per share, 1_500 already credited
+        let pending = calculate_pending_reward(1_000 * WAD, 5 * WAD / 2, 1_500 * WAD).unwrap();
+        assert_eq!(pending, 1_000);
+
+        // Tally ahead of the farm (not refreshed yet) never goes negative
+        assert_eq!(calculate_pending_reward(1_000 * WAD, WAD, 2_000 * WAD).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_pending_reward_does_not_overflow_u128() {
+        // stake * reward_per_share overflows u128 before the WAD division
+        let pending = calculate_pending_reward(1_000_000_000 * WAD, 100 * WAD, 0).unwrap();
+        assert_eq!(pending, 100_000_000_000);
+    }
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::kamino::{
+        get_kamino_balance::calculate_collateral_exchange_rate,
+        kamino_account_reader::{read_reserve_fields, read_vault_allocation, read_vault_state_fields},
+        reserve_preflight::get_reserve_withdraw_room,
+    },
+    states::kamino::{Fraction, FractionExtra, MAX_RESERVES},
+};
+
+
+pub struct KaminoLiquidity {
+    /// USDC the vault can pay out in a single withdraw
+    pub available: u64,
+    /// Position in `reserve_accounts` of the reserve that backs the withdraw
+    pub reserve_index: Option<usize>,
+}
+
+/*
+   A Kamino vault withdraw first pays out of the vault's `token_available` and then
+   redeems from a single reserve, so the amount it can serve is
+
+   token_available + min(reserve.available_amount, tokens the vault holds in that reserve,
+                         room left under the reserve's withdrawal cap)
+
+   for the reserve with the deepest liquidity among the ones we were given.
+*/
+pub fn get_kamino_available_liquidity<'info>(
+    vault_state_account: &AccountInfo<'info>,
+    reserve_accounts: &[AccountInfo<'info>],
+    current_slot: Option<u64>,
+) -> Result<KaminoLiquidity> {
+    let vault_data = vault_state_account.try_borrow_data()?;
+
+    let vault_fields = read_vault_state_fields(&vault_data)?;
+    let now = Clock::get()?.unix_timestamp.max(0) as u64;
+
+    let mut best_reserve: Option<(usize, u64)> = None;
+
+    for (index, reserve_account) in reserve_accounts.iter().enumerate() {
+        let invested = get_invested_in_reserve(&vault_data, reserve_account, current_slot)?;
+        if invested == 0 {
+            continue;
+        }
+
+        let reserve_available = {
+            let reserve_data = reserve_account.try_borrow_data()?;
+            read_reserve_fields(&reserve_data)?.available_amount
+        };
+
+        let withdrawable = invested
+            .min(reserve_available)
+            .min(get_reserve_withdraw_room(reserve_account, now)?);
+
+        if best_reserve.is_none_or(|(_, best)| withdrawable > best) {
+            best_reserve = Some((index, withdrawable));
+        }
+    }
+
+    let from_reserve = best_reserve.map(|(_, amount)| amount).unwrap_or(0);
+
+    let available = vault_fields
+        .token_available
+        .checked_add(from_reserve)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    msg!("Kamino token available: {}, from reserve: {}", vault_fields.token_available, from_reserve);
+
+    Ok(KaminoLiquidity {
+        available,
+        reserve_index: best_reserve.map(|(index, _)| index),
+    })
+}
+
+
+pub fn get_invested_in_reserve(
+    vault_data: &[u8],
+    reserve_account: &AccountInfo,
+    current_slot: Option<u64>,
+) -> Result<u64> {
+    for i in 0..MAX_RESERVES {
+        let allocation = read_vault_allocation(vault_data, i)?;
+
+        if allocation.reserve != reserve_account.key() {
+            continue;
+        }
+
+        if allocation.ctoken_allocation == 0 {
+            return Ok(0);
+        }
+
+        let exchange_rate = calculate_collateral_exchange_rate(reserve_account, current_slot)?;
+
+        // invested = ctoken_allocation / exchange_rate
+        let invested = Fraction::from(allocation.ctoken_allocation)
+            .checked_div(exchange_rate)
+            .ok_or(AggregatorError::MathOverflow)?;
+
+        return invested.try_to_floor::<u64>().ok_or(AggregatorError::MathOverflow.into());
+    }
+
+    Ok(0)
+}
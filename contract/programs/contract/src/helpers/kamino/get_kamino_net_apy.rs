@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{
+        BPS_BASE, MIN_REALIZED_APY_WINDOW_SECONDS, REALIZED_APY_SNAPSHOT_INTERVAL_SECONDS,
+        SECONDS_PER_YEAR,
+    },
+    errors::AggregatorError,
+    helpers::kamino::{
+        get_kamino_apy::get_kamino_supply_apy_bps,
+        kamino_account_reader::{read_vault_fee_fields, VaultFeeFields},
+    },
+    states::KaminoInterestSnapshot,
+};
+
+
+/*
+   APY a Kamino vault depositor actually receives, in bps.
+
+   The vault takes a performance fee on the interest it earns and a yearly management fee
+   on AUM, so  net = gross * (1 - performance_fee) - management_fee.
+
+   Gross is the interest the vault realized since `snapshot`, annualized over its AUM, once
+   the vault has charged fees at least MIN_REALIZED_APY_WINDOW_SECONDS after the snapshot.
+   Until then it is the current supply APY of the reserves.
+*/
+pub fn get_kamino_net_apy_bps<'info>(
+    vault_state_account: &AccountInfo<'info>,
+    reserve_accounts: &[AccountInfo<'info>],
+    current_slot: Option<u64>,
+    snapshot: &KaminoInterestSnapshot,
+) -> Result<u64> {
+    let fees = read_kamino_vault_fees(vault_state_account)?;
+
+    let gross_apy_bps = match calculate_realized_apy_bps(snapshot, &fees)? {
+        Some(realized_apy_bps) => realized_apy_bps,
+        None => get_kamino_supply_apy_bps(vault_state_account, reserve_accounts, current_slot)?,
+    };
+
+    Ok(apply_kamino_vault_fees_bps(gross_apy_bps, &fees))
+}
+
+
+pub fn read_kamino_vault_fees(vault_state_account: &AccountInfo) -> Result<VaultFeeFields> {
+    let vault_data = vault_state_account.try_borrow_data()?;
+
+    read_vault_fee_fields(&vault_data)
+}
+
+
+pub fn apply_kamino_vault_fees_bps(gross_apy_bps: u64, fees: &VaultFeeFields) -> u64 {
+    let after_performance_fee = (gross_apy_bps as u128)
+        * (BPS_BASE as u128).saturating_sub(fees.performance_fee_bps as u128)
+        / BPS_BASE as u128;
+
+    (after_performance_fee as u64).saturating_sub(fees.management_fee_bps)
+}
+
+
+/// Gross interest between the snapshot and the vault's last fee charge, annualized, None without a usable window
+pub fn calculate_realized_apy_bps(snapshot: &KaminoInterestSnapshot, fees: &VaultFeeFields) -> Result<Option<u64>> {
+    let window = fees.last_fee_charge_timestamp.saturating_sub(snapshot.timestamp);
+    if snapshot.timestamp == 0 || window < MIN_REALIZED_APY_WINDOW_SECONDS {
+        return Ok(None);
+    }
+
+    // Cumulative interest never goes down, a larger snapshot was taken on another vault
+    let Some(interest_sf) = fees.cumulative_earned_interest_sf.checked_sub(snapshot.cumulative_earned_interest_sf) else {
+        return Ok(None);
+    };
+
+    // Both are Fractions with 60 fractional bits, compare them in whole token units
+    let (interest, aum) = (interest_sf >> 60, fees.prev_aum_sf >> 60);
+    if aum == 0 {
+        return Ok(None);
+    }
+
+    let apy_bps = interest
+        .checked_mul(SECONDS_PER_YEAR)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_mul(BPS_BASE as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(aum.checked_mul(window as u128).ok_or(AggregatorError::MathOverflow)?)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    Ok(Some(u64::try_from(apy_bps).unwrap_or(u64::MAX)))
+}
+
+
+/*
+   Starts a new realized APY window at the vault's last fee charge once the current one is
+   REALIZED_APY_SNAPSHOT_INTERVAL_SECONDS long, or when there is no usable snapshot.
+   Returns whether the snapshot moved.
+*/
+pub fn roll_kamino_interest_snapshot(snapshot: &mut KaminoInterestSnapshot, fees: &VaultFeeFields) -> bool {
+    let is_stale = snapshot.timestamp == 0
+        || fees.cumulative_earned_interest_sf < snapshot.cumulative_earned_interest_sf
+        || fees.last_fee_charge_timestamp >= snapshot.timestamp.saturating_add(REALIZED_APY_SNAPSHOT_INTERVAL_SECONDS);
+
+    if !is_stale || fees.last_fee_charge_timestamp == 0 {
+        return false;
+    }
+
+    *snapshot = KaminoInterestSnapshot {
+        cumulative_earned_interest_sf: fees.cumulative_earned_interest_sf,
+        timestamp: fees.last_fee_charge_timestamp,
+    };
+    true
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86400;
+
+    fn fees(cumulative_interest: u128, aum: u128, timestamp: u64) -> VaultFeeFields {
+        VaultFeeFields {
+            performance_fee_bps: 1000,
+            management_fee_bps: 50,
+            last_fee_charge_timestamp: timestamp,
+            prev_aum_sf: aum << 60,
+            cumulative_earned_interest_sf: cumulative_interest << 60,
+        }
+    }
+
+    #[test]
+    fn test_fees_come_off_the_gross_apy() {
+        // 8% gross, 10% of it to the vault and 0.5% of AUM a year -> 6.7%
+        assert_eq!(apply_kamino_vault_fees_bps(800, &fees(0, 0, 0)), 670);
+        assert_eq!(apply_kamino_vault_fees_bps(40, &fees(0, 0, 0)), 0);
+    }
+
+    #[test]
+    fn test_realized_apy_over_the_snapshot_window() {
+        let start = 1_700_000_000;
+        let mut snapshot = KaminoInterestSnapshot::default();
+        assert!(roll_kamino_interest_snapshot(&mut snapshot, &fees(5_000_000, 1_000_000_000, start)));
+
+        // 1_000 USDC earning 8% a year for 73 days
+        let current = fees(5_000_000 + 16_000_000, 1_000_000_000, start + 73 * DAY);
+        assert_eq!(calculate_realized_apy_bps(&snapshot, &current).unwrap(), Some(800));
+
+        // Too short a window or no snapshot falls back to the reserve rates
+        assert_eq!(calculate_realized_apy_bps(&snapshot, &fees(5_000_001, 1_000_000_000, start + 60)).unwrap(), None);
+        assert_eq!(calculate_realized_apy_bps(&KaminoInterestSnapshot::default(), &current).unwrap(), None);
+
+        // The window keeps growing until the snapshot interval is over
+        assert!(!roll_kamino_interest_snapshot(&mut snapshot, &fees(5_000_100, 1_000_000_000, start + DAY)));
+        assert!(roll_kamino_interest_snapshot(&mut snapshot, &current));
+        assert_eq!(snapshot.timestamp, start + 73 * DAY);
+    }
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+
+use crate::{errors::AggregatorError, helpers::deposit_to_kamino::KaminoVault};
+
+
+
+fn get_farm_harvest_reward_discriminator() -> Vec<u8> {
+    vec![68, 200, 228, 233, 184, 32, 226, 188]
+}
+
+
+/// Farm reward accounts that are not part of the Kamino remaining accounts
+pub struct FarmRewardAccounts<'info> {
+    pub farm_global_config: AccountInfo<'info>,
+    pub reward_mint: AccountInfo<'info>,
+    /// Config owned token account that receives the reward
+    pub reward_token_account: AccountInfo<'info>,
+    pub rewards_vault: AccountInfo<'info>,
+    pub rewards_treasury_vault: AccountInfo<'info>,
+    pub reward_token_program: AccountInfo<'info>,
+}
+
+
+impl<'info> KaminoVault<'info> {
+
+    /// Claims the farm reward at `reward_index` accrued on the shares the config staked
+    pub fn harvest_farm_reward(
+        &self,
+        reward_accounts: &FarmRewardAccounts<'info>,
+        reward_index: u64,
+        config_bump: u8,
+    ) -> Result<()> {
+        if !self.has_farm() {
+            msg!("Vault has no farm (vault_farm == default), nothing to harvest");
+            return Ok(());
+        }
+
+        let mut instruction_data = get_farm_harvest_reward_discriminator();
+        instruction_data.extend_from_slice(&reward_index.to_le_bytes());
+
+        let account_metas = vec![
+            AccountMeta::new(*self.config.key, true),
+            AccountMeta::new(*self.config_state.key, false),
+            AccountMeta::new(*self.farm_state.key, false),
+            AccountMeta::new_readonly(*reward_accounts.farm_global_config.key, false),
+            AccountMeta::new_readonly(*reward_accounts.reward_mint.key, false),
+            AccountMeta::new(*reward_accounts.reward_token_account.key, false),
+            AccountMeta::new(*reward_accounts.rewards_vault.key, false),
+            AccountMeta::new(*reward_accounts.rewards_treasury_vault.key, false),
+            AccountMeta::new_readonly(*self.farm_vault_authority.key, false),
+            AccountMeta::new_readonly(*self.scope_prices.key, false),
+            AccountMeta::new_readonly(*reward_accounts.reward_token_program.key, false),
+        ];
+
+        let instruction = Instruction {
+            program_id: *self.farm_program.key,
+            accounts: account_metas,
+            data: instruction_data,
+        };
+
+        let seeds = &[b"config".as_ref(), &[config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts_for_cpi = vec![
+            self.config.clone(),
+            self.config_state.clone(),
+            self.farm_state.clone(),
+            reward_accounts.farm_global_config.clone(),
+            reward_accounts.reward_mint.clone(),
+            reward_accounts.reward_token_account.clone(),
+            reward_accounts.rewards_vault.clone(),
+            reward_accounts.rewards_treasury_vault.clone(),
+            self.farm_vault_authority.clone(),
+            self.scope_prices.clone(),
+            reward_accounts.reward_token_program.clone(),
+        ];
+
+        invoke_signed(&instruction, &accounts_for_cpi, signer_seeds)
+        .map_err(|e| {
+            msg!("Kamino farm harvest CPI failed with error: {:?}", e);
+            AggregatorError::CpiToLendingProgramFailed
+        })?;
+
+        Ok(())
+    }
+}
@@ -1,47 +1,24 @@
-
 use anchor_lang::prelude::*;
 
-pub mod vault_offsets {
-    pub const TOKEN_AVAILABLE: usize = 216;                    
-    pub const SHARES_ISSUED: usize = TOKEN_AVAILABLE + 8;      
-    pub const PENDING_FEES_SF: usize = SHARES_ISSUED + 8 + 56; 
-    
-    pub const VAULT_ALLOCATION_STRATEGY: usize = PENDING_FEES_SF + 16; 
-    
-    pub const VAULT_ALLOCATION_SIZE: usize = 2160;
-    pub const MAX_RESERVES: usize = 25;
-}
+use crate::{
+    constants::{RESERVE_DISCRIMINATOR, VAULT_STATE_DISCRIMINATOR},
+    errors::AggregatorError,
+    helpers::zero_copy_view::zero_copy_view,
+    states::kamino::{CurvePoint, Reserve, VaultState},
+};
+
+
+pub const BORROW_RATE_CURVE_POINTS: usize = 11;
 
 
-pub mod allocation_offsets {
-    pub const RESERVE: usize = 0;                          
-    pub const CTOKEN_VAULT: usize = RESERVE + 32;          
-    pub const TARGET_ALLOCATION_WEIGHT: usize = CTOKEN_VAULT + 32; 
-    pub const TOKEN_ALLOCATION_CAP: usize = TARGET_ALLOCATION_WEIGHT + 8; 
-    pub const CTOKEN_VAULT_BUMP: usize = TOKEN_ALLOCATION_CAP + 8; 
-    
-    pub const CTOKEN_ALLOCATION: usize = CTOKEN_VAULT_BUMP + 8 + 1016; 
+/// Zero-copy view over a Kamino vault, the layout is size-checked in `states::kamino::vault_state`
+pub fn load_vault_state(data: &[u8]) -> Result<&VaultState> {
+    zero_copy_view(data, &VAULT_STATE_DISCRIMINATOR)
 }
 
-pub mod reserve_offsets {
-    pub const LAST_UPDATE_SLOT: usize = 8;
-    
-    pub const LIQUIDITY_START: usize = 120;
-    
-    pub const AVAILABLE_AMOUNT: usize = LIQUIDITY_START + 96;           
-    pub const BORROWED_AMOUNT_SF: usize = AVAILABLE_AMOUNT + 8;         
-    pub const ACCUMULATED_PROTOCOL_FEES_SF: usize = BORROWED_AMOUNT_SF + 16 + 96; 
-    pub const ACCUMULATED_REFERRER_FEES_SF: usize = ACCUMULATED_PROTOCOL_FEES_SF + 16; 
-    pub const PENDING_REFERRER_FEES_SF: usize = ACCUMULATED_REFERRER_FEES_SF + 16; 
-    
-    pub const COLLATERAL_START: usize = LIQUIDITY_START + 1232 + 1200;
-    pub const MINT_TOTAL_SUPPLY: usize = COLLATERAL_START + 32;  
-    pub const CONFIG_START: usize = COLLATERAL_START + 1096 + 1200;
-    
-    pub const PROTOCOL_ORDER_EXECUTION_FEE_PCT: usize = CONFIG_START + 13;  
-    pub const PROTOCOL_TAKE_RATE_PCT: usize = CONFIG_START + 14;            
-    pub const PROTOCOL_LIQUIDATION_FEE_PCT: usize = CONFIG_START + 15;      
-    pub const HOST_FIXED_INTEREST_RATE_BPS: usize = CONFIG_START + 2;       
+/// Zero-copy view over a klend reserve, the layout is size-checked in `states::kamino::reserve`
+pub fn load_reserve(data: &[u8]) -> Result<&Reserve> {
+    zero_copy_view(data, &RESERVE_DISCRIMINATOR)
 }
 
 #[derive(Debug)]
@@ -52,16 +29,36 @@ pub struct VaultStateFields {
 }
 
 pub fn read_vault_state_fields(data: &[u8]) -> Result<VaultStateFields> {
-    let data = &data[8..];
-    
-    if data.len() < vault_offsets::PENDING_FEES_SF + 16 {
-        return Err(ProgramError::InvalidAccountData.into());
-    }
-    
+    let vault = load_vault_state(data)?;
+
     Ok(VaultStateFields {
-        token_available: read_u64(data, vault_offsets::TOKEN_AVAILABLE),
-        shares_issued: read_u64(data, vault_offsets::SHARES_ISSUED),
-        pending_fees_sf: read_u128(data, vault_offsets::PENDING_FEES_SF),
+        token_available: vault.token_available,
+        shares_issued: vault.shares_issued,
+        pending_fees_sf: vault.pending_fees_sf.get(),
+    })
+}
+
+/// Inputs for the vault's fees and realized interest, see `get_kamino_net_apy`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VaultFeeFields {
+    pub performance_fee_bps: u64,
+    pub management_fee_bps: u64,
+    pub last_fee_charge_timestamp: u64,
+    /// AUM at the last fee charge
+    pub prev_aum_sf: u128,
+    /// Interest earned since creation, fees included
+    pub cumulative_earned_interest_sf: u128,
+}
+
+pub fn read_vault_fee_fields(data: &[u8]) -> Result<VaultFeeFields> {
+    let vault = load_vault_state(data)?;
+
+    Ok(VaultFeeFields {
+        performance_fee_bps: vault.performance_fee_bps,
+        management_fee_bps: vault.management_fee_bps,
+        last_fee_charge_timestamp: vault.last_fee_charge_timestamp,
+        prev_aum_sf: vault.prev_aum_sf.get(),
+        cumulative_earned_interest_sf: vault.cumulative_earned_interest_sf.get(),
     })
 }
 
@@ -74,22 +71,19 @@ pub fn read_vault_allocation(
     data: &[u8],
     allocation_index: usize,
 ) -> Result<VaultAllocationFields> {
-    // Skip 8-byte discriminator
-    let data = &data[8..];
-    
-    let base_offset = vault_offsets::VAULT_ALLOCATION_STRATEGY 
-        + (allocation_index * vault_offsets::VAULT_ALLOCATION_SIZE);
-    
-    if data.len() < base_offset + allocation_offsets::CTOKEN_ALLOCATION + 8 {
-        return Err(ProgramError::InvalidAccountData.into());
-    }
-    
+    let allocation = load_vault_state(data)?
+        .vault_allocation_strategy
+        .get(allocation_index)
+        .ok_or(AggregatorError::InvalidAccountData)?;
+
     Ok(VaultAllocationFields {
-        reserve: read_pubkey(data, base_offset + allocation_offsets::RESERVE),
-        ctoken_allocation: read_u64(data, base_offset + allocation_offsets::CTOKEN_ALLOCATION),
+        reserve: allocation.reserve,
+        ctoken_allocation: allocation.ctoken_allocation,
     })
 }
 
+
+#[derive(Debug)]
 pub struct ReserveFields {
     pub last_update_slot: u64,
     pub available_amount: u64,
@@ -103,77 +97,116 @@ pub struct ReserveFields {
 }
 
 pub fn read_reserve_fields(data: &[u8]) -> Result<ReserveFields> {
-    // Skip 8-byte discriminator
-    let data = &data[8..];
-    
-    if data.len() < reserve_offsets::MINT_TOTAL_SUPPLY + 8 {
-        return Err(ProgramError::InvalidAccountData.into());
-    }
-    
+    let reserve = load_reserve(data)?;
+    let liquidity = &reserve.liquidity;
+
     Ok(ReserveFields {
-        last_update_slot: read_u64(data, reserve_offsets::LAST_UPDATE_SLOT),
-        available_amount: read_u64(data, reserve_offsets::AVAILABLE_AMOUNT),
-        borrowed_amount_sf: read_u128(data, reserve_offsets::BORROWED_AMOUNT_SF),
-        accumulated_protocol_fees_sf: read_u128(data, reserve_offsets::ACCUMULATED_PROTOCOL_FEES_SF),
-        accumulated_referrer_fees_sf: read_u128(data, reserve_offsets::ACCUMULATED_REFERRER_FEES_SF),
-        pending_referrer_fees_sf: read_u128(data, reserve_offsets::PENDING_REFERRER_FEES_SF),
-        mint_total_supply: read_u64(data, reserve_offsets::MINT_TOTAL_SUPPLY),
-        protocol_take_rate_pct: read_u8(data, reserve_offsets::PROTOCOL_TAKE_RATE_PCT),
-        host_fixed_interest_rate_bps: read_u16(data, reserve_offsets::HOST_FIXED_INTEREST_RATE_BPS),
+        last_update_slot: reserve.last_update.slot,
+        available_amount: liquidity.available_amount,
+        borrowed_amount_sf: liquidity.borrowed_amount_sf.get(),
+        accumulated_protocol_fees_sf: liquidity.accumulated_protocol_fees_sf.get(),
+        accumulated_referrer_fees_sf: liquidity.accumulated_referrer_fees_sf.get(),
+        pending_referrer_fees_sf: liquidity.pending_referrer_fees_sf.get(),
+        mint_total_supply: reserve.collateral.mint_total_supply,
+        protocol_take_rate_pct: reserve.config.protocol_take_rate_pct,
+        host_fixed_interest_rate_bps: reserve.config.host_fixed_interest_rate_bps,
     })
 }
 
-
-#[inline]
-fn read_u8(data: &[u8], offset: usize) -> u8 {
-    data[offset]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawalCapFields {
+    pub config_capacity: i64,
+    pub current_total: i64,
+    pub last_interval_start_timestamp: u64,
+    pub config_interval_length_seconds: u64,
 }
 
-#[inline]
-fn read_u16(data: &[u8], offset: usize) -> u16 {
-    u16::from_le_bytes([data[offset], data[offset + 1]])
+/// Reserve config that decides whether the vault can move liquidity in or out, see `reserve_preflight`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReserveLimitFields {
+    pub status: u8,
+    pub block_ctoken_usage: u8,
+    pub deposit_limit: u64,
+    pub deposit_withdrawal_cap: WithdrawalCapFields,
 }
 
-#[inline]
-fn read_u64(data: &[u8], offset: usize) -> u64 {
-    let mut bytes = [0u8; 8];
-    bytes.copy_from_slice(&data[offset..offset + 8]);
-    u64::from_le_bytes(bytes)
+pub fn read_reserve_limit_fields(data: &[u8]) -> Result<ReserveLimitFields> {
+    let config = &load_reserve(data)?.config;
+    let cap = &config.deposit_withdrawal_cap;
+
+    Ok(ReserveLimitFields {
+        status: config.status,
+        block_ctoken_usage: config.block_ctoken_usage,
+        deposit_limit: config.deposit_limit,
+        deposit_withdrawal_cap: WithdrawalCapFields {
+            config_capacity: cap.config_capacity,
+            current_total: cap.current_total,
+            last_interval_start_timestamp: cap.last_interval_start_timestamp,
+            config_interval_length_seconds: cap.config_interval_length_seconds,
+        },
+    })
 }
 
-#[inline]
-fn read_u128(data: &[u8], offset: usize) -> u128 {
-    let mut bytes = [0u8; 16];
-    bytes.copy_from_slice(&data[offset..offset + 16]);
-    u128::from_le_bytes(bytes)
+pub fn read_reserve_borrow_rate_curve(data: &[u8]) -> Result<[CurvePoint; BORROW_RATE_CURVE_POINTS]> {
+    Ok(load_reserve(data)?.config.borrow_rate_curve.points)
 }
 
-#[inline]
-fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
-    Pubkey::new_from_array(
-        data[offset..offset + 32]
-            .try_into()
-            .expect("slice with incorrect length")
-    )
-}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use crate::states::kamino::{PodU128, VaultAllocation};
+    use bytemuck::Zeroable;
+    use std::mem::offset_of;
+
     #[test]
-    fn test_vault_offsets() {
-        // Verify key offset calculations
-        assert_eq!(vault_offsets::TOKEN_AVAILABLE, 216);
-        assert_eq!(vault_offsets::SHARES_ISSUED, 224);
-        assert_eq!(vault_offsets::PENDING_FEES_SF, 288);
+    fn test_vault_state_layout() {
+        // Offsets past the discriminator, from the kvault IDL
+        assert_eq!(offset_of!(VaultState, token_available), 216);
+        assert_eq!(offset_of!(VaultState, shares_issued), 224);
+        assert_eq!(offset_of!(VaultState, performance_fee_bps), 248);
+        assert_eq!(offset_of!(VaultState, prev_aum_sf), 272);
+        assert_eq!(offset_of!(VaultState, pending_fees_sf), 288);
+        assert_eq!(offset_of!(VaultState, vault_allocation_strategy), 304);
+        assert_eq!(offset_of!(VaultState, cumulative_earned_interest_sf), 58472);
+        assert_eq!(offset_of!(VaultState, allocation_admin), 58640);
+        assert_eq!(offset_of!(VaultAllocation, ctoken_allocation), 1104);
     }
-    
+
     #[test]
-    fn test_reserve_offsets() {
-        // Verify reserve offset calculations
-        assert_eq!(reserve_offsets::LAST_UPDATE_SLOT, 8);
-        assert_eq!(reserve_offsets::LIQUIDITY_START, 120);
+    fn test_reserve_layout() {
+        // Offsets past the discriminator, from the klend IDL
+        assert_eq!(offset_of!(Reserve, last_update), 8);
+        assert_eq!(offset_of!(Reserve, liquidity.available_amount), 216);
+        assert_eq!(offset_of!(Reserve, liquidity.borrowed_amount_sf), 224);
+        assert_eq!(offset_of!(Reserve, liquidity.accumulated_protocol_fees_sf), 336);
+        assert_eq!(offset_of!(Reserve, liquidity.pending_referrer_fees_sf), 368);
+        assert_eq!(offset_of!(Reserve, collateral.mint_total_supply), 2584);
+        assert_eq!(offset_of!(Reserve, config), 4848);
+        assert_eq!(offset_of!(Reserve, config.block_ctoken_usage), 4854);
+        assert_eq!(offset_of!(Reserve, config.protocol_take_rate_pct), 4862);
+        assert_eq!(offset_of!(Reserve, config.borrow_rate_curve), 4912);
+        assert_eq!(offset_of!(Reserve, config.deposit_limit), 5008);
+        assert_eq!(offset_of!(Reserve, config.deposit_withdrawal_cap), 5408);
     }
-}
 
+    #[test]
+    fn test_reserve_view() {
+        let mut reserve = Reserve::zeroed();
+        reserve.last_update.slot = 42;
+        reserve.liquidity.available_amount = 1_000_000;
+        reserve.liquidity.borrowed_amount_sf = PodU128::new(3u128 << 60);
+        reserve.collateral.mint_total_supply = 900_000;
+        reserve.config.deposit_limit = 5_000_000;
+
+        let data = [RESERVE_DISCRIMINATOR.as_slice(), bytemuck::bytes_of(&reserve)].concat();
+        let fields = read_reserve_fields(&data).unwrap();
+        assert_eq!((fields.last_update_slot, fields.available_amount, fields.mint_total_supply), (42, 1_000_000, 900_000));
+        assert_eq!(fields.borrowed_amount_sf, 3u128 << 60);
+        assert_eq!(read_reserve_limit_fields(&data).unwrap().deposit_limit, 5_000_000);
+
+        // Another account type or a truncated reserve is an error, not a panic
+        assert!(read_reserve_fields(&[VAULT_STATE_DISCRIMINATOR.as_slice(), bytemuck::bytes_of(&reserve)].concat()).is_err());
+        assert!(read_reserve_fields(&data[..4000]).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::kamino::kamino_account_reader::read_vault_allocation,
+    states::{kamino::MAX_RESERVES, ReserveWithdrawAccounts},
+};
+
+
+/// Kamino vault state in remaining accounts
+pub const KAMINO_VAULT_STATE_INDEX: usize = 13;
+/// klend program and instruction sysvar in the Kamino vault accounts, shared with the klend reserve segment
+pub const KLEND_PROGRAM_INDEX: usize = 18;
+pub const INSTRUCTION_SYSVAR_INDEX: usize = 29;
+/// First reserve group, right after the 13 JupLend and 20 Kamino vault accounts
+pub const KAMINO_RESERVES_START: usize = 33;
+/// Accounts per reserve group, in `ReserveWithdrawAccounts` order
+pub const RESERVE_ACCOUNTS_LEN: usize = 7;
+
+
+/*
+   Reserves the vault allocates to, in `vault_allocation_strategy` order.
+
+   The strategy has MAX_RESERVES slots and removed reserves leave an empty slot behind,
+   so empty slots are skipped rather than treated as the end of the list.
+*/
+pub fn get_vault_reserves(vault_data: &[u8]) -> Result<Vec<Pubkey>> {
+
+    let mut reserves = Vec::new();
+    for i in 0..MAX_RESERVES {
+        let allocation = read_vault_allocation(vault_data, i)?;
+        if allocation.reserve != Pubkey::default() {
+            reserves.push(allocation.reserve);
+        }
+    }
+
+    Ok(reserves)
+}
+
+
+/*
+   Reads one group of RESERVE_ACCOUNTS_LEN accounts per vault reserve, starting at
+   KAMINO_RESERVES_START. Groups must follow the vault's allocation order and each group's
+   first account must be that reserve.
+
+   remaining_accounts = [13 JupLend] + [20 Kamino vault] + [7 x number of vault reserves] + ...
+*/
+pub fn parse_reserve_accounts<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<Vec<ReserveWithdrawAccounts<'info>>> {
+    let vault_state = remaining_accounts
+        .get(KAMINO_VAULT_STATE_INDEX)
+        .ok_or(AggregatorError::MissingAccount)?;
+    let vault_reserves = get_vault_reserves(&vault_state.try_borrow_data()?)?;
+
+    let mut reserve_accounts = Vec::with_capacity(vault_reserves.len());
+    for (i, vault_reserve) in vault_reserves.iter().enumerate() {
+        let start = KAMINO_RESERVES_START + i * RESERVE_ACCOUNTS_LEN;
+        let group = remaining_accounts
+            .get(start..start + RESERVE_ACCOUNTS_LEN)
+            .ok_or(AggregatorError::MissingAccount)?;
+
+        if group[0].key != vault_reserve {
+            msg!("Reserve group {} is {}, vault expects {}", i, group[0].key, vault_reserve);
+            return Err(AggregatorError::InvalidReserveAccount.into());
+        }
+
+        reserve_accounts.push(ReserveWithdrawAccounts {
+            reserve: group[0].clone(),
+            ctoken_vault: group[1].clone(),
+            lending_market: group[2].clone(),
+            lending_market_authority: group[3].clone(),
+            reserve_liquidity_supply: group[4].clone(),
+            reserve_collateral_mint: group[5].clone(),
+            reserve_collateral_token_program: group[6].clone(),
+        });
+    }
+
+    Ok(reserve_accounts)
+}
+
+
+/// Just the reserve of each group, what the balance, APY and liquidity reads need
+pub fn get_reserve_account_infos<'info>(remaining_accounts: &[AccountInfo<'info>]) -> Result<Vec<AccountInfo<'info>>> {
+    Ok(parse_reserve_accounts(remaining_accounts)?
+        .into_iter()
+        .map(|accounts| accounts.reserve)
+        .collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::VAULT_STATE_DISCRIMINATOR, states::kamino::VaultState};
+    use bytemuck::Zeroable;
+
+    fn vault_data(reserves: &[(usize, Pubkey)]) -> Vec<u8> {
+        let mut vault = VaultState::zeroed();
+        for (slot, reserve) in reserves {
+            vault.vault_allocation_strategy[*slot].reserve = *reserve;
+        }
+        [VAULT_STATE_DISCRIMINATOR.as_slice(), bytemuck::bytes_of(&vault)].concat()
+    }
+
+    #[test]
+    fn test_vault_reserves_skip_empty_slots() {
+        let reserves: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let data = vault_data(&[(0, reserves[0]), (2, reserves[1]), (24, reserves[2])]);
+
+        assert_eq!(get_vault_reserves(&data).unwrap(), reserves);
+        assert!(get_vault_reserves(&data[8..]).is_err());
+    }
+}
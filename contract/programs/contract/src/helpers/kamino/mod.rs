@@ -2,10 +2,28 @@ pub mod deposit_to_kamino;
 
 pub mod withdraw_from_kamino;
 
+pub mod harvest_from_kamino;
+
 pub mod get_kamino_balance;
 
 pub mod kamino_account_reader;
 
+pub mod kamino_reserve_accounts;
+
+pub mod reserve_preflight;
+
+pub mod get_kamino_liquidity;
+
+pub mod get_kamino_apy;
+
+pub mod get_kamino_net_apy;
+
+pub mod get_kamino_farm_rewards;
+
+pub mod farm_state_helper;
+
+pub mod farm_position;
+
 pub mod get_kamino_farm_active_balance;
 pub use get_kamino_farm_active_balance::*;
 
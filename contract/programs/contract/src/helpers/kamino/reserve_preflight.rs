@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::kamino::{
+        get_kamino_balance::calculate_total_supply,
+        kamino_account_reader::{read_reserve_fields, read_reserve_limit_fields, ReserveLimitFields, WithdrawalCapFields},
+    },
+    states::kamino::FractionExtra,
+};
+
+
+/// `ReserveConfig.status` of a reserve that takes deposits, Obsolete and Hidden reserves only pay out
+pub const RESERVE_STATUS_ACTIVE: u8 = 0;
+
+
+/*
+   Pre-flight checks on the reserve config, so planners can leave a constrained reserve
+   alone instead of sending a CPI that reverts the whole transaction.
+
+   - status: only Active reserves accept deposits
+   - block_ctoken_usage: the vault can neither mint nor redeem cTokens
+   - deposit_limit: cap on the reserve's total supply
+   - deposit_withdrawal_cap: liquidity that may leave per interval, deposits don't free room here
+*/
+pub fn reserve_withdraw_room(limits: &ReserveLimitFields, now: u64) -> u64 {
+    if limits.block_ctoken_usage != 0 {
+        return 0;
+    }
+
+    withdrawal_cap_room(&limits.deposit_withdrawal_cap, now)
+}
+
+
+pub fn reserve_deposit_room(limits: &ReserveLimitFields, total_supply: u64) -> u64 {
+    if limits.status != RESERVE_STATUS_ACTIVE || limits.block_ctoken_usage != 0 {
+        return 0;
+    }
+
+    limits.deposit_limit.saturating_sub(total_supply)
+}
+
+
+/// Room left in the current interval, u64::MAX when the cap is disabled
+pub fn withdrawal_cap_room(cap: &WithdrawalCapFields, now: u64) -> u64 {
+    if cap.config_interval_length_seconds == 0 || cap.config_capacity < 0 {
+        return u64::MAX;
+    }
+
+    // A new interval starts from an empty accumulator
+    let interval_over = now.saturating_sub(cap.last_interval_start_timestamp) >= cap.config_interval_length_seconds;
+    let current_total = if interval_over { 0 } else { cap.current_total };
+
+    (cap.config_capacity as i128 - current_total as i128).clamp(0, u64::MAX as i128) as u64
+}
+
+
+pub fn get_reserve_withdraw_room(reserve_account: &AccountInfo, now: u64) -> Result<u64> {
+    let reserve_data = reserve_account.try_borrow_data()?;
+    Ok(reserve_withdraw_room(&read_reserve_limit_fields(&reserve_data)?, now))
+}
+
+
+pub fn get_reserve_deposit_room(reserve_account: &AccountInfo) -> Result<u64> {
+    let reserve_data = reserve_account.try_borrow_data()?;
+    let total_supply = calculate_total_supply(&read_reserve_fields(&reserve_data)?)?
+        .try_to_floor::<u64>()
+        .ok_or(AggregatorError::MathOverflow)?;
+    let room = reserve_deposit_room(&read_reserve_limit_fields(&reserve_data)?, total_supply);
+
+    if room == 0 {
+        msg!("Reserve {} takes no deposits", reserve_account.key);
+    }
+    Ok(room)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(status: u8, block_ctoken_usage: u8, cap: WithdrawalCapFields) -> ReserveLimitFields {
+        ReserveLimitFields { status, block_ctoken_usage, deposit_limit: 10_000_000, deposit_withdrawal_cap: cap }
+    }
+
+    fn cap(config_capacity: i64, current_total: i64) -> WithdrawalCapFields {
+        WithdrawalCapFields {
+            config_capacity,
+            current_total,
+            last_interval_start_timestamp: 1_000,
+            config_interval_length_seconds: 3_600,
+        }
+    }
+
+    #[test]
+    fn test_withdrawal_cap_room() {
+        assert_eq!(withdrawal_cap_room(&cap(1_000_000, 400_000), 2_000), 600_000);
+        // Over the cap, or the interval ended and the accumulator resets
+        assert_eq!(withdrawal_cap_room(&cap(1_000_000, 1_200_000), 2_000), 0);
+        assert_eq!(withdrawal_cap_room(&cap(1_000_000, 1_200_000), 4_600), 1_000_000);
+        // Deposits push the total below zero and leave more room
+        assert_eq!(withdrawal_cap_room(&cap(1_000_000, -500_000), 2_000), 1_500_000);
+        assert_eq!(withdrawal_cap_room(&WithdrawalCapFields::default(), 2_000), u64::MAX);
+    }
+
+    #[test]
+    fn test_constrained_reserves() {
+        let open = limits(RESERVE_STATUS_ACTIVE, 0, WithdrawalCapFields::default());
+        assert_eq!(reserve_deposit_room(&open, 9_000_000), 1_000_000);
+        assert_eq!(reserve_deposit_room(&open, 12_000_000), 0);
+
+        // Obsolete reserves still pay out, blocked cTokens stop both ways
+        let obsolete = limits(1, 0, cap(1_000_000, 0));
+        assert_eq!(reserve_deposit_room(&obsolete, 0), 0);
+        assert_eq!(reserve_withdraw_room(&obsolete, 2_000), 1_000_000);
+
+        let blocked = limits(RESERVE_STATUS_ACTIVE, 1, WithdrawalCapFields::default());
+        assert_eq!(reserve_deposit_room(&blocked, 0), 0);
+        assert_eq!(reserve_withdraw_room(&blocked, 2_000), 0);
+    }
+}
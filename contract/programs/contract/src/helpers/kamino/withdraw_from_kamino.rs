@@ -6,7 +6,9 @@ use crate::{
         deposit_to_kamino::KaminoVault,
         kamino::{
             get_kamino_balance::get_kamino_shares_amount_from_usdc, 
-            get_kamino_farm_active_balance
+            farm_position::get_kamino_farm_position,
+            get_kamino_farm_active_balance,
+            get_kamino_liquidity::get_kamino_available_liquidity
         },
     }, states::ReserveWithdrawAccounts
 };
@@ -40,7 +42,7 @@ impl<'info> KaminoVault<'info> {
     }
 
     
-    fn unstake_from_farm(&self, shares_amount: u64, config_bump: u8) -> Result<()> {
+    pub fn unstake_from_farm(&self, shares_amount: u64, config_bump: u8) -> Result<()> {
         msg!("Unstaking {} shares from farm", shares_amount);
         
         // Convert u64 to u128 as required by the Kamino Farm unstake instruction
@@ -79,8 +81,8 @@ impl<'info> KaminoVault<'info> {
         Ok(())
     }
 
-    /// we have kamino farm staking but this implement is here for future use
-    fn withdraw_unstaked_from_farm(&self, config_bump: u8) -> Result<()> {
+    /// Moves unstaked shares whose cooldown is over back into the config's shares ATA
+    pub fn withdraw_unstaked_from_farm(&self, config_bump: u8) -> Result<()> {
         msg!("Withdrawing unstaked deposits from farm");
         
         let instruction_data = get_farm_withdraw_unstaked_discriminator();
@@ -134,7 +136,7 @@ impl<'info> KaminoVault<'info> {
         let mut instruction_data = get_withdraw_discriminator();
         instruction_data.extend_from_slice(&shares_amount.to_le_bytes());
 
-        let mut account_metas = Vec::with_capacity(29);
+        let mut account_metas = Vec::with_capacity(25 + self.reserve_accounts.len() * 2);
         account_metas.push(AccountMeta::new(*self.config.key, true));
         account_metas.push(AccountMeta::new(*self.vault_state.key, false));
         // add global config here 
@@ -161,10 +163,13 @@ impl<'info> KaminoVault<'info> {
         account_metas.push(AccountMeta::new_readonly(*self.instruction_sysvar.key, false));
         account_metas.push(AccountMeta::new_readonly(*self.event_authority.key, false));
         account_metas.push(AccountMeta::new_readonly(*self.kamino_lending_vault_program.key, false));
-        account_metas.push(AccountMeta::new(*self.reserve_accounts[0].reserve.key, false));
-        account_metas.push(AccountMeta::new(*self.reserve_accounts[1].reserve.key, false));
-        account_metas.push(AccountMeta::new_readonly(*self.reserve_accounts[0].lending_market.key, false));
-        account_metas.push(AccountMeta::new_readonly(*self.reserve_accounts[1].lending_market.key, false));
+        // Every vault reserve, then every lending market, same as deposit
+        for account in &self.reserve_accounts {
+            account_metas.push(AccountMeta::new(*account.reserve.key, false));
+        }
+        for account in &self.reserve_accounts {
+            account_metas.push(AccountMeta::new_readonly(*account.lending_market.key, false));
+        }
 
         let instruction = Instruction {
             program_id: *self.kamino_vault_program.key,
@@ -175,7 +180,7 @@ impl<'info> KaminoVault<'info> {
         let seeds = &[b"config".as_ref(), &[config_bump]];
         let signer_seeds = &[&seeds[..]];
 
-        let mut accounts_for_cpi = Vec::with_capacity(29);
+        let mut accounts_for_cpi = Vec::with_capacity(25 + self.reserve_accounts.len() * 2);
         accounts_for_cpi.push(self.config.clone());
         accounts_for_cpi.push(self.vault_state.clone());
         accounts_for_cpi.push(self.global_config.clone());
@@ -201,10 +206,12 @@ impl<'info> KaminoVault<'info> {
         accounts_for_cpi.push(self.instruction_sysvar.clone());
         accounts_for_cpi.push(self.event_authority.clone());
         accounts_for_cpi.push(self.kamino_lending_vault_program.clone());
-        accounts_for_cpi.push(self.reserve_accounts[0].reserve.clone());
-        accounts_for_cpi.push(self.reserve_accounts[1].reserve.clone());
-        accounts_for_cpi.push(self.reserve_accounts[0].lending_market.clone());
-        accounts_for_cpi.push(self.reserve_accounts[1].lending_market.clone());
+        for account in &self.reserve_accounts {
+            accounts_for_cpi.push(account.reserve.clone());
+        }
+        for account in &self.reserve_accounts {
+            accounts_for_cpi.push(account.lending_market.clone());
+        }
 
         invoke_signed(&instruction, &accounts_for_cpi, signer_seeds)
             .map_err(|e| {
@@ -220,23 +227,22 @@ impl<'info> KaminoVault<'info> {
         msg!("Withdrawing {} shares from vault (with allocations)", shares_amount);
         msg!("Number of reserves: {}", self.reserve_accounts.len());
         
-        
-        let shares_remaining = shares_amount;
-        
-        for (index, reserve) in self.reserve_accounts.iter().enumerate() {
-            msg!("Withdrawing from reserve {} of {}", index + 1, self.reserve_accounts.len());
-            
-            
-            let amount_for_reserve = if index == self.reserve_accounts.len() - 1 {
-                
-                shares_remaining
-            } else {
-                0
-            };
-            
-            if amount_for_reserve > 0 {
-                self.withdraw_from_single_reserve(reserve, amount_for_reserve, config_bump)?;
-            }
+        // Redeem from the reserve that can pay out the most right now
+        let reserves: Vec<AccountInfo<'info>> = self.reserve_accounts.iter().map(|x| x.reserve.clone()).collect();
+        let liquidity = get_kamino_available_liquidity(
+            &self.vault_state,
+            &reserves,
+            Some(Clock::get()?.slot),
+        )?;
+
+        let reserve_index = liquidity
+            .reserve_index
+            .unwrap_or(self.reserve_accounts.len() - 1);
+
+        msg!("Withdrawing from reserve {} of {}", reserve_index + 1, self.reserve_accounts.len());
+
+        if shares_amount > 0 {
+            self.withdraw_from_single_reserve(&self.reserve_accounts[reserve_index], shares_amount, config_bump)?;
         }
 
         Ok(())
@@ -334,13 +340,23 @@ impl<'info> KaminoVault<'info> {
         )?;
 
         if self.has_farm() {
-            let shares_in_ata = user_shares_ata.amount;
+            let (position, timing) = get_kamino_farm_position(user_shares_ata, &self.config_state, &self.farm_state)?;
+            let mut shares_in_ata = position.unstaked;
             msg!("Vault has farm. Shares in ATA: {}", shares_in_ata);
+
+            // An unstake requested by `crank_kamino_farm` whose cooldown is over
+            if position.pending_withdrawal_ready {
+                msg!("Withdrawing {} unstaked shares from farm", position.pending_withdrawal);
+                self.withdraw_unstaked_from_farm(config_bump)?;
+                shares_in_ata = shares_in_ata.saturating_add(position.pending_withdrawal);
+            }
             
             // Check if we need to unstake (not enough shares in ATA)
             if shares_amount > shares_in_ata {
                 msg!("Need to unstake from farm");
-                
+
+                // With a cooldown the unstaked shares only come back in a later transaction
+                require!(timing.withdrawal_cooldown == 0, AggregatorError::FarmCooldownActive);
                 
                 let amount_to_unstake = if shares_amount == u64::MAX {
                     u64::MAX
@@ -0,0 +1,147 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    errors::AggregatorError,
+    helpers::{
+        kamino::kamino_reserve_accounts::{INSTRUCTION_SYSVAR_INDEX, KLEND_PROGRAM_INDEX},
+        klend::klend_reserve_accounts::{check_klend_reserve_accounts, parse_klend_reserve_accounts},
+        refresh_protocol_state::refresh_klend_reserve,
+        segment_offsets::SegmentOffsets,
+    },
+    states::AggregatorConfig,
+};
+
+
+
+fn get_deposit_reserve_liquidity_discriminator() -> Vec<u8> {
+    vec![169, 201, 30, 126, 6, 205, 102, 68]
+}
+
+
+pub struct KlendReserve<'info> {
+    pub signer: AccountInfo<'info>,
+    pub asset_token_account: AccountInfo<'info>,
+    pub liquidity_mint: AccountInfo<'info>,
+
+    // Reserve accounts
+    pub reserve: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub lending_market_authority: AccountInfo<'info>,
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+    pub reserve_collateral_mint: AccountInfo<'info>,
+    pub ctoken_account: AccountInfo<'info>,
+    pub oracles: Vec<AccountInfo<'info>>,
+
+    pub collateral_token_program: AccountInfo<'info>,
+    pub liquidity_token_program: AccountInfo<'info>,
+    pub instruction_sysvar: AccountInfo<'info>,
+
+    // Target lending program
+    pub klend_program: AccountInfo<'info>,
+}
+
+
+impl<'info> KlendReserve<'info> {
+    pub fn new(
+        config: &Account<'info, AggregatorConfig>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        offsets: &SegmentOffsets,
+        vault_usdc: &InterfaceAccount<'info, TokenAccount>,
+        usdc_mint: &InterfaceAccount<'info, Mint>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<Box<KlendReserve<'info>>> {
+
+        let account = |index: usize| remaining_accounts.get(index).ok_or(AggregatorError::MissingAccount);
+        let accounts = parse_klend_reserve_accounts(remaining_accounts, offsets)?;
+        let klend_program = account(KLEND_PROGRAM_INDEX)?;
+
+        check_klend_reserve_accounts(config, &accounts, klend_program)?;
+
+        Ok(Box::new(Self {
+            signer: config.to_account_info(),
+            asset_token_account: vault_usdc.to_account_info(),
+            liquidity_mint: usdc_mint.to_account_info(),
+            reserve: accounts.reserve,
+            lending_market: accounts.lending_market,
+            lending_market_authority: accounts.lending_market_authority,
+            reserve_liquidity_supply: accounts.reserve_liquidity_supply,
+            reserve_collateral_mint: accounts.reserve_collateral_mint,
+            ctoken_account: accounts.ctoken_account,
+            oracles: accounts.oracles,
+            collateral_token_program: accounts.collateral_token_program,
+            liquidity_token_program: token_program.to_account_info(),
+            instruction_sysvar: account(INSTRUCTION_SYSVAR_INDEX)?.clone(),
+            klend_program: klend_program.clone(),
+        }))
+    }
+
+    /// klend rejects deposits and redemptions on a reserve that wasn't refreshed this slot
+    pub fn refresh_reserve(&self) -> Result<()> {
+        refresh_klend_reserve(&self.klend_program, &self.reserve, &self.lending_market, &self.oracles)
+    }
+
+    pub fn deposit_to_klend(&self, amount: u64, config_bump: u8) -> Result<()> {
+
+        self.refresh_reserve()?;
+
+        let mut instruction_data = get_deposit_reserve_liquidity_discriminator();
+        instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+        let account_metas = vec![
+            // owner (signer)
+            AccountMeta::new_readonly(*self.signer.key, true),
+            // reserve (mutable)
+            AccountMeta::new(*self.reserve.key, false),
+            AccountMeta::new_readonly(*self.lending_market.key, false),
+            AccountMeta::new_readonly(*self.lending_market_authority.key, false),
+            AccountMeta::new_readonly(*self.liquidity_mint.key, false),
+            // reserve_liquidity_supply (mutable)
+            AccountMeta::new(*self.reserve_liquidity_supply.key, false),
+            // reserve_collateral_mint (mutable)
+            AccountMeta::new(*self.reserve_collateral_mint.key, false),
+            // user_source_liquidity (mutable)
+            AccountMeta::new(*self.asset_token_account.key, false),
+            // user_destination_collateral (mutable)
+            AccountMeta::new(*self.ctoken_account.key, false),
+            AccountMeta::new_readonly(*self.collateral_token_program.key, false),
+            AccountMeta::new_readonly(*self.liquidity_token_program.key, false),
+            AccountMeta::new_readonly(*self.instruction_sysvar.key, false),
+        ];
+
+        let instruction = Instruction {
+            program_id: *self.klend_program.key,
+            accounts: account_metas,
+            data: instruction_data,
+        };
+
+        let seeds = &[b"config".as_ref(), &[config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+
+        invoke_signed(
+            &instruction,
+            &[
+                self.signer.clone(),
+                self.reserve.clone(),
+                self.lending_market.clone(),
+                self.lending_market_authority.clone(),
+                self.liquidity_mint.clone(),
+                self.reserve_liquidity_supply.clone(),
+                self.reserve_collateral_mint.clone(),
+                self.asset_token_account.clone(),
+                self.ctoken_account.clone(),
+                self.collateral_token_program.clone(),
+                self.liquidity_token_program.clone(),
+                self.instruction_sysvar.clone(),
+            ],
+            signer_seeds,
+        )
+        .map_err(|e| {
+            msg!("klend deposit_reserve_liquidity CPI failed with error: {:?}", e);
+            AggregatorError::CpiToLendingProgramFailed
+        })?;
+
+        Ok(())
+    }
+}
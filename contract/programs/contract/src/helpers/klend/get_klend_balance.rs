@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::kamino::get_kamino_balance::calculate_collateral_exchange_rate,
+    states::kamino::{Fraction, FractionExtra},
+};
+
+
+/*
+   USDC behind the cTokens the config PDA holds in the klend reserve
+
+   balance = ctokens / exchange_rate, rounded down
+
+   exchange_rate is cTokens per USDC, the same rate the Kamino vault prices its reserve
+   allocations with, estimated forward to `current_slot` when the reserve wasn't refreshed.
+   A cToken account that doesn't exist yet holds nothing.
+*/
+pub fn get_klend_balance(ctoken_account: &AccountInfo, reserve: &AccountInfo, current_slot: Option<u64>) -> Result<u64> {
+    let ctokens = get_ctoken_amount(ctoken_account)?;
+    if ctokens == 0 {
+        return Ok(0);
+    }
+
+    collateral_to_liquidity(ctokens, calculate_collateral_exchange_rate(reserve, current_slot)?)
+}
+
+
+pub fn get_ctoken_amount(ctoken_account: &AccountInfo) -> Result<u64> {
+    if ctoken_account.data_is_empty() {
+        return Ok(0);
+    }
+
+    Ok(TokenAccount::try_deserialize(&mut &ctoken_account.try_borrow_data()?[..])?.amount)
+}
+
+
+/// USDC paid out for `ctokens`, rounded down like klend's redemption
+pub fn collateral_to_liquidity(ctokens: u64, exchange_rate: Fraction) -> Result<u64> {
+    Fraction::from(ctokens)
+        .checked_div(exchange_rate)
+        .and_then(|amount| amount.try_to_floor::<u64>())
+        .ok_or(AggregatorError::MathOverflow.into())
+}
+
+
+/// cTokens to redeem for at least `usdc_amount`, rounded up
+pub fn liquidity_to_collateral(usdc_amount: u64, exchange_rate: Fraction) -> Result<u64> {
+    Fraction::from(usdc_amount)
+        .checked_mul(exchange_rate)
+        .and_then(|ctokens| ctokens.try_to_ceil::<u64>())
+        .ok_or(AggregatorError::MathOverflow.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collateral_conversions_round_in_the_reserves_favour() {
+        // 0.8 cTokens per USDC
+        let rate = Fraction::from_num(4) / Fraction::from_num(5);
+
+        assert_eq!(collateral_to_liquidity(800_000, rate).unwrap(), 1_000_000);
+        assert_eq!(collateral_to_liquidity(800_001, rate).unwrap(), 1_000_001);
+        assert_eq!(liquidity_to_collateral(1_000_000, rate).unwrap(), 800_000);
+        assert_eq!(liquidity_to_collateral(1_000_001, rate).unwrap(), 800_001);
+
+        // Redeeming what liquidity_to_collateral asks for never pays out less than requested
+        let amount = 1_234_567;
+        assert!(collateral_to_liquidity(liquidity_to_collateral(amount, rate).unwrap(), rate).unwrap() >= amount);
+    }
+}
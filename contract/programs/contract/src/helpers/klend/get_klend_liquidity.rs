@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::helpers::kamino::{
+    kamino_account_reader::read_reserve_fields,
+    reserve_preflight::{get_reserve_deposit_room, get_reserve_withdraw_room},
+};
+
+
+/// What the reserve can pay out right now, its unborrowed liquidity within the withdrawal cap
+pub fn get_klend_available_liquidity(reserve: &AccountInfo) -> Result<u64> {
+    let now = Clock::get()?.unix_timestamp.max(0) as u64;
+
+    let available_amount = {
+        let reserve_data = reserve.try_borrow_data()?;
+        read_reserve_fields(&reserve_data)?.available_amount
+    };
+
+    Ok(available_amount.min(get_reserve_withdraw_room(reserve, now)?))
+}
+
+
+/// Room left under the reserve's deposit limit, 0 when it isn't Active or blocks cTokens
+pub fn get_klend_deposit_capacity(reserve: &AccountInfo) -> Result<u64> {
+    get_reserve_deposit_room(reserve)
+}
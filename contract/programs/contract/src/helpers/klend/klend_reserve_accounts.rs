@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::kamino::kamino_account_reader::load_reserve,
+    helpers::refresh_protocol_state::REFRESH_ORACLES_PER_RESERVE,
+    helpers::segment_offsets::SegmentOffsets,
+    states::AggregatorConfig,
+};
+
+
+/// Accounts in the klend reserve segment, in `KlendReserveAccounts` order
+pub const KLEND_RESERVE_ACCOUNTS_LEN: usize = 7 + REFRESH_ORACLES_PER_RESERVE;
+
+
+#[derive(Clone)]
+pub struct KlendReserveAccounts<'info> {
+    pub reserve: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub lending_market_authority: AccountInfo<'info>,
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+    pub reserve_collateral_mint: AccountInfo<'info>,
+    /// Config PDA's cToken ATA, created by `init_klend_reserve`
+    pub ctoken_account: AccountInfo<'info>,
+    pub collateral_token_program: AccountInfo<'info>,
+    /// pyth, switchboard price, switchboard twap and scope, klend's program id where the reserve has none.
+    /// klend only takes deposits and redemptions on a reserve refreshed in the same slot
+    pub oracles: Vec<AccountInfo<'info>>,
+}
+
+
+/*
+   The klend reserve segment sits right after the MarginFi segment. The klend program and
+   the instruction sysvar are shared with the Kamino vault accounts.
+*/
+pub fn parse_klend_reserve_accounts<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    offsets: &SegmentOffsets,
+) -> Result<KlendReserveAccounts<'info>> {
+    let start = offsets.klend;
+    let segment = remaining_accounts
+        .get(start..start + KLEND_RESERVE_ACCOUNTS_LEN)
+        .ok_or(AggregatorError::MissingAccount)?;
+
+    Ok(KlendReserveAccounts {
+        reserve: segment[0].clone(),
+        lending_market: segment[1].clone(),
+        lending_market_authority: segment[2].clone(),
+        reserve_liquidity_supply: segment[3].clone(),
+        reserve_collateral_mint: segment[4].clone(),
+        ctoken_account: segment[5].clone(),
+        collateral_token_program: segment[6].clone(),
+        oracles: segment[7..].to_vec(),
+    })
+}
+
+
+/*
+   Whether the segment holds the config's klend reserve, owned by the klend program, and the
+   config PDA's cToken ATA of it. The collateral mint and its token program are checked too,
+   anyone can open a config ATA for a mint of their own. False while the config has no klend
+   reserve, anything else in its place is an error.
+*/
+pub fn check_klend_reserve_accounts(
+    config: &AggregatorConfig,
+    accounts: &KlendReserveAccounts,
+    klend_program: &AccountInfo,
+) -> Result<bool> {
+    require_keys_eq!(*accounts.reserve.key, config.klend_reserve, AggregatorError::InvalidKlendReserve);
+
+    if config.klend_reserve == Pubkey::default() {
+        return Ok(false);
+    }
+
+    require_keys_eq!(*accounts.reserve.owner, *klend_program.key, AggregatorError::InvalidKlendReserve);
+    require_keys_eq!(
+        *accounts.reserve_collateral_mint.key,
+        load_reserve(&accounts.reserve.try_borrow_data()?)?.collateral.mint_pubkey,
+        AggregatorError::InvalidKlendReserve
+    );
+    require_keys_eq!(
+        *accounts.reserve_collateral_mint.owner,
+        *accounts.collateral_token_program.key,
+        AggregatorError::InvalidKlendReserve
+    );
+
+    // Redemptions burn from this account, it has to be the config PDA's own cToken ATA
+    require_keys_eq!(
+        *accounts.ctoken_account.key,
+        get_associated_token_address_with_program_id(
+            &config.address()?,
+            accounts.reserve_collateral_mint.key,
+            accounts.collateral_token_program.key,
+        ),
+        AggregatorError::InvalidKlendReserve
+    );
+
+    Ok(true)
+}
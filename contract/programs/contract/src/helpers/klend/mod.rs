@@ -0,0 +1,9 @@
+pub mod klend_reserve_accounts;
+
+pub mod deposit_to_klend;
+
+pub mod redeem_from_klend;
+
+pub mod get_klend_balance;
+
+pub mod get_klend_liquidity;
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+
+use crate::{
+    errors::AggregatorError,
+    helpers::{
+        kamino::get_kamino_balance::calculate_collateral_exchange_rate,
+        klend::{
+            deposit_to_klend::KlendReserve,
+            get_klend_balance::{get_ctoken_amount, liquidity_to_collateral},
+        },
+    },
+};
+
+
+fn get_redeem_reserve_collateral_discriminator() -> Vec<u8> {
+    vec![234, 117, 181, 125, 185, 142, 220, 29]
+}
+
+
+
+impl<'info> KlendReserve<'info> {
+    /*
+       Redeems enough cTokens for `usdc_amount`, capped at the cTokens we hold.
+
+       The reserve is refreshed first, so the exchange rate read after it is the one klend
+       redeems at and ceil(usdc_amount * rate) cTokens pay out at least `usdc_amount`.
+    */
+    pub fn redeem_from_klend(&self, usdc_amount: u64, config_bump: u8) -> Result<()> {
+
+        self.refresh_reserve()?;
+
+        let exchange_rate = calculate_collateral_exchange_rate(&self.reserve, None)?;
+        let ctokens = liquidity_to_collateral(usdc_amount, exchange_rate)?
+            .min(get_ctoken_amount(&self.ctoken_account)?);
+
+        if ctokens == 0 {
+            return Ok(());
+        }
+
+        let mut instruction_data = get_redeem_reserve_collateral_discriminator();
+        instruction_data.extend_from_slice(&ctokens.to_le_bytes());
+
+        let account_metas = vec![
+            // owner (signer)
+            AccountMeta::new_readonly(*self.signer.key, true),
+            AccountMeta::new_readonly(*self.lending_market.key, false),
+            // reserve (mutable)
+            AccountMeta::new(*self.reserve.key, false),
+            AccountMeta::new_readonly(*self.lending_market_authority.key, false),
+            AccountMeta::new_readonly(*self.liquidity_mint.key, false),
+            // reserve_collateral_mint (mutable)
+            AccountMeta::new(*self.reserve_collateral_mint.key, false),
+            // reserve_liquidity_supply (mutable)
+            AccountMeta::new(*self.reserve_liquidity_supply.key, false),
+            // user_source_collateral (mutable)
+            AccountMeta::new(*self.ctoken_account.key, false),
+            // user_destination_liquidity (mutable)
+            AccountMeta::new(*self.asset_token_account.key, false),
+            AccountMeta::new_readonly(*self.collateral_token_program.key, false),
+            AccountMeta::new_readonly(*self.liquidity_token_program.key, false),
+            AccountMeta::new_readonly(*self.instruction_sysvar.key, false),
+        ];
+
+        let instruction = Instruction {
+            program_id: *self.klend_program.key,
+            accounts: account_metas,
+            data: instruction_data,
+        };
+
+        let seeds = &[b"config".as_ref(), &[config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+
+        invoke_signed(
+            &instruction,
+            &[
+                self.signer.clone(),
+                self.lending_market.clone(),
+                self.reserve.clone(),
+                self.lending_market_authority.clone(),
+                self.liquidity_mint.clone(),
+                self.reserve_collateral_mint.clone(),
+                self.reserve_liquidity_supply.clone(),
+                self.ctoken_account.clone(),
+                self.asset_token_account.clone(),
+                self.collateral_token_program.clone(),
+                self.liquidity_token_program.clone(),
+                self.instruction_sysvar.clone(),
+            ],
+            signer_seeds,
+        )
+        .map_err(|e| {
+            msg!("klend redeem_reserve_collateral CPI failed with error: {:?}", e);
+            AggregatorError::CpiToLendingProgramFailed
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+use anchor_spl::token_interface::{TokenAccount, TokenInterface};
+
+use crate::{
+    errors::AggregatorError,
+    helpers::{
+        marginfi::marginfi_accounts::{check_marginfi_accounts, parse_marginfi_accounts},
+        segment_offsets::SegmentOffsets,
+    },
+    states::AggregatorConfig,
+};
+
+
+
+fn get_deposit_discriminator() -> Vec<u8> {
+    vec![171, 94, 235, 103, 82, 64, 212, 140]
+}
+
+
+pub struct Marginfi<'info> {
+    pub signer: AccountInfo<'info>,
+    pub asset_token_account: AccountInfo<'info>,
+
+    // Protocol accounts
+    pub group: AccountInfo<'info>,
+    pub marginfi_account: AccountInfo<'info>,
+    pub bank: AccountInfo<'info>,
+    pub liquidity_vault: AccountInfo<'info>,
+    pub liquidity_vault_authority: AccountInfo<'info>,
+    pub bank_oracle: AccountInfo<'info>,
+
+    pub token_program: AccountInfo<'info>,
+
+    // Target lending program
+    pub marginfi_program: AccountInfo<'info>,
+}
+
+
+impl<'info> Marginfi<'info> {
+    pub fn new(
+        config: &Account<'info, AggregatorConfig>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        offsets: &SegmentOffsets,
+        vault_usdc: &InterfaceAccount<'info, TokenAccount>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<Box<Marginfi<'info>>> {
+
+        let accounts = parse_marginfi_accounts(remaining_accounts, offsets)?;
+
+        // The config PDA signs for this account, nothing else in the segment is ours
+        require_keys_eq!(
+            *accounts.marginfi_account.key,
+            config.marginfi_account,
+            AggregatorError::InvalidMarginfiAccount
+        );
+        check_marginfi_accounts(config, &accounts)?;
+
+        Ok(Box::new(Self {
+            signer: config.to_account_info(),
+            asset_token_account: vault_usdc.to_account_info(),
+            group: accounts.group,
+            marginfi_account: accounts.marginfi_account,
+            bank: accounts.bank,
+            liquidity_vault: accounts.liquidity_vault,
+            liquidity_vault_authority: accounts.liquidity_vault_authority,
+            bank_oracle: accounts.bank_oracle,
+            token_program: token_program.to_account_info(),
+            marginfi_program: accounts.marginfi_program,
+        }))
+    }
+
+    pub fn deposit_to_marginfi(&self, amount: u64, config_bump: u8) -> Result<()> {
+
+        let mut instruction_data = get_deposit_discriminator();
+        instruction_data.extend_from_slice(&amount.to_le_bytes());
+        // deposit_up_to_limit: None
+        instruction_data.push(0);
+
+        let account_metas = vec![
+            // group (readonly)
+            AccountMeta::new_readonly(*self.group.key, false),
+            // marginfi_account (mutable)
+            AccountMeta::new(*self.marginfi_account.key, false),
+            // authority (signer)
+            AccountMeta::new_readonly(*self.signer.key, true),
+            // bank (mutable)
+            AccountMeta::new(*self.bank.key, false),
+            // signer_token_account (mutable)
+            AccountMeta::new(*self.asset_token_account.key, false),
+            // liquidity_vault (mutable)
+            AccountMeta::new(*self.liquidity_vault.key, false),
+            // token_program
+            AccountMeta::new_readonly(*self.token_program.key, false),
+        ];
+
+        let instruction = Instruction {
+            program_id: *self.marginfi_program.key,
+            accounts: account_metas,
+            data: instruction_data,
+        };
+
+        let seeds = &[b"config".as_ref(), &[config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+
+        invoke_signed(
+            &instruction,
+            &[
+                self.group.clone(),
+                self.marginfi_account.clone(),
+                self.signer.clone(),
+                self.bank.clone(),
+                self.asset_token_account.clone(),
+                self.liquidity_vault.clone(),
+                self.token_program.clone(),
+            ],
+            signer_seeds,
+        )
+        .map_err(|e| {
+            msg!("MarginFi deposit CPI failed with error: {:?}", e);
+            AggregatorError::CpiToLendingProgramFailed
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use fixed::types::I80F48;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::marginfi::marginfi_account_reader::{load_bank, load_marginfi_account},
+    states::marginfi::MarginfiAccount,
+};
+
+
+/*
+   USDC our marginfi account holds in the bank
+
+   balance = asset_shares * asset_share_value, rounded down
+
+   asset_share_value only grows when the bank accrues interest, so unless the refresh block
+   ran `lending_pool_accrue_bank_interest` first the balance lags by the interest since the
+   bank's last_update. A marginfi account that doesn't exist yet holds nothing.
+*/
+pub fn get_marginfi_balance(marginfi_account: &AccountInfo, bank: &AccountInfo) -> Result<u64> {
+    if marginfi_account.data_is_empty() {
+        return Ok(0);
+    }
+
+    let account_data = marginfi_account.try_borrow_data()?;
+    let bank_data = bank.try_borrow_data()?;
+
+    calculate_marginfi_balance(
+        load_marginfi_account(&account_data)?,
+        bank.key,
+        load_bank(&bank_data)?.asset_share_value.get(),
+    )
+}
+
+
+pub fn calculate_marginfi_balance(account: &MarginfiAccount, bank: &Pubkey, asset_share_value: I80F48) -> Result<u64> {
+    let Some(balance) = account
+        .balances
+        .iter()
+        .find(|balance| balance.active != 0 && balance.bank_pk == *bank)
+    else {
+        return Ok(0);
+    };
+
+    balance
+        .asset_shares
+        .get()
+        .checked_mul(asset_share_value)
+        .and_then(|amount| amount.checked_to_num::<u64>())
+        .ok_or(AggregatorError::MathOverflow.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn test_balance_is_shares_times_share_value() {
+        let bank = Pubkey::new_unique();
+        let mut account = MarginfiAccount::zeroed();
+        account.balances[3].active = 1;
+        account.balances[3].bank_pk = bank;
+        account.balances[3].asset_shares = I80F48::from_num(1_000_000).into();
+
+        // 1.05 USDC per share, the fractional USDC is dropped
+        let share_value = I80F48::from_num(1_050_001) / I80F48::from_num(1_000_000);
+        assert_eq!(calculate_marginfi_balance(&account, &bank, share_value).unwrap(), 1_050_000);
+
+        // Closed balances and other banks hold nothing for us
+        assert_eq!(calculate_marginfi_balance(&account, &Pubkey::new_unique(), share_value).unwrap(), 0);
+        account.balances[3].active = 0;
+        assert_eq!(calculate_marginfi_balance(&account, &bank, share_value).unwrap(), 0);
+    }
+}
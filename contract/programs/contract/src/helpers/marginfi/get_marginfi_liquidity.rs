@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::marginfi::marginfi_account_reader::load_bank,
+    states::marginfi::Bank,
+};
+
+
+/// What the bank can pay out right now, everything in its liquidity vault that isn't borrowed
+pub fn get_marginfi_available_liquidity(liquidity_vault: &AccountInfo) -> Result<u64> {
+    let vault = TokenAccount::try_deserialize(&mut &liquidity_vault.try_borrow_data()?[..])?;
+    Ok(vault.amount)
+}
+
+
+pub fn get_marginfi_deposit_capacity(bank: &AccountInfo) -> Result<u64> {
+    let data = bank.try_borrow_data()?;
+    marginfi_deposit_room(load_bank(&data)?)
+}
+
+
+/*
+   Room left under the bank's deposit limit, u64::MAX when it has none.
+   marginfi keeps total deposits strictly below the limit, so one unit less than the gap.
+*/
+pub fn marginfi_deposit_room(bank: &Bank) -> Result<u64> {
+    let deposit_limit = bank.config.deposit_limit;
+    if deposit_limit == 0 {
+        return Ok(u64::MAX);
+    }
+
+    let total_deposits = bank
+        .total_asset_shares
+        .get()
+        .checked_mul(bank.asset_share_value.get())
+        .and_then(|amount| amount.checked_to_num::<u64>())
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    Ok(deposit_limit.saturating_sub(total_deposits).saturating_sub(1))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use fixed::types::I80F48;
+
+    #[test]
+    fn test_deposit_room_under_limit() {
+        let mut bank = Bank::zeroed();
+        bank.asset_share_value = I80F48::from_num(2).into();
+        bank.total_asset_shares = I80F48::from_num(400_000).into();
+        assert_eq!(marginfi_deposit_room(&bank).unwrap(), u64::MAX);
+
+        bank.config.deposit_limit = 1_000_000;
+        assert_eq!(marginfi_deposit_room(&bank).unwrap(), 199_999);
+
+        bank.config.deposit_limit = 800_000;
+        assert_eq!(marginfi_deposit_room(&bank).unwrap(), 0);
+    }
+}
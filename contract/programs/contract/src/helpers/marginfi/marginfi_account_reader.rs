@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{MARGINFI_ACCOUNT_DISCRIMINATOR, MARGINFI_BANK_DISCRIMINATOR},
+    helpers::zero_copy_view::zero_copy_view,
+    states::marginfi::{Bank, MarginfiAccount},
+};
+
+
+/// Zero-copy view over the leading fields of a marginfi Bank
+pub fn load_bank(data: &[u8]) -> Result<&Bank> {
+    zero_copy_view(data, &MARGINFI_BANK_DISCRIMINATOR)
+}
+
+/// Zero-copy view over a MarginfiAccount through its lending balances
+pub fn load_marginfi_account(data: &[u8]) -> Result<&MarginfiAccount> {
+    zero_copy_view(data, &MARGINFI_ACCOUNT_DISCRIMINATOR)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::marginfi::{Balance, BankConfig};
+    use std::mem::{offset_of, size_of};
+
+    #[test]
+    fn test_bank_layout() {
+        // Offsets after the discriminator, from the marginfi IDL
+        assert_eq!(offset_of!(Bank, group), 33);
+        assert_eq!(offset_of!(Bank, asset_share_value), 72);
+        assert_eq!(offset_of!(Bank, liquidity_vault), 104);
+        assert_eq!(offset_of!(Bank, insurance_vault), 138);
+        assert_eq!(offset_of!(Bank, fee_vault), 192);
+        assert_eq!(offset_of!(Bank, total_asset_shares), 264);
+        assert_eq!(offset_of!(Bank, last_update), 280);
+        assert_eq!(offset_of!(Bank, config), 288);
+        assert_eq!(offset_of!(BankConfig, deposit_limit), 64);
+        assert_eq!(size_of::<Bank>(), 360);
+    }
+
+    #[test]
+    fn test_marginfi_account_layout() {
+        assert_eq!(offset_of!(Balance, bank_pk), 1);
+        assert_eq!(offset_of!(Balance, asset_shares), 40);
+        assert_eq!(offset_of!(Balance, last_update), 88);
+        assert_eq!(size_of::<Balance>(), 104);
+        assert_eq!(offset_of!(MarginfiAccount, balances), 64);
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::segment_offsets::SegmentOffsets,
+    states::AggregatorConfig,
+};
+
+
+/// Accounts in the MarginFi segment, in `MarginfiAccounts` order
+pub const MARGINFI_ACCOUNTS_LEN: usize = 7;
+
+
+#[derive(Clone)]
+pub struct MarginfiAccounts<'info> {
+    pub group: AccountInfo<'info>,
+    /// Owned by the config PDA, the system program id until `init_marginfi_account` ran
+    pub marginfi_account: AccountInfo<'info>,
+    pub bank: AccountInfo<'info>,
+    pub liquidity_vault: AccountInfo<'info>,
+    pub liquidity_vault_authority: AccountInfo<'info>,
+    /// The bank's price feed, for the health check on partial withdrawals
+    pub bank_oracle: AccountInfo<'info>,
+    pub marginfi_program: AccountInfo<'info>,
+}
+
+
+/// The MarginFi segment sits right after the Kamino reserve groups
+pub fn parse_marginfi_accounts<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    offsets: &SegmentOffsets,
+) -> Result<MarginfiAccounts<'info>> {
+    let start = offsets.marginfi;
+    let segment = remaining_accounts
+        .get(start..start + MARGINFI_ACCOUNTS_LEN)
+        .ok_or(AggregatorError::MissingAccount)?;
+
+    Ok(MarginfiAccounts {
+        group: segment[0].clone(),
+        marginfi_account: segment[1].clone(),
+        bank: segment[2].clone(),
+        liquidity_vault: segment[3].clone(),
+        liquidity_vault_authority: segment[4].clone(),
+        bank_oracle: segment[5].clone(),
+        marginfi_program: segment[6].clone(),
+    })
+}
+
+
+/*
+   Whether the segment holds the config's marginfi account, owned by the marginfi program
+   passed alongside it, and the bank the config lends to. False while the config has no
+   marginfi account, anything else in its place is an error.
+*/
+pub fn check_marginfi_accounts(config: &AggregatorConfig, accounts: &MarginfiAccounts) -> Result<bool> {
+    if config.marginfi_account == Pubkey::default() {
+        return Ok(false);
+    }
+
+    require_keys_eq!(*accounts.marginfi_account.key, config.marginfi_account, AggregatorError::InvalidMarginfiAccount);
+    require_keys_eq!(
+        *accounts.marginfi_account.owner,
+        *accounts.marginfi_program.key,
+        AggregatorError::InvalidMarginfiAccount
+    );
+    require_keys_eq!(*accounts.bank.key, config.marginfi_bank, AggregatorError::InvalidMarginfiBank);
+
+    Ok(true)
+}
@@ -0,0 +1,11 @@
+pub mod marginfi_accounts;
+
+pub mod marginfi_account_reader;
+
+pub mod deposit_to_marginfi;
+
+pub mod withdraw_from_marginfi;
+
+pub mod get_marginfi_balance;
+
+pub mod get_marginfi_liquidity;
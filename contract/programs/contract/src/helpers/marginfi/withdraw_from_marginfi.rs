@@ -0,0 +1,92 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+
+use crate::{
+    errors::AggregatorError,
+    helpers::marginfi::{deposit_to_marginfi::Marginfi, get_marginfi_balance::get_marginfi_balance},
+};
+
+
+fn get_withdraw_discriminator() -> Vec<u8> {
+    vec![36, 72, 74, 19, 210, 210, 192, 192]
+}
+
+
+
+impl<'info> Marginfi<'info> {
+    /*
+       Withdraws `usdc_amount`, or closes the bank balance when that is all we hold.
+
+       A partial withdrawal leaves the balance open, so marginfi runs its health check over
+       the account's active banks and needs the bank and its oracle as remaining accounts.
+       Closing the balance leaves nothing to check.
+    */
+    pub fn withdraw_from_marginfi(&self, usdc_amount: u64, config_bump: u8) -> Result<()> {
+
+        let withdraw_all = usdc_amount >= get_marginfi_balance(&self.marginfi_account, &self.bank)?;
+
+        let mut instruction_data = get_withdraw_discriminator();
+        instruction_data.extend_from_slice(&usdc_amount.to_le_bytes());
+        // withdraw_all: Option<bool>
+        if withdraw_all {
+            instruction_data.extend_from_slice(&[1, 1]);
+        } else {
+            instruction_data.push(0);
+        }
+
+        let mut account_metas = vec![
+            // group (readonly)
+            AccountMeta::new_readonly(*self.group.key, false),
+            // marginfi_account (mutable)
+            AccountMeta::new(*self.marginfi_account.key, false),
+            // authority (signer)
+            AccountMeta::new_readonly(*self.signer.key, true),
+            // bank (mutable)
+            AccountMeta::new(*self.bank.key, false),
+            // destination_token_account (mutable)
+            AccountMeta::new(*self.asset_token_account.key, false),
+            // bank_liquidity_vault_authority (readonly)
+            AccountMeta::new_readonly(*self.liquidity_vault_authority.key, false),
+            // liquidity_vault (mutable)
+            AccountMeta::new(*self.liquidity_vault.key, false),
+            // token_program
+            AccountMeta::new_readonly(*self.token_program.key, false),
+        ];
+
+        let mut accounts_for_cpi = vec![
+            self.group.clone(),
+            self.marginfi_account.clone(),
+            self.signer.clone(),
+            self.bank.clone(),
+            self.asset_token_account.clone(),
+            self.liquidity_vault_authority.clone(),
+            self.liquidity_vault.clone(),
+            self.token_program.clone(),
+        ];
+
+        if !withdraw_all {
+            // Health check observation accounts: the bank and its price feed
+            account_metas.push(AccountMeta::new_readonly(*self.bank.key, false));
+            account_metas.push(AccountMeta::new_readonly(*self.bank_oracle.key, false));
+            accounts_for_cpi.push(self.bank_oracle.clone());
+        }
+
+        let instruction = Instruction {
+            program_id: *self.marginfi_program.key,
+            accounts: account_metas,
+            data: instruction_data,
+        };
+
+        let seeds = &[b"config".as_ref(), &[config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+
+
+        invoke_signed(&instruction, &accounts_for_cpi, signer_seeds)
+        .map_err(|e| {
+            msg!("MarginFi withdraw CPI failed with error: {:?}", e);
+            AggregatorError::CpiToLendingProgramFailed
+        })?;
+
+        Ok(())
+    }
+}
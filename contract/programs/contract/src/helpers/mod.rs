@@ -2,12 +2,35 @@ pub mod calculate_shares_to_mint;
 
 pub mod calculate_usdc_for_shares;
 
+pub mod zero_copy_view;
+
 pub mod juplend;
 use juplend::*;
 
 pub mod kamino;
 use kamino::*;
 
+pub mod marginfi;
+
+pub mod klend;
+
+pub mod save;
+
 pub mod withdraw_from_protocols;
 pub mod rebalance_allocation;
-pub mod calculate_total_asset_balance;
\ No newline at end of file
+pub mod protocol_apy;
+pub mod allocation_optimizer;
+pub mod swap_rewards;
+pub mod calculate_total_asset_balance;
+pub mod refresh_protocol_state;
+pub mod segment_offsets;
+pub mod vault_view;
+pub mod share_conversion;
+pub mod access_control;
+pub mod holding_period;
+pub mod share_metadata;
+pub mod top_up_rent;
+pub mod config_migration;
+
+#[cfg(test)]
+mod golden_vectors;
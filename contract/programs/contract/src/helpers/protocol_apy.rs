@@ -0,0 +1,351 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    constants::{BPS_BASE, EXCHANGE_PRICES_PRECISION, REWARDS_RATE_PRECISION},
+    errors::AggregatorError,
+    events::AllocationUpdateEvent,
+    helpers::{
+        allocation_optimizer::{optimize_allocation, JupLendRateModel, KaminoReserveModel, KaminoVaultRateModel, RATE_PRECISION},
+        juplend::{
+            get_juplend_apy::get_juplend_supply_apy_bps,
+            get_juplend_balance::get_rewards_rate,
+            get_juplend_liquidity::get_juplend_totals,
+        },
+        kamino::{
+            get_kamino_liquidity::get_invested_in_reserve,
+            get_kamino_net_apy::{get_kamino_net_apy_bps, read_kamino_vault_fees},
+            kamino_account_reader::{read_reserve_borrow_rate_curve, read_reserve_fields, read_vault_state_fields},
+            kamino_reserve_accounts::get_reserve_account_infos,
+        },
+        token_reserve_helper::load_token_reserve,
+    },
+    states::{
+        AggregatorConfig, AllocationBounds, AllocationMode, KaminoInterestSnapshot,
+        lending::Lending,
+        lending_rewards_rate_model::LendingRewardsRateModel,
+    },
+};
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolApys {
+    pub juplend_apy_bps: u64,
+    pub kamino_apy_bps: u64,
+}
+
+
+/// Kamino is net of the vault's fees, realized over `kamino_snapshot` when it is old enough
+pub fn get_protocol_apys<'info>(
+    remaining_accounts: &'info [AccountInfo<'info>],
+    kamino_snapshot: &KaminoInterestSnapshot,
+) -> Result<ProtocolApys> {
+    let account = |index: usize| remaining_accounts.get(index).ok_or(AggregatorError::MissingAccount);
+
+    let jup_lending = Lending::try_deserialize(&mut &account(0)?.try_borrow_data()?[..])?;
+    let jup_rewards_rate_model = LendingRewardsRateModel::try_deserialize(&mut &account(1)?.try_borrow_data()?[..])?;
+    let f_token_mint = InterfaceAccount::<'info, Mint>::try_from(account(2)?)?;
+
+    let juplend_apy_bps = get_juplend_supply_apy_bps(
+        account(5)?,
+        &jup_lending,
+        &jup_rewards_rate_model,
+        f_token_mint.supply,
+    )?;
+
+    let kamino_apy_bps = get_kamino_net_apy_bps(
+        account(13)?,
+        &get_reserve_account_infos(remaining_accounts)?,
+        Some(Clock::get()?.slot),
+        kamino_snapshot,
+    )?;
+
+    msg!("Supply APY - Juplend: {} bps, Kamino (net of fees): {} bps", juplend_apy_bps, kamino_apy_bps);
+
+    Ok(ProtocolApys { juplend_apy_bps, kamino_apy_bps })
+}
+
+
+/*
+   Supply rate models for the allocation optimizer, built from the same remaining accounts.
+   `balances` is what we currently hold in [JupLend, Kamino].
+*/
+pub fn get_rate_models<'info>(
+    remaining_accounts: &'info [AccountInfo<'info>],
+    balances: &[u64],
+) -> Result<(JupLendRateModel, KaminoVaultRateModel)> {
+    let account = |index: usize| remaining_accounts.get(index).ok_or(AggregatorError::MissingAccount);
+
+    require!(balances.len() == 2, AggregatorError::InvalidProtocolIndex);
+
+    let jup_lending = Lending::try_deserialize(&mut &account(0)?.try_borrow_data()?[..])?;
+    let jup_rewards_rate_model = LendingRewardsRateModel::try_deserialize(&mut &account(1)?.try_borrow_data()?[..])?;
+    let f_token_mint = InterfaceAccount::<'info, Mint>::try_from(account(2)?)?;
+
+    let f_token_assets = (jup_lending.token_exchange_price as u128)
+        .checked_mul(f_token_mint.supply as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(EXCHANGE_PRICES_PRECISION)
+        .ok_or(AggregatorError::MathOverflow)?;
+    let rewards_rate = get_rewards_rate(
+        u64::try_from(f_token_assets).unwrap_or(u64::MAX),
+        &jup_rewards_rate_model,
+        Clock::get()?.unix_timestamp as u64,
+    )?;
+
+    let juplend = {
+        let data = account(5)?.try_borrow_data()?;
+        let (total_supply, total_borrow) = get_juplend_totals(&data)?;
+        let token_reserve = load_token_reserve(&data)?;
+        JupLendRateModel {
+            total_supply,
+            total_borrow,
+            borrow_rate: token_reserve.borrow_rate,
+            utilization: token_reserve.last_utilization,
+            fee_on_interest: token_reserve.fee_on_interest,
+            // The rate model is 1e14 = 100%, the optimizer works in RATE_PRECISION
+            rewards_rate: rewards_rate.rate * RATE_PRECISION / REWARDS_RATE_PRECISION,
+            current_balance: balances[0],
+        }
+    };
+
+    let current_slot = Clock::get()?.slot;
+    let reserve_accounts = get_reserve_account_infos(remaining_accounts)?;
+    let vault_fees = read_kamino_vault_fees(account(13)?)?;
+    let vault_data = account(13)?.try_borrow_data()?;
+
+    let mut reserves = Vec::with_capacity(reserve_accounts.len());
+    for reserve_account in &reserve_accounts {
+        let invested = get_invested_in_reserve(&vault_data, reserve_account, Some(current_slot))?;
+        let reserve_data = reserve_account.try_borrow_data()?;
+        let reserve = read_reserve_fields(&reserve_data)?;
+        reserves.push(KaminoReserveModel {
+            available_amount: reserve.available_amount,
+            // borrowed_amount_sf is a Fraction with 60 fractional bits
+            borrowed_amount: reserve.borrowed_amount_sf >> 60,
+            protocol_take_rate_pct: reserve.protocol_take_rate_pct,
+            borrow_rate_curve: read_reserve_borrow_rate_curve(&reserve_data)?,
+            invested,
+        });
+    }
+
+    let kamino = KaminoVaultRateModel {
+        token_available: read_vault_state_fields(&vault_data)?.token_available,
+        reserves,
+        current_balance: balances[1],
+        performance_fee_bps: vault_fees.performance_fee_bps,
+        management_fee_bps: vault_fees.management_fee_bps,
+    };
+
+    Ok((juplend, kamino))
+}
+
+
+/*
+   Range of JupLend bps that keeps both protocols inside their bounds.
+   Kamino takes whatever JupLend doesn't, so its bounds mirror onto JupLend's.
+
+   Bounds and the range are shares of the floating bps, what's left once MarginFi's fixed
+   target is taken out (see `split_floating_bps`).
+*/
+pub fn juplend_bps_range(juplend_bounds: &AllocationBounds, kamino_bounds: &AllocationBounds) -> Result<(u16, u16)> {
+    require!(
+        juplend_bounds.min_bps <= juplend_bounds.max_bps
+            && kamino_bounds.min_bps <= kamino_bounds.max_bps
+            && juplend_bounds.max_bps <= BPS_BASE
+            && kamino_bounds.max_bps <= BPS_BASE,
+        AggregatorError::InvalidAllocationBounds
+    );
+
+    let lower = juplend_bounds.min_bps.max(BPS_BASE - kamino_bounds.max_bps);
+    let upper = juplend_bounds.max_bps.min(BPS_BASE - kamino_bounds.min_bps);
+
+    require!(lower <= upper, AggregatorError::InvalidAllocationBounds);
+
+    Ok((lower, upper))
+}
+
+
+pub fn calculate_apy_driven_juplend_bps(
+    apys: &ProtocolApys,
+    current_juplend_bps: u16,
+    juplend_bounds: &AllocationBounds,
+    kamino_bounds: &AllocationBounds,
+) -> Result<u16> {
+    let (lower, upper) = juplend_bps_range(juplend_bounds, kamino_bounds)?;
+
+    let juplend_bps = match apys.juplend_apy_bps.cmp(&apys.kamino_apy_bps) {
+        std::cmp::Ordering::Greater => upper,
+        std::cmp::Ordering::Less => lower,
+        std::cmp::Ordering::Equal => current_juplend_bps.clamp(lower, upper),
+    };
+
+    Ok(juplend_bps)
+}
+
+
+/// JupLend's share of the floating bps, the inverse of `split_floating_bps`
+pub fn floating_share_bps(juplend_bps: u16, floating_bps: u16) -> u16 {
+    if floating_bps == 0 {
+        return 0;
+    }
+
+    ((juplend_bps as u32 * BPS_BASE as u32 / floating_bps as u32) as u16).min(BPS_BASE)
+}
+
+
+/// Turns JupLend's share of the floating bps into absolute [JupLend, Kamino] targets
+pub fn split_floating_bps(juplend_share_bps: u16, floating_bps: u16) -> (u16, u16) {
+    let juplend_bps = (juplend_share_bps.min(BPS_BASE) as u32 * floating_bps as u32 / BPS_BASE as u32) as u16;
+    (juplend_bps, floating_bps - juplend_bps)
+}
+
+
+/*
+   [JupLend, Kamino] targets for the on-chain allocation modes, without touching the config:
+   - ApyDriven: points the targets at the protocol paying more right now
+   - RateOptimized: splits total assets so the rates after our own move are equal
+
+   Only JupLend and Kamino move, the fixed targets stay and the two split the rest.
+
+   Returns the targets with the APYs behind them, None in Fixed mode.
+*/
+pub fn compute_allocation_targets<'info>(
+    config: &AggregatorConfig,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    usdc_in_all_protocol: &[u64],
+    idle_usdc: u64,
+) -> Result<Option<(u16, u16, ProtocolApys)>> {
+    let floating_bps = config.floating_allocation_bps();
+    let current_share_bps = floating_share_bps(config.juplend_allocation_bps, floating_bps);
+
+    let (juplend_share_bps, apys) = match config.allocation_mode {
+        AllocationMode::Fixed => return Ok(None),
+        AllocationMode::ApyDriven => {
+            let apys = get_protocol_apys(remaining_accounts, &config.kamino_interest_snapshot)?;
+
+            let juplend_share_bps = calculate_apy_driven_juplend_bps(
+                &apys,
+                current_share_bps,
+                &config.juplend_bounds,
+                &config.kamino_bounds,
+            )?;
+            (juplend_share_bps, apys)
+        }
+        AllocationMode::RateOptimized => {
+            let total_usdc = usdc_in_all_protocol
+                .iter()
+                .try_fold(idle_usdc, |acc, x| acc.checked_add(*x))
+                .ok_or(AggregatorError::MathOverflow)?;
+
+            // What JupLend and Kamino should hold between them
+            let floating_usdc = (total_usdc as u128 * floating_bps as u128 / BPS_BASE as u128) as u64;
+
+            let (juplend, kamino) = get_rate_models(
+                remaining_accounts,
+                usdc_in_all_protocol.get(..2).ok_or(AggregatorError::InvalidProtocolIndex)?,
+            )?;
+            let allocation = optimize_allocation(
+                floating_usdc,
+                &juplend,
+                &kamino,
+                juplend_bps_range(&config.juplend_bounds, &config.kamino_bounds)?,
+            )?;
+
+            msg!(
+                "Optimal split - Juplend: {} ({} bps), Kamino: {} ({} bps)",
+                allocation.juplend_amount,
+                allocation.juplend_apy_bps,
+                allocation.kamino_amount,
+                allocation.kamino_apy_bps
+            );
+
+            // Nothing to split yet, keep the current targets
+            if floating_usdc == 0 {
+                (current_share_bps, ProtocolApys::default())
+            } else {
+                (allocation.juplend_bps, ProtocolApys {
+                    juplend_apy_bps: allocation.juplend_apy_bps,
+                    kamino_apy_bps: allocation.kamino_apy_bps,
+                })
+            }
+        }
+    };
+
+    let (juplend_bps, kamino_bps) = split_floating_bps(juplend_share_bps, floating_bps);
+
+    Ok(Some((juplend_bps, kamino_bps, apys)))
+}
+
+
+/// Writes `compute_allocation_targets` into the config, returns the APYs it read so the
+/// rebalance can reuse them, None in Fixed mode
+pub fn refresh_allocation_targets<'info>(
+    config: &mut AggregatorConfig,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    usdc_in_all_protocol: &[u64],
+    idle_usdc: u64,
+) -> Result<Option<ProtocolApys>> {
+    let Some((juplend_bps, kamino_bps, apys)) =
+        compute_allocation_targets(config, remaining_accounts, usdc_in_all_protocol, idle_usdc)?
+    else {
+        return Ok(None);
+    };
+
+    if juplend_bps != config.juplend_allocation_bps || kamino_bps != config.kamino_allocation_bps {
+        config.juplend_allocation_bps = juplend_bps;
+        config.kamino_allocation_bps = kamino_bps;
+
+        emit!(AllocationUpdateEvent {
+            juplend_bps,
+            kamino_bps,
+            marginfi_bps: config.marginfi_allocation_bps,
+            klend_bps: config.klend_allocation_bps,
+            save_bps: config.save_allocation_bps,
+        });
+    }
+
+    Ok(Some(apys))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(min_bps: u16, max_bps: u16) -> AllocationBounds {
+        AllocationBounds { min_bps, max_bps }
+    }
+
+    #[test]
+    fn test_bounds_mirror_between_protocols() {
+        assert_eq!(juplend_bps_range(&bounds(2000, 9000), &bounds(3000, 10000)).unwrap(), (2000, 7000));
+        assert!(juplend_bps_range(&bounds(8000, 9000), &bounds(3000, 10000)).is_err());
+    }
+
+    #[test]
+    fn test_targets_follow_higher_apy() {
+        let juplend_bounds = bounds(2000, 8000);
+        let kamino_bounds = bounds(2000, 8000);
+
+        let juplend_higher = ProtocolApys { juplend_apy_bps: 600, kamino_apy_bps: 450 };
+        assert_eq!(calculate_apy_driven_juplend_bps(&juplend_higher, 5000, &juplend_bounds, &kamino_bounds).unwrap(), 8000);
+
+        let kamino_higher = ProtocolApys { juplend_apy_bps: 450, kamino_apy_bps: 600 };
+        assert_eq!(calculate_apy_driven_juplend_bps(&kamino_higher, 5000, &juplend_bounds, &kamino_bounds).unwrap(), 2000);
+
+        let equal = ProtocolApys { juplend_apy_bps: 500, kamino_apy_bps: 500 };
+        assert_eq!(calculate_apy_driven_juplend_bps(&equal, 9000, &juplend_bounds, &kamino_bounds).unwrap(), 8000);
+    }
+
+    #[test]
+    fn test_floating_split_leaves_marginfi_target() {
+        // MarginFi holds 2000 bps, JupLend gets 75% of the other 8000
+        assert_eq!(split_floating_bps(7500, 8000), (6000, 2000));
+        assert_eq!(floating_share_bps(6000, 8000), 7500);
+
+        assert_eq!(split_floating_bps(7500, BPS_BASE), (7500, 2500));
+        assert_eq!(split_floating_bps(7500, 0), (0, 0));
+        assert_eq!(floating_share_bps(0, 0), 0);
+    }
+}
@@ -5,7 +5,30 @@ use crate::{
     constants::{BPS_BASE, MIN_OPERATE_AMOUNT}, 
     errors::AggregatorError, events::RebalanceEvent, 
     helpers::{
-        deposit_to_juplend::Juplend, deposit_to_kamino::KaminoVault
+        deposit_to_juplend::Juplend, deposit_to_kamino::KaminoVault,
+        juplend::get_juplend_liquidity::get_juplend_available_liquidity,
+        kamino::{
+            farm_position::get_kamino_withdrawable_usdc,
+            get_kamino_liquidity::get_kamino_available_liquidity,
+            kamino_reserve_accounts::{get_reserve_account_infos, KLEND_PROGRAM_INDEX},
+        },
+        marginfi::{
+            deposit_to_marginfi::Marginfi,
+            get_marginfi_liquidity::{get_marginfi_available_liquidity, get_marginfi_deposit_capacity},
+            marginfi_accounts::{check_marginfi_accounts, parse_marginfi_accounts},
+        },
+        klend::{
+            deposit_to_klend::KlendReserve,
+            get_klend_liquidity::{get_klend_available_liquidity, get_klend_deposit_capacity},
+            klend_reserve_accounts::{check_klend_reserve_accounts, parse_klend_reserve_accounts},
+        },
+        save::{
+            deposit_to_save::SaveReserve,
+            get_save_liquidity::{get_save_available_liquidity, get_save_deposit_capacity},
+            save_reserve_accounts::{check_save_reserve_accounts, parse_save_reserve_accounts},
+        },
+        protocol_apy::ProtocolApys,
+        segment_offsets::SegmentOffsets,
     }, 
     states::{
         ReserveWithdrawAccounts, 
@@ -16,6 +39,7 @@ use crate::{
 
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn rebalance_allocation<'info>(
     signer: &Signer<'info>,
     remaining_accounts: &'info [AccountInfo<'info>],
@@ -27,6 +51,7 @@ pub fn rebalance_allocation<'info>(
     associated_token_program: &AccountInfo<'info>,
     system_program: &AccountInfo<'info>,
     rent: &AccountInfo<'info>,
+    protocol_apys: Option<ProtocolApys>,
 ) -> Result<()> {
         
     
@@ -39,19 +64,16 @@ pub fn rebalance_allocation<'info>(
             .checked_add(vault_usdc.amount)
             .ok_or(AggregatorError::MathOverflow)?;
 
-    let target_juplend_balance = total_usdc_in_all_protocols_combined
-            .checked_mul(config.juplend_allocation_bps as u64)
-            .ok_or(AggregatorError::MathOverflow)?
-            .checked_div(BPS_BASE as u64)
-            .ok_or(AggregatorError::MathOverflow)?;
-
-    let target_kamino_balance = total_usdc_in_all_protocols_combined
-            .checked_mul(config.kamino_allocation_bps as u64)
-            .ok_or(AggregatorError::MathOverflow)?
-            .checked_div(BPS_BASE as u64)
-            .ok_or(AggregatorError::MathOverflow)?;
+    let target_balances = calculate_target_balances(
+            total_usdc_in_all_protocols_combined,
+            &config.allocation_bps(),
+    )?;
 
-    
+    let target_juplend_balance = target_balances[0];
+    let target_kamino_balance = target_balances[1];
+    let target_marginfi_balance = target_balances[2];
+    let target_klend_balance = target_balances[3];
+    let target_save_balance = target_balances[4];
 
     let juplend_accounts = Juplend::new(
         config,
@@ -73,126 +95,466 @@ pub fn rebalance_allocation<'info>(
         system_program,
         rent,
     )?;
+    let offsets = SegmentOffsets::new(kamino_accounts.reserve_accounts.len());
+    let marginfi_accounts = Marginfi::new(config, remaining_accounts, &offsets, vault_usdc, token_program)?;
+    let klend_accounts = KlendReserve::new(config, remaining_accounts, &offsets, vault_usdc, usdc_mint, token_program)?;
+    let save_accounts = SaveReserve::new(config, remaining_accounts, &offsets, vault_usdc, token_program)?;
 
     msg!("Juplend balance: {}", target_juplend_balance);
     msg!("Kamino balance: {}", target_kamino_balance);
+    msg!("MarginFi balance: {}", target_marginfi_balance);
+    msg!("klend reserve balance: {}", target_klend_balance);
+    msg!("Save reserve balance: {}", target_save_balance);
+
+    let (max_withdrawals, max_deposits) = get_protocol_capacities(config, remaining_accounts)?;
+    msg!("Protocol capacities - withdrawals: {:?}, deposits: {:?}", max_withdrawals, max_deposits);
+
+    let rebalance_plan = plan_rebalance(
+        vault_usdc.amount,
+        &usdc_in_all_protocol,
+        &target_balances,
+        &max_withdrawals,
+        &max_deposits,
+    )?;
 
     execute_rebalance(
         &juplend_accounts,
         &kamino_accounts,
+        &marginfi_accounts,
+        &klend_accounts,
+        &save_accounts,
         vault_usdc,
         remaining_accounts,
-        usdc_in_all_protocol[0],
-        usdc_in_all_protocol[1],
-        target_juplend_balance,
-        target_kamino_balance,
+        &rebalance_plan,
         config.bump,
     )?;
 
     
 
+    // Fixed mode doesn't read APYs to pick targets, so the event reports zero instead of
+    // paying for the reads on every deposit and withdraw
+    let protocol_apys = protocol_apys.unwrap_or_default();
+
     emit!(RebalanceEvent {
         juplend_balance: target_juplend_balance,
         kamino_balance: target_kamino_balance,
+        marginfi_balance: target_marginfi_balance,
+        klend_balance: target_klend_balance,
+        save_balance: target_save_balance,
+        juplend_apy_bps: protocol_apys.juplend_apy_bps,
+        kamino_apy_bps: protocol_apys.kamino_apy_bps,
     });
 
     Ok(())
 }
 
 
-fn execute_rebalance<'info>(
-    juplend_accounts: &Juplend<'info>,
-    kamino_accounts: &KaminoVault<'info>,
-    vault_usdc: &mut InterfaceAccount<'info, TokenAccount>,
-    remaining_accounts: &'info [AccountInfo<'info>],
-    current_juplend: u64,
-    current_kamino: u64,
-    target_juplend: u64,
-    target_kamino: u64,
-    config_bump: u8,
-) -> Result<()> {
+pub fn calculate_target_balances(total_usdc: u64, allocation_bps: &[u16]) -> Result<Vec<u64>> {
+    allocation_bps
+        .iter()
+        .map(|bps| {
+            total_usdc
+                .checked_mul(*bps as u64)
+                .ok_or(AggregatorError::MathOverflow)?
+                .checked_div(BPS_BASE as u64)
+                .ok_or(AggregatorError::MathOverflow.into())
+        })
+        .collect()
+}
 
-    // Step 1: If there's USDC in vault, deposit it to Juplend first
-    // Only deposit if amount is meaningful (> 1000 lamports to avoid dust)
-    let vault_balance = vault_usdc.amount;
-    
-    if vault_balance >= MIN_OPERATE_AMOUNT {
-        msg!("Depositing to Juplend: {}", vault_balance);
-        juplend_accounts.deposit_to_juplend(vault_balance, config_bump)?;
-    } else if vault_balance > 0 {
-        msg!("Vault balance {} is too small to deposit, skipping", vault_balance);
+
+/*
+   Largest distance between a protocol's balance and its target, in bps of total assets.
+   Idle USDC in the vault counts towards total assets, so undeployed funds show up as drift.
+*/
+pub fn calculate_allocation_drift_bps(balances: &[u64], idle_usdc: u64, allocation_bps: &[u16]) -> Result<u16> {
+    require!(
+        balances.len() == allocation_bps.len(),
+        AggregatorError::InvalidProtocolIndex
+    );
+
+    let total_usdc = balances
+        .iter()
+        .try_fold(idle_usdc, |acc, x| acc.checked_add(*x))
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    if total_usdc == 0 {
+        return Ok(0);
     }
-    
-    msg!("Vault balance: {}", vault_balance);
-    // Step 2: Calculate new balances after depositing vault USDC to Juplend
-    let new_current_juplend_balance = current_juplend
-        .checked_add(vault_balance)
+
+    let target_balances = calculate_target_balances(total_usdc, allocation_bps)?;
+
+    let max_distance = balances
+        .iter()
+        .zip(target_balances.iter())
+        .map(|(balance, target)| balance.abs_diff(*target))
+        .max()
+        .unwrap_or(0);
+
+    let drift_bps = (max_distance as u128)
+        .checked_mul(BPS_BASE as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(total_usdc as u128)
         .ok_or(AggregatorError::MathOverflow)?;
-    let new_current_kamino_balance = current_kamino;
-    msg!("New CurrentJuplend balance: {}", new_current_juplend_balance);
-    msg!("New Current Kamino balance: {}", new_current_kamino_balance);
-    
 
-    // Step 4: Determine which protocol has excess and rebalance
-    if new_current_juplend_balance > target_juplend {
-        // Juplend has excess, move to Kamino
-        let amount_to_move = new_current_juplend_balance
-            .checked_sub(target_juplend)
-            .ok_or(AggregatorError::MathOverflow)?;
+    Ok(drift_bps.min(BPS_BASE as u128) as u16)
+}
+
 
-        if amount_to_move < MIN_OPERATE_AMOUNT {
-            msg!("Amount to move from Juplend ({}) is too small, skipping rebalance", amount_to_move);
-            return Ok(());
+pub fn is_drift_above_threshold(config: &AggregatorConfig, drift_bps: u16) -> bool {
+    drift_bps > config.drift_threshold_bps
+}
+
+
+/*
+   What [JupLend, Kamino, MarginFi, klend reserve, Save reserve] can pay out and take in this
+   transaction, as (max_withdrawals, max_deposits).
+
+   Kamino withdrawals are limited by reserve liquidity and withdrawal caps and by the farm
+   shares that aren't behind a cooldown. Deposits into the Kamino vault aren't limited, what
+   its reserves can't take stays in the vault's `token_available`.
+   MarginFi pays out what sits in the bank's liquidity vault and takes deposits up to its
+   deposit limit, nothing either way until the config has a marginfi account. The klend
+   reserve pays out within its withdrawal cap and takes deposits up to its deposit limit
+   while Active, nothing either way until the config has a klend reserve. The Save reserve
+   pays out its available liquidity and takes deposits up to its deposit limit, nothing
+   either way until the config has a Save reserve.
+*/
+pub fn get_protocol_capacities<'info>(
+    config: &AggregatorConfig,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<(Vec<u64>, Vec<u64>)> {
+    let account = |index: usize| remaining_accounts.get(index).ok_or(AggregatorError::MissingAccount);
+
+    let current_slot = Clock::get()?.slot;
+    let reserve_accounts = get_reserve_account_infos(remaining_accounts)?;
+
+    let juplend_liquidity = get_juplend_available_liquidity(account(5)?)?;
+    let kamino_liquidity = get_kamino_available_liquidity(account(13)?, &reserve_accounts, Some(current_slot))?;
+    let kamino_withdrawable = get_kamino_withdrawable_usdc(remaining_accounts, &reserve_accounts, current_slot)?;
+    let offsets = SegmentOffsets::new(reserve_accounts.len());
+    let marginfi_accounts = parse_marginfi_accounts(remaining_accounts, &offsets)?;
+    let klend_accounts = parse_klend_reserve_accounts(remaining_accounts, &offsets)?;
+    let save_accounts = parse_save_reserve_accounts(remaining_accounts, &offsets)?;
+    let klend_program = account(KLEND_PROGRAM_INDEX)?;
+
+    let (marginfi_liquidity, marginfi_capacity) = if check_marginfi_accounts(config, &marginfi_accounts)? {
+        (
+            get_marginfi_available_liquidity(&marginfi_accounts.liquidity_vault)?,
+            get_marginfi_deposit_capacity(&marginfi_accounts.bank)?,
+        )
+    } else {
+        (0, 0)
+    };
+
+    let (klend_liquidity, klend_capacity) = if check_klend_reserve_accounts(config, &klend_accounts, klend_program)? {
+        (
+            get_klend_available_liquidity(&klend_accounts.reserve)?,
+            get_klend_deposit_capacity(&klend_accounts.reserve)?,
+        )
+    } else {
+        (0, 0)
+    };
+
+    let (save_liquidity, save_capacity) = if check_save_reserve_accounts(config, &save_accounts)? {
+        (
+            get_save_available_liquidity(&save_accounts.reserve)?,
+            get_save_deposit_capacity(&save_accounts.reserve)?,
+        )
+    } else {
+        (0, 0)
+    };
+
+    Ok((
+        vec![
+            juplend_liquidity,
+            kamino_liquidity.available.min(kamino_withdrawable),
+            marginfi_liquidity,
+            klend_liquidity,
+            save_liquidity,
+        ],
+        vec![
+            u64::MAX,
+            u64::MAX,
+            marginfi_capacity,
+            klend_capacity,
+            save_capacity,
+        ],
+    ))
+}
+
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RebalancePlan {
+    /// Amount to pull out of each protocol into the vault
+    pub withdrawals: Vec<u64>,
+    /// Amount to push from the vault into each protocol
+    pub deposits: Vec<u64>,
+}
+
+/*
+   Plans the moves needed to bring every protocol to its target.
+
+   Idle USDC in the vault is sent straight to the protocols below target, so a deposit
+   only touches the protocols it is meant for. Protocols above target are only withdrawn
+   from when the idle USDC can't cover the deficits on its own.
+   Moves smaller than MIN_OPERATE_AMOUNT are skipped.
+
+   `max_withdrawals` and `max_deposits` are what each protocol can take or pay out right
+   now (see `get_protocol_capacities`). A constrained protocol gets a smaller move or none,
+   and what can't be placed stays idle until the next rebalance.
+*/
+pub fn plan_rebalance(
+    idle_usdc: u64,
+    current_balances: &[u64],
+    target_balances: &[u64],
+    max_withdrawals: &[u64],
+    max_deposits: &[u64],
+) -> Result<RebalancePlan> {
+    require!(
+        current_balances.len() == target_balances.len()
+            && max_withdrawals.len() == target_balances.len()
+            && max_deposits.len() == target_balances.len(),
+        AggregatorError::InvalidProtocolIndex
+    );
+
+    let protocols = current_balances.len();
+    let mut deficits = vec![0u64; protocols];
+    let mut surpluses = vec![0u64; protocols];
+
+    for (i, (current, target)) in current_balances.iter().zip(target_balances.iter()).enumerate() {
+        deficits[i] = target.saturating_sub(*current);
+        surpluses[i] = current.saturating_sub(*target).min(max_withdrawals[i]);
+    }
+
+    // Targets are rounded down, give the rounding dust to the largest target
+    let total_deficit: u64 = deficits.iter().sum();
+    let total_surplus: u64 = surpluses.iter().sum();
+    let funds_for_deposits = idle_usdc
+        .checked_add(total_surplus)
+        .ok_or(AggregatorError::MathOverflow)?;
+    if funds_for_deposits > total_deficit {
+        if let Some(largest) = (0..protocols).max_by_key(|i| target_balances[*i]) {
+            deficits[largest] += funds_for_deposits - total_deficit;
         }
+    }
 
-        msg!("Withdrawing from Juplend: {}", amount_to_move);
-        juplend_accounts.withdraw_from_juplend(amount_to_move, config_bump)?;
-        msg!("JupLend withdrawal done. Depositing to Kamino: {}", amount_to_move);
+    for (deficit, max_deposit) in deficits.iter_mut().zip(max_deposits.iter()) {
+        *deficit = (*deficit).min(*max_deposit);
+    }
 
-        kamino_accounts.execute_complete_deposit(amount_to_move, config_bump)?;
+    let mut withdrawals = vec![0u64; protocols];
+    let mut still_needed = deficits
+        .iter()
+        .filter(|deficit| **deficit >= MIN_OPERATE_AMOUNT)
+        .sum::<u64>()
+        .saturating_sub(idle_usdc);
 
-        
-    } else if new_current_kamino_balance > target_kamino {
-        // Kamino has excess, move to Juplend
-        let amount_to_move = new_current_kamino_balance
-            .checked_sub(target_kamino)
-            .ok_or(AggregatorError::MathOverflow)?;
-        
-        if amount_to_move < MIN_OPERATE_AMOUNT {
-            msg!("Amount to move from Kamino ({}) is too small, skipping rebalance", amount_to_move);
-            return Ok(());
+    // Pull only what idle USDC can't cover, starting with the most over-allocated protocol
+    while still_needed > 0 {
+        let Some(i) = (0..protocols)
+            .filter(|i| surpluses[*i] > withdrawals[*i])
+            .max_by_key(|i| surpluses[*i] - withdrawals[*i])
+        else {
+            break;
+        };
+        let amount = still_needed.min(surpluses[i] - withdrawals[i]);
+        withdrawals[i] += amount;
+        still_needed -= amount;
+    }
+
+    for withdrawal in withdrawals.iter_mut() {
+        if *withdrawal < MIN_OPERATE_AMOUNT {
+            *withdrawal = 0;
         }
-        
+    }
+
+    let mut available = idle_usdc
+        .checked_add(withdrawals.iter().sum::<u64>())
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    let mut deposits = vec![0u64; protocols];
+    for i in 0..protocols {
+        if deficits[i] < MIN_OPERATE_AMOUNT || withdrawals[i] > 0 {
+            continue;
+        }
+        let amount = deficits[i].min(available);
+        if amount < MIN_OPERATE_AMOUNT {
+            continue;
+        }
+        deposits[i] = amount;
+        available -= amount;
+    }
+
+    Ok(RebalancePlan { withdrawals, deposits })
+}
+
+
+#[allow(clippy::too_many_arguments)]
+fn execute_rebalance<'info>(
+    juplend_accounts: &Juplend<'info>,
+    kamino_accounts: &KaminoVault<'info>,
+    marginfi_accounts: &Marginfi<'info>,
+    klend_accounts: &KlendReserve<'info>,
+    save_accounts: &SaveReserve<'info>,
+    vault_usdc: &mut InterfaceAccount<'info, TokenAccount>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    rebalance_plan: &RebalancePlan,
+    config_bump: u8,
+) -> Result<()> {
+
+    msg!("Rebalance plan - withdrawals: {:?}, deposits: {:?}", rebalance_plan.withdrawals, rebalance_plan.deposits);
+
+    // Step 1: Pull the surplus that idle USDC can't cover into the vault
+    if rebalance_plan.withdrawals[0] > 0 {
+        msg!("Withdrawing from Juplend: {}", rebalance_plan.withdrawals[0]);
+        juplend_accounts.withdraw_from_juplend(rebalance_plan.withdrawals[0], config_bump)?;
+    }
+
+    if rebalance_plan.withdrawals[1] > 0 {
         // Get Kamino shares to withdraw
         let current_slot = Clock::get()?.slot;
         let kamino_user_shares_ata_account_info = InterfaceAccount::<TokenAccount>::try_from(&remaining_accounts[31])?;
         let kamino_user_state_account_info = &remaining_accounts[30];
         let kamino_vault_state_account_info = &remaining_accounts[13];
 
-        
         let temp_reserve_accounts : Vec<ReserveWithdrawAccounts<'info>> = kamino_accounts.reserve_accounts.clone();
         let reserve_accounts: Vec<AccountInfo<'info>> = temp_reserve_accounts.iter().map(|x| x.reserve.clone()).collect();
 
+        msg!("Withdrawing from Kamino: {}", rebalance_plan.withdrawals[1]);
+
         kamino_accounts.withdraw_from_kamino_by_shares(
             &kamino_user_shares_ata_account_info,
             kamino_user_state_account_info,
             kamino_vault_state_account_info,
             &reserve_accounts,
             current_slot,
-            amount_to_move,
+            rebalance_plan.withdrawals[1],
             config_bump,
         )?;
+    }
 
-        msg!("Kamino vault usdc amount before withdraw: {}", vault_usdc.amount);
+    if rebalance_plan.withdrawals[2] > 0 {
+        msg!("Withdrawing from MarginFi: {}", rebalance_plan.withdrawals[2]);
+        marginfi_accounts.withdraw_from_marginfi(rebalance_plan.withdrawals[2], config_bump)?;
+    }
 
-        
+    if rebalance_plan.withdrawals[3] > 0 {
+        msg!("Redeeming from klend reserve: {}", rebalance_plan.withdrawals[3]);
+        klend_accounts.redeem_from_klend(rebalance_plan.withdrawals[3], config_bump)?;
+    }
+
+    if rebalance_plan.withdrawals[4] > 0 {
+        msg!("Redeeming from Save reserve: {}", rebalance_plan.withdrawals[4]);
+        save_accounts.redeem_from_save(rebalance_plan.withdrawals[4], config_bump)?;
+    }
 
-        vault_usdc.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
+    vault_usdc.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
+    msg!("Vault balance: {}", vault_usdc.amount);
 
-        msg!("Kamino vault usdc amount after reload: {}", vault_usdc.amount);
-        msg!("Depositing to Juplend: {}", vault_usdc.amount);
-        juplend_accounts.deposit_to_juplend(vault_usdc.amount, config_bump)?;
+    // Step 2: Send vault USDC straight to the protocols below target.
+    // Kamino share rounding can leave the vault slightly short, so cap by what is there.
+    let mut vault_balance = vault_usdc.amount;
+
+    let juplend_deposit = rebalance_plan.deposits[0].min(vault_balance);
+    if juplend_deposit >= MIN_OPERATE_AMOUNT {
+        msg!("Depositing to Juplend: {}", juplend_deposit);
+        juplend_accounts.deposit_to_juplend(juplend_deposit, config_bump)?;
+        vault_balance -= juplend_deposit;
+    }
+
+    let kamino_deposit = rebalance_plan.deposits[1].min(vault_balance);
+    if kamino_deposit >= MIN_OPERATE_AMOUNT {
+        msg!("Depositing to Kamino: {}", kamino_deposit);
+        kamino_accounts.execute_complete_deposit(kamino_deposit, config_bump)?;
+        vault_balance -= kamino_deposit;
+    }
+
+    let marginfi_deposit = rebalance_plan.deposits[2].min(vault_balance);
+    if marginfi_deposit >= MIN_OPERATE_AMOUNT {
+        msg!("Depositing to MarginFi: {}", marginfi_deposit);
+        marginfi_accounts.deposit_to_marginfi(marginfi_deposit, config_bump)?;
+        vault_balance -= marginfi_deposit;
+    }
+
+    let klend_deposit = rebalance_plan.deposits[3].min(vault_balance);
+    if klend_deposit >= MIN_OPERATE_AMOUNT {
+        msg!("Depositing to klend reserve: {}", klend_deposit);
+        klend_accounts.deposit_to_klend(klend_deposit, config_bump)?;
+        vault_balance -= klend_deposit;
     }
 
+    let save_deposit = rebalance_plan.deposits[4].min(vault_balance);
+    if save_deposit >= MIN_OPERATE_AMOUNT {
+        msg!("Depositing to Save reserve: {}", save_deposit);
+        save_accounts.deposit_to_save(save_deposit, config_bump)?;
+    }
+
+    vault_usdc.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NO_LIMIT: [u64; 2] = [u64::MAX, u64::MAX];
+
+    #[test]
+    fn test_drift_counts_idle_usdc() {
+        // 100_000 idle on 1_100_000 total, each protocol is 50_000 below target
+        let drift = calculate_allocation_drift_bps(&[500_000, 500_000], 100_000, &[5000, 5000]).unwrap();
+        assert_eq!(drift, 454);
+    }
+
+    #[test]
+    fn test_drift_is_zero_when_on_target() {
+        assert_eq!(calculate_allocation_drift_bps(&[700_000, 300_000], 0, &[7000, 3000]).unwrap(), 0);
+        assert_eq!(calculate_allocation_drift_bps(&[0, 0], 0, &[7000, 3000]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_deposit_goes_straight_to_targets() {
+        // 100_000 idle on a balanced 50/50 vault, no inter-protocol move needed
+        let plan = plan_rebalance(100_000, &[500_000, 500_000], &[550_000, 550_000], &NO_LIMIT, &NO_LIMIT).unwrap();
+        assert_eq!(plan.withdrawals, vec![0, 0]);
+        assert_eq!(plan.deposits, vec![50_000, 50_000]);
+    }
+
+    #[test]
+    fn test_idle_tops_up_the_protocol_below_target() {
+        // Juplend is over target, idle USDC plus its surplus fill Kamino
+        let plan = plan_rebalance(100_000, &[600_000, 400_000], &[550_000, 550_000], &NO_LIMIT, &NO_LIMIT).unwrap();
+        assert_eq!(plan.withdrawals, vec![50_000, 0]);
+        assert_eq!(plan.deposits, vec![0, 150_000]);
+    }
+
+    #[test]
+    fn test_withdraws_only_remaining_imbalance() {
+        let plan = plan_rebalance(0, &[700_000, 300_000], &[500_000, 500_000], &NO_LIMIT, &NO_LIMIT).unwrap();
+        assert_eq!(plan.withdrawals, vec![200_000, 0]);
+        assert_eq!(plan.deposits, vec![0, 200_000]);
+    }
+
+    #[test]
+    fn test_constrained_protocols_get_smaller_moves() {
+        // Kamino reserves can only pay out 80_000, JupLend gets just that
+        let plan = plan_rebalance(0, &[300_000, 700_000], &[500_000, 500_000], &[u64::MAX, 80_000], &NO_LIMIT).unwrap();
+        assert_eq!(plan.withdrawals, vec![0, 80_000]);
+        assert_eq!(plan.deposits, vec![80_000, 0]);
+
+        // Kamino takes no deposits, idle USDC stays in the vault and JupLend isn't drained for it
+        let plan = plan_rebalance(100_000, &[700_000, 300_000], &[550_000, 550_000], &NO_LIMIT, &[u64::MAX, 0]).unwrap();
+        assert_eq!(plan.withdrawals, vec![0, 0]);
+        assert_eq!(plan.deposits, vec![0, 0]);
+    }
+
+    #[test]
+    fn test_skips_dust_moves() {
+        let plan = plan_rebalance(500, &[500_000, 500_000], &[500_250, 500_250], &NO_LIMIT, &NO_LIMIT).unwrap();
+        assert_eq!(plan.withdrawals, vec![0, 0]);
+        assert_eq!(plan.deposits, vec![0, 0]);
+    }
+}
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke, *};
+
+use crate::{
+    errors::AggregatorError,
+    helpers::{
+        kamino::kamino_reserve_accounts::{parse_reserve_accounts, KLEND_PROGRAM_INDEX},
+        klend::klend_reserve_accounts::parse_klend_reserve_accounts,
+        marginfi::marginfi_accounts::parse_marginfi_accounts,
+        save::save_reserve_accounts::parse_save_reserve_accounts,
+        segment_offsets::SegmentOffsets,
+    },
+};
+
+
+fn get_update_rate_discriminator() -> Vec<u8> {
+    vec![24, 225, 53, 189, 72, 212, 225, 178]
+}
+
+fn get_refresh_reserve_discriminator() -> Vec<u8> {
+    vec![2, 218, 138, 235, 79, 201, 25, 102]
+}
+
+fn get_accrue_bank_interest_discriminator() -> Vec<u8> {
+    vec![108, 201, 30, 87, 47, 65, 97, 188]
+}
+
+/// Save is a native program, its instructions start with a one-byte tag
+const SAVE_REFRESH_RESERVE_TAG: u8 = 3;
+
+
+/// JupLend lending program in remaining accounts
+const JUPLEND_LENDING_PROGRAM_INDEX: usize = 12;
+
+/// pyth, switchboard price, switchboard twap and scope, klend's program id where a reserve has none
+pub const REFRESH_ORACLES_PER_RESERVE: usize = 4;
+
+
+/*
+   Opt-in exact pricing. When the refresh block follows the reserve groups
+
+   remaining_accounts = [protocol accounts] + [USDC mint] + [4 oracles x number of vault reserves]
+
+   JupLend's `update_rate`, klend's `refresh_reserve` and marginfi's
+   `lending_pool_accrue_bank_interest` run first, so the lending, the reserves and the bank
+   carry this slot's exchange prices and the balance reads use them as they are instead of
+   estimating the interest since the last update (MarginFi isn't estimated, it lags).
+   The direct klend reserve and the Save reserve are refreshed with the oracles in their
+   own segments.
+
+   Without the block, or with the lending, a reserve or the bank passed read-only (view
+   simulations), nothing is refreshed and pricing falls back to estimation. Returns whether
+   it refreshed.
+*/
+pub fn refresh_protocol_state<'info>(remaining_accounts: &[AccountInfo<'info>]) -> Result<bool> {
+    let account = |index: usize| remaining_accounts.get(index).ok_or(AggregatorError::MissingAccount);
+
+    let reserve_accounts = parse_reserve_accounts(remaining_accounts)?;
+    let offsets = SegmentOffsets::new(reserve_accounts.len());
+    let marginfi_accounts = parse_marginfi_accounts(remaining_accounts, &offsets)?;
+    let klend_accounts = parse_klend_reserve_accounts(remaining_accounts, &offsets)?;
+    let save_accounts = parse_save_reserve_accounts(remaining_accounts, &offsets)?;
+    let refresh_start = offsets.refresh;
+
+    let Some(refresh_accounts) = remaining_accounts
+        .get(refresh_start..refresh_start + 1 + reserve_accounts.len() * REFRESH_ORACLES_PER_RESERVE)
+    else {
+        return Ok(false);
+    };
+
+    let lending = account(0)?;
+    if !lending.is_writable
+        || reserve_accounts.iter().any(|accounts| !accounts.reserve.is_writable)
+        || !marginfi_accounts.bank.is_writable
+        || !klend_accounts.reserve.is_writable
+        || !save_accounts.reserve.is_writable
+    {
+        msg!("Refresh accounts are read-only, estimating exchange prices");
+        return Ok(false);
+    }
+
+    let (mint, f_token_mint, token_reserve, rewards_rate_model) =
+        (&refresh_accounts[0], account(2)?, account(5)?, account(1)?);
+
+    let account_metas = vec![
+        // lending (mutable)
+        AccountMeta::new(*lending.key, false),
+        AccountMeta::new_readonly(*mint.key, false),
+        AccountMeta::new_readonly(*f_token_mint.key, false),
+        // supply_token_reserves_liquidity
+        AccountMeta::new_readonly(*token_reserve.key, false),
+        AccountMeta::new_readonly(*rewards_rate_model.key, false),
+    ];
+
+    invoke(
+        &Instruction {
+            program_id: *account(JUPLEND_LENDING_PROGRAM_INDEX)?.key,
+            accounts: account_metas,
+            data: get_update_rate_discriminator(),
+        },
+        &[
+            lending.clone(),
+            mint.clone(),
+            f_token_mint.clone(),
+            token_reserve.clone(),
+            rewards_rate_model.clone(),
+        ],
+    )
+    .map_err(|e| {
+        msg!("JupLend update_rate CPI failed with error: {:?}", e);
+        AggregatorError::CpiToLendingProgramFailed
+    })?;
+
+    let klend_program = account(KLEND_PROGRAM_INDEX)?;
+    for (i, accounts) in reserve_accounts.iter().enumerate() {
+        let oracles = &refresh_accounts[1 + i * REFRESH_ORACLES_PER_RESERVE..1 + (i + 1) * REFRESH_ORACLES_PER_RESERVE];
+        refresh_klend_reserve(klend_program, &accounts.reserve, &accounts.lending_market, oracles)?;
+    }
+
+    refresh_klend_reserve(
+        klend_program,
+        &klend_accounts.reserve,
+        &klend_accounts.lending_market,
+        &klend_accounts.oracles,
+    )?;
+
+    refresh_save_reserve(&save_accounts.save_program, &save_accounts.reserve, &save_accounts.oracles)?;
+
+    invoke(
+        &Instruction {
+            program_id: *marginfi_accounts.marginfi_program.key,
+            accounts: vec![
+                AccountMeta::new_readonly(*marginfi_accounts.group.key, false),
+                // bank (mutable)
+                AccountMeta::new(*marginfi_accounts.bank.key, false),
+            ],
+            data: get_accrue_bank_interest_discriminator(),
+        },
+        &[marginfi_accounts.group.clone(), marginfi_accounts.bank.clone()],
+    )
+    .map_err(|e| {
+        msg!("MarginFi accrue_bank_interest CPI failed with error: {:?}", e);
+        AggregatorError::CpiToLendingProgramFailed
+    })?;
+
+    msg!(
+        "Refreshed JupLend lending, {} Kamino reserves, the MarginFi bank, the klend reserve and the Save reserve",
+        reserve_accounts.len()
+    );
+
+    Ok(true)
+}
+
+
+/// klend's `refresh_reserve`, `oracles` in REFRESH_ORACLES_PER_RESERVE order
+pub fn refresh_klend_reserve<'info>(
+    klend_program: &AccountInfo<'info>,
+    reserve: &AccountInfo<'info>,
+    lending_market: &AccountInfo<'info>,
+    oracles: &[AccountInfo<'info>],
+) -> Result<()> {
+    let mut account_metas = Vec::with_capacity(2 + REFRESH_ORACLES_PER_RESERVE);
+    account_metas.push(AccountMeta::new(*reserve.key, false));
+    account_metas.push(AccountMeta::new_readonly(*lending_market.key, false));
+    account_metas.extend(oracles.iter().map(|oracle| AccountMeta::new_readonly(*oracle.key, false)));
+
+    let mut accounts_for_cpi = Vec::with_capacity(2 + REFRESH_ORACLES_PER_RESERVE);
+    accounts_for_cpi.push(reserve.clone());
+    accounts_for_cpi.push(lending_market.clone());
+    accounts_for_cpi.extend(oracles.iter().cloned());
+
+    invoke(
+        &Instruction {
+            program_id: *klend_program.key,
+            accounts: account_metas,
+            data: get_refresh_reserve_discriminator(),
+        },
+        &accounts_for_cpi,
+    )
+    .map_err(|e| {
+        msg!("Kamino refresh_reserve CPI failed with error: {:?}", e);
+        AggregatorError::CpiToLendingProgramFailed
+    })?;
+
+    Ok(())
+}
+
+
+/// Save's `refresh_reserve`, `oracles` are pyth, switchboard and the extra oracle Save only reads when configured
+pub fn refresh_save_reserve<'info>(
+    save_program: &AccountInfo<'info>,
+    reserve: &AccountInfo<'info>,
+    oracles: &[AccountInfo<'info>],
+) -> Result<()> {
+    let mut account_metas = Vec::with_capacity(1 + oracles.len());
+    account_metas.push(AccountMeta::new(*reserve.key, false));
+    account_metas.extend(oracles.iter().map(|oracle| AccountMeta::new_readonly(*oracle.key, false)));
+
+    let mut accounts_for_cpi = Vec::with_capacity(1 + oracles.len());
+    accounts_for_cpi.push(reserve.clone());
+    accounts_for_cpi.extend(oracles.iter().cloned());
+
+    invoke(
+        &Instruction {
+            program_id: *save_program.key,
+            accounts: account_metas,
+            data: vec![SAVE_REFRESH_RESERVE_TAG],
+        },
+        &accounts_for_cpi,
+    )
+    .map_err(|e| {
+        msg!("Save refresh_reserve CPI failed with error: {:?}", e);
+        AggregatorError::CpiToLendingProgramFailed
+    })?;
+
+    Ok(())
+}
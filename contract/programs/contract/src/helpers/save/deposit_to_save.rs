@@ -0,0 +1,130 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+use anchor_spl::token_interface::{TokenAccount, TokenInterface};
+
+use crate::{
+    errors::AggregatorError,
+    helpers::{
+        refresh_protocol_state::refresh_save_reserve,
+        save::save_reserve_accounts::{check_save_reserve_accounts, parse_save_reserve_accounts},
+        segment_offsets::SegmentOffsets,
+    },
+    states::AggregatorConfig,
+};
+
+
+
+/// `deposit_reserve_liquidity` instruction tag
+const DEPOSIT_RESERVE_LIQUIDITY_TAG: u8 = 4;
+
+
+pub struct SaveReserve<'info> {
+    pub signer: AccountInfo<'info>,
+    pub asset_token_account: AccountInfo<'info>,
+
+    // Reserve accounts
+    pub reserve: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub lending_market_authority: AccountInfo<'info>,
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+    pub reserve_collateral_mint: AccountInfo<'info>,
+    pub ctoken_account: AccountInfo<'info>,
+    pub oracles: Vec<AccountInfo<'info>>,
+
+    pub token_program: AccountInfo<'info>,
+
+    // Target lending program
+    pub save_program: AccountInfo<'info>,
+}
+
+
+impl<'info> SaveReserve<'info> {
+    pub fn new(
+        config: &Account<'info, AggregatorConfig>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        offsets: &SegmentOffsets,
+        vault_usdc: &InterfaceAccount<'info, TokenAccount>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<Box<SaveReserve<'info>>> {
+
+        let accounts = parse_save_reserve_accounts(remaining_accounts, offsets)?;
+
+        check_save_reserve_accounts(config, &accounts)?;
+
+        Ok(Box::new(Self {
+            signer: config.to_account_info(),
+            asset_token_account: vault_usdc.to_account_info(),
+            reserve: accounts.reserve,
+            lending_market: accounts.lending_market,
+            lending_market_authority: accounts.lending_market_authority,
+            reserve_liquidity_supply: accounts.reserve_liquidity_supply,
+            reserve_collateral_mint: accounts.reserve_collateral_mint,
+            ctoken_account: accounts.ctoken_account,
+            oracles: accounts.oracles,
+            token_program: token_program.to_account_info(),
+            save_program: accounts.save_program,
+        }))
+    }
+
+    /// Save rejects deposits and redemptions on a reserve that wasn't refreshed this slot
+    pub fn refresh_reserve(&self) -> Result<()> {
+        refresh_save_reserve(&self.save_program, &self.reserve, &self.oracles)
+    }
+
+    pub fn deposit_to_save(&self, amount: u64, config_bump: u8) -> Result<()> {
+
+        self.refresh_reserve()?;
+
+        let mut instruction_data = vec![DEPOSIT_RESERVE_LIQUIDITY_TAG];
+        instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+        let account_metas = vec![
+            // source_liquidity (mutable)
+            AccountMeta::new(*self.asset_token_account.key, false),
+            // destination_collateral (mutable)
+            AccountMeta::new(*self.ctoken_account.key, false),
+            // reserve (mutable)
+            AccountMeta::new(*self.reserve.key, false),
+            // reserve_liquidity_supply (mutable)
+            AccountMeta::new(*self.reserve_liquidity_supply.key, false),
+            // reserve_collateral_mint (mutable)
+            AccountMeta::new(*self.reserve_collateral_mint.key, false),
+            AccountMeta::new_readonly(*self.lending_market.key, false),
+            AccountMeta::new_readonly(*self.lending_market_authority.key, false),
+            // user_transfer_authority (signer)
+            AccountMeta::new_readonly(*self.signer.key, true),
+            AccountMeta::new_readonly(*self.token_program.key, false),
+        ];
+
+        let instruction = Instruction {
+            program_id: *self.save_program.key,
+            accounts: account_metas,
+            data: instruction_data,
+        };
+
+        let seeds = &[b"config".as_ref(), &[config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+
+        invoke_signed(
+            &instruction,
+            &[
+                self.asset_token_account.clone(),
+                self.ctoken_account.clone(),
+                self.reserve.clone(),
+                self.reserve_liquidity_supply.clone(),
+                self.reserve_collateral_mint.clone(),
+                self.lending_market.clone(),
+                self.lending_market_authority.clone(),
+                self.signer.clone(),
+                self.token_program.clone(),
+            ],
+            signer_seeds,
+        )
+        .map_err(|e| {
+            msg!("Save deposit_reserve_liquidity CPI failed with error: {:?}", e);
+            AggregatorError::CpiToLendingProgramFailed
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::WAD,
+    errors::AggregatorError,
+    helpers::{
+        klend::get_klend_balance::{collateral_to_liquidity, get_ctoken_amount},
+        save::save_reserve_reader::{read_save_reserve, SaveReserveFields},
+    },
+    states::kamino::Fraction,
+};
+
+
+const INITIAL_COLLATERAL_RATE: Fraction = Fraction::ONE;
+
+
+/*
+   USDC behind the cTokens the config PDA holds in the Save reserve
+
+   balance = ctokens / exchange_rate, rounded down
+
+   Same shape as a klend reserve, only Save keeps its amounts in WADs. The reserve's
+   interest isn't estimated forward, callers refresh the reserve first or the balance
+   lags by the interest since its last update.
+   A cToken account that doesn't exist yet holds nothing.
+*/
+pub fn get_save_balance(ctoken_account: &AccountInfo, reserve: &AccountInfo) -> Result<u64> {
+    let ctokens = get_ctoken_amount(ctoken_account)?;
+    if ctokens == 0 {
+        return Ok(0);
+    }
+
+    let reserve = read_save_reserve(&reserve.try_borrow_data()?)?;
+    collateral_to_liquidity(ctokens, calculate_save_exchange_rate(&reserve)?)
+}
+
+
+/// available + borrowed - protocol fees, in USDC
+pub fn calculate_save_total_supply(reserve: &SaveReserveFields) -> Result<Fraction> {
+    let total_supply_wads = (reserve.available_amount as u128)
+        .checked_mul(WAD)
+        .and_then(|available| available.checked_add(reserve.borrowed_amount_wads))
+        .and_then(|supply| supply.checked_sub(reserve.accumulated_protocol_fees_wads))
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    wads_to_fraction(total_supply_wads)
+}
+
+
+/// cTokens per USDC, mint_total_supply / total_supply or 1.0 while either is zero
+pub fn calculate_save_exchange_rate(reserve: &SaveReserveFields) -> Result<Fraction> {
+    let total_supply = calculate_save_total_supply(reserve)?;
+    if reserve.collateral_mint_total_supply == 0 || total_supply == Fraction::ZERO {
+        return Ok(INITIAL_COLLATERAL_RATE);
+    }
+
+    Fraction::from(reserve.collateral_mint_total_supply)
+        .checked_div(total_supply)
+        .ok_or(AggregatorError::MathOverflow.into())
+}
+
+
+/// WAD-scaled amount as a Fraction, without going through a u128 that overflows 68 integer bits
+fn wads_to_fraction(wads: u128) -> Result<Fraction> {
+    let integer = Fraction::checked_from_num(wads / WAD).ok_or(AggregatorError::MathOverflow)?;
+    let fractional = Fraction::from_bits(((wads % WAD) << Fraction::FRAC_NBITS) / WAD);
+
+    integer.checked_add(fractional).ok_or(AggregatorError::MathOverflow.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exchange_rate_from_wads() {
+        // 600_000 available + 400_000.5 borrowed - 0.5 fees backs 800_000 cTokens
+        let reserve = SaveReserveFields {
+            available_amount: 600_000,
+            borrowed_amount_wads: 400_000 * WAD + WAD / 2,
+            accumulated_protocol_fees_wads: WAD / 2,
+            collateral_mint_total_supply: 800_000,
+            ..Default::default()
+        };
+
+        let rate = calculate_save_exchange_rate(&reserve).unwrap();
+        assert_eq!(calculate_save_total_supply(&reserve).unwrap(), Fraction::from_num(1_000_000));
+        assert_eq!(collateral_to_liquidity(80_000, rate).unwrap(), 100_000);
+
+        // An empty reserve starts at one cToken per USDC
+        assert_eq!(calculate_save_exchange_rate(&SaveReserveFields::default()).unwrap(), Fraction::ONE);
+    }
+}
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::save::{get_save_balance::calculate_save_total_supply, save_reserve_reader::read_save_reserve},
+    states::kamino::FractionExtra,
+};
+
+
+/// What the reserve can pay out right now, its unborrowed liquidity
+pub fn get_save_available_liquidity(reserve: &AccountInfo) -> Result<u64> {
+    Ok(read_save_reserve(&reserve.try_borrow_data()?)?.available_amount)
+}
+
+
+/// Room left under the reserve's deposit limit
+pub fn get_save_deposit_capacity(reserve: &AccountInfo) -> Result<u64> {
+    let reserve = read_save_reserve(&reserve.try_borrow_data()?)?;
+    let total_supply = calculate_save_total_supply(&reserve)?
+        .try_to_ceil::<u64>()
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    Ok(reserve.deposit_limit.saturating_sub(total_supply))
+}
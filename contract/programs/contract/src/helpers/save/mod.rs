@@ -0,0 +1,11 @@
+pub mod save_reserve_accounts;
+
+pub mod save_reserve_reader;
+
+pub mod deposit_to_save;
+
+pub mod redeem_from_save;
+
+pub mod get_save_balance;
+
+pub mod get_save_liquidity;
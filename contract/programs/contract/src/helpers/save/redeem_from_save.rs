@@ -0,0 +1,93 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+
+use crate::{
+    errors::AggregatorError,
+    helpers::{
+        klend::get_klend_balance::{get_ctoken_amount, liquidity_to_collateral},
+        save::{
+            deposit_to_save::SaveReserve,
+            get_save_balance::calculate_save_exchange_rate,
+            save_reserve_reader::read_save_reserve,
+        },
+    },
+};
+
+
+/// `redeem_reserve_collateral` instruction tag
+const REDEEM_RESERVE_COLLATERAL_TAG: u8 = 5;
+
+
+
+impl<'info> SaveReserve<'info> {
+    /*
+       Redeems enough cTokens for `usdc_amount`, capped at the cTokens we hold.
+
+       The reserve is refreshed first, so the exchange rate read after it is the one Save
+       redeems at and ceil(usdc_amount * rate) cTokens pay out at least `usdc_amount`.
+    */
+    pub fn redeem_from_save(&self, usdc_amount: u64, config_bump: u8) -> Result<()> {
+
+        self.refresh_reserve()?;
+
+        let exchange_rate = calculate_save_exchange_rate(&read_save_reserve(&self.reserve.try_borrow_data()?)?)?;
+        let ctokens = liquidity_to_collateral(usdc_amount, exchange_rate)?
+            .min(get_ctoken_amount(&self.ctoken_account)?);
+
+        if ctokens == 0 {
+            return Ok(());
+        }
+
+        let mut instruction_data = vec![REDEEM_RESERVE_COLLATERAL_TAG];
+        instruction_data.extend_from_slice(&ctokens.to_le_bytes());
+
+        let account_metas = vec![
+            // source_collateral (mutable)
+            AccountMeta::new(*self.ctoken_account.key, false),
+            // destination_liquidity (mutable)
+            AccountMeta::new(*self.asset_token_account.key, false),
+            // reserve (mutable)
+            AccountMeta::new(*self.reserve.key, false),
+            // reserve_collateral_mint (mutable)
+            AccountMeta::new(*self.reserve_collateral_mint.key, false),
+            // reserve_liquidity_supply (mutable)
+            AccountMeta::new(*self.reserve_liquidity_supply.key, false),
+            AccountMeta::new_readonly(*self.lending_market.key, false),
+            AccountMeta::new_readonly(*self.lending_market_authority.key, false),
+            // user_transfer_authority (signer)
+            AccountMeta::new_readonly(*self.signer.key, true),
+            AccountMeta::new_readonly(*self.token_program.key, false),
+        ];
+
+        let instruction = Instruction {
+            program_id: *self.save_program.key,
+            accounts: account_metas,
+            data: instruction_data,
+        };
+
+        let seeds = &[b"config".as_ref(), &[config_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+
+        invoke_signed(
+            &instruction,
+            &[
+                self.ctoken_account.clone(),
+                self.asset_token_account.clone(),
+                self.reserve.clone(),
+                self.reserve_collateral_mint.clone(),
+                self.reserve_liquidity_supply.clone(),
+                self.lending_market.clone(),
+                self.lending_market_authority.clone(),
+                self.signer.clone(),
+                self.token_program.clone(),
+            ],
+            signer_seeds,
+        )
+        .map_err(|e| {
+            msg!("Save redeem_reserve_collateral CPI failed with error: {:?}", e);
+            AggregatorError::CpiToLendingProgramFailed
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::save::save_reserve_reader::read_save_reserve,
+    helpers::segment_offsets::SegmentOffsets,
+    states::AggregatorConfig,
+};
+
+
+/// Accounts in the Save reserve segment, in `SaveReserveAccounts` order
+pub const SAVE_RESERVE_ACCOUNTS_LEN: usize = 10;
+
+
+#[derive(Clone)]
+pub struct SaveReserveAccounts<'info> {
+    pub reserve: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub lending_market_authority: AccountInfo<'info>,
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+    pub reserve_collateral_mint: AccountInfo<'info>,
+    /// Config PDA's cToken ATA, created by `init_save_reserve`
+    pub ctoken_account: AccountInfo<'info>,
+    /// pyth, switchboard and extra oracle, the Save program id where the reserve has no extra one.
+    /// Save only takes deposits and redemptions on a reserve refreshed in the same slot
+    pub oracles: Vec<AccountInfo<'info>>,
+    pub save_program: AccountInfo<'info>,
+}
+
+
+/// The Save reserve segment sits right after the klend reserve segment
+pub fn parse_save_reserve_accounts<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    offsets: &SegmentOffsets,
+) -> Result<SaveReserveAccounts<'info>> {
+    let start = offsets.save;
+    let segment = remaining_accounts
+        .get(start..start + SAVE_RESERVE_ACCOUNTS_LEN)
+        .ok_or(AggregatorError::MissingAccount)?;
+
+    Ok(SaveReserveAccounts {
+        reserve: segment[0].clone(),
+        lending_market: segment[1].clone(),
+        lending_market_authority: segment[2].clone(),
+        reserve_liquidity_supply: segment[3].clone(),
+        reserve_collateral_mint: segment[4].clone(),
+        ctoken_account: segment[5].clone(),
+        oracles: segment[6..9].to_vec(),
+        save_program: segment[9].clone(),
+    })
+}
+
+
+/*
+   Same checks as `check_klend_reserve_accounts`, against the config's Save reserve and the
+   Save program passed at the end of the segment. The ATA is derived with the collateral
+   mint's owner, Save only mints classic SPL cTokens.
+*/
+pub fn check_save_reserve_accounts(config: &AggregatorConfig, accounts: &SaveReserveAccounts) -> Result<bool> {
+    require_keys_eq!(*accounts.reserve.key, config.save_reserve, AggregatorError::InvalidSaveReserve);
+
+    if config.save_reserve == Pubkey::default() {
+        return Ok(false);
+    }
+
+    require_keys_eq!(*accounts.reserve.owner, *accounts.save_program.key, AggregatorError::InvalidSaveReserve);
+    require_keys_eq!(
+        *accounts.reserve_collateral_mint.key,
+        read_save_reserve(&accounts.reserve.try_borrow_data()?)?.collateral_mint,
+        AggregatorError::InvalidSaveReserve
+    );
+
+    // Redemptions burn from this account, it has to be the config PDA's own cToken ATA
+    require_keys_eq!(
+        *accounts.ctoken_account.key,
+        get_associated_token_address_with_program_id(
+            &config.address()?,
+            accounts.reserve_collateral_mint.key,
+            accounts.reserve_collateral_mint.owner,
+        ),
+        AggregatorError::InvalidSaveReserve
+    );
+
+    Ok(true)
+}
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AggregatorError;
+
+
+/*
+   Save reserves are `Pack` accounts without a discriminator or field alignment,
+   so the fields we need are read at their byte offsets.
+
+   The account is always RESERVE_LEN bytes and starts with the program's layout version.
+*/
+pub const SAVE_RESERVE_LEN: usize = 619;
+pub const SAVE_RESERVE_VERSION: u8 = 1;
+
+const LAST_UPDATE_SLOT_OFFSET: usize = 1;
+const LENDING_MARKET_OFFSET: usize = 10;
+const LIQUIDITY_MINT_OFFSET: usize = 42;
+const LIQUIDITY_SUPPLY_OFFSET: usize = 75;
+const LIQUIDITY_PYTH_ORACLE_OFFSET: usize = 107;
+const LIQUIDITY_SWITCHBOARD_ORACLE_OFFSET: usize = 139;
+const LIQUIDITY_AVAILABLE_AMOUNT_OFFSET: usize = 171;
+const LIQUIDITY_BORROWED_AMOUNT_WADS_OFFSET: usize = 179;
+const COLLATERAL_MINT_OFFSET: usize = 227;
+const COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET: usize = 259;
+const CONFIG_DEPOSIT_LIMIT_OFFSET: usize = 323;
+const LIQUIDITY_ACCUMULATED_PROTOCOL_FEES_WADS_OFFSET: usize = 373;
+
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SaveReserveFields {
+    pub last_update_slot: u64,
+    pub lending_market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub pyth_oracle: Pubkey,
+    pub switchboard_oracle: Pubkey,
+    pub available_amount: u64,
+    /// Scaled by WAD (1e18)
+    pub borrowed_amount_wads: u128,
+    pub collateral_mint: Pubkey,
+    pub collateral_mint_total_supply: u64,
+    pub deposit_limit: u64,
+    /// Scaled by WAD (1e18)
+    pub accumulated_protocol_fees_wads: u128,
+}
+
+
+pub fn read_save_reserve(data: &[u8]) -> Result<SaveReserveFields> {
+    if data.len() != SAVE_RESERVE_LEN || data[0] != SAVE_RESERVE_VERSION {
+        msg!("Expected a Save reserve of {} bytes, got {}", SAVE_RESERVE_LEN, data.len());
+        return Err(AggregatorError::InvalidAccountData.into());
+    }
+
+    let pubkey = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+    let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let u128_at = |offset: usize| u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+
+    Ok(SaveReserveFields {
+        last_update_slot: u64_at(LAST_UPDATE_SLOT_OFFSET),
+        lending_market: pubkey(LENDING_MARKET_OFFSET),
+        liquidity_mint: pubkey(LIQUIDITY_MINT_OFFSET),
+        liquidity_supply: pubkey(LIQUIDITY_SUPPLY_OFFSET),
+        pyth_oracle: pubkey(LIQUIDITY_PYTH_ORACLE_OFFSET),
+        switchboard_oracle: pubkey(LIQUIDITY_SWITCHBOARD_ORACLE_OFFSET),
+        available_amount: u64_at(LIQUIDITY_AVAILABLE_AMOUNT_OFFSET),
+        borrowed_amount_wads: u128_at(LIQUIDITY_BORROWED_AMOUNT_WADS_OFFSET),
+        collateral_mint: pubkey(COLLATERAL_MINT_OFFSET),
+        collateral_mint_total_supply: u64_at(COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET),
+        deposit_limit: u64_at(CONFIG_DEPOSIT_LIMIT_OFFSET),
+        accumulated_protocol_fees_wads: u128_at(LIQUIDITY_ACCUMULATED_PROTOCOL_FEES_WADS_OFFSET),
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_fields_at_their_offsets() {
+        let (mint, collateral_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut data = vec![0u8; SAVE_RESERVE_LEN];
+        data[0] = SAVE_RESERVE_VERSION;
+        data[LIQUIDITY_MINT_OFFSET..LIQUIDITY_MINT_OFFSET + 32].copy_from_slice(mint.as_ref());
+        data[LIQUIDITY_AVAILABLE_AMOUNT_OFFSET..LIQUIDITY_AVAILABLE_AMOUNT_OFFSET + 8]
+            .copy_from_slice(&1_500_000u64.to_le_bytes());
+        data[COLLATERAL_MINT_OFFSET..COLLATERAL_MINT_OFFSET + 32].copy_from_slice(collateral_mint.as_ref());
+        data[CONFIG_DEPOSIT_LIMIT_OFFSET..CONFIG_DEPOSIT_LIMIT_OFFSET + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let reserve = read_save_reserve(&data).unwrap();
+        assert_eq!(reserve.liquidity_mint, mint);
+        assert_eq!(reserve.available_amount, 1_500_000);
+        assert_eq!(reserve.collateral_mint, collateral_mint);
+        assert_eq!(reserve.deposit_limit, u64::MAX);
+        assert_eq!(reserve.collateral_mint_total_supply, 0);
+
+        // Anything else, a klend reserve or a truncated account, is rejected
+        assert!(read_save_reserve(&data[..SAVE_RESERVE_LEN - 1]).is_err());
+        data[0] = 0;
+        assert!(read_save_reserve(&data).is_err());
+    }
+}
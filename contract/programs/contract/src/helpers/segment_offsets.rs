@@ -0,0 +1,54 @@
+use crate::{
+    helpers::{
+        kamino::kamino_reserve_accounts::{KAMINO_RESERVES_START, RESERVE_ACCOUNTS_LEN},
+        klend::klend_reserve_accounts::KLEND_RESERVE_ACCOUNTS_LEN,
+        marginfi::marginfi_accounts::MARGINFI_ACCOUNTS_LEN,
+        save::save_reserve_accounts::SAVE_RESERVE_ACCOUNTS_LEN,
+    },
+};
+
+
+/*
+   Where each segment after the Kamino reserve groups starts
+
+   remaining_accounts = [13 JupLend] + [20 Kamino vault] + [7 x number of vault reserves]
+                        + [7 MarginFi] + [11 klend reserve] + [10 Save reserve]
+                        + [optional refresh block]
+
+   Only the number of vault reserves moves them, so they're worked out once from the parsed
+   reserve groups and handed to every segment parser instead of each one reading the vault
+   state again.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SegmentOffsets {
+    pub marginfi: usize,
+    pub klend: usize,
+    pub save: usize,
+    pub refresh: usize,
+}
+
+
+impl SegmentOffsets {
+    pub fn new(vault_reserve_count: usize) -> Self {
+        let marginfi = KAMINO_RESERVES_START + vault_reserve_count * RESERVE_ACCOUNTS_LEN;
+        let klend = marginfi + MARGINFI_ACCOUNTS_LEN;
+        let save = klend + KLEND_RESERVE_ACCOUNTS_LEN;
+
+        Self { marginfi, klend, save, refresh: save + SAVE_RESERVE_ACCOUNTS_LEN }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_follow_the_reserve_groups() {
+        assert_eq!(
+            SegmentOffsets::new(2),
+            SegmentOffsets { marginfi: 47, klend: 54, save: 65, refresh: 75 }
+        );
+        assert_eq!(SegmentOffsets::new(0).marginfi, KAMINO_RESERVES_START);
+    }
+}
@@ -0,0 +1,196 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::AggregatorError,
+    helpers::{
+        calculate_shares_to_mint::calculate_shares_to_mint,
+        calculate_total_asset_balance::calculate_total_asset_balance,
+        calculate_usdc_for_shares::calculate_usdc_for_shares,
+        juplend::get_juplend_liquidity::get_juplend_available_liquidity,
+        kamino::{
+            farm_position::get_kamino_withdrawable_usdc,
+            get_kamino_liquidity::get_kamino_available_liquidity,
+            kamino_reserve_accounts::{get_reserve_account_infos, KLEND_PROGRAM_INDEX},
+        },
+        klend::{
+            get_klend_liquidity::get_klend_available_liquidity,
+            klend_reserve_accounts::{check_klend_reserve_accounts, parse_klend_reserve_accounts},
+        },
+        marginfi::{get_marginfi_liquidity::get_marginfi_available_liquidity, marginfi_accounts::{check_marginfi_accounts, parse_marginfi_accounts}},
+        save::{
+            get_save_liquidity::get_save_available_liquidity,
+            save_reserve_accounts::{check_save_reserve_accounts, parse_save_reserve_accounts},
+        },
+        segment_offsets::SegmentOffsets,
+    },
+    states::AggregatorConfig,
+};
+
+
+/*
+   ERC-4626 style conversions.
+
+   Deposits and redeems round down in favour of the vault, exactly like `deposit` and
+   `withdraw`, which use the same u128 math. `preview_mint` and `preview_withdraw` go the other way and round up, so that
+   depositing the previewed assets mints at least the requested shares and burning the
+   previewed shares returns at least the requested assets.
+*/
+
+/// (per-protocol balances, idle + all protocols)
+pub fn get_total_assets<'info>(
+    config: &AggregatorConfig,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    idle_usdc: u64,
+) -> Result<(Vec<u64>, u64)> {
+    let usdc_in_all_protocol = calculate_total_asset_balance(config, remaining_accounts)?;
+    let total_assets = usdc_in_all_protocol
+        .iter()
+        .try_fold(idle_usdc, |acc, x| acc.checked_add(*x))
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    Ok((usdc_in_all_protocol, total_assets))
+}
+
+
+pub fn convert_to_shares(assets: u64, total_shares: u64, total_assets: u64) -> Result<u64> {
+    calculate_shares_to_mint(assets, total_shares, total_assets)
+}
+
+
+pub fn convert_to_assets(shares: u64, total_shares: u64, total_assets: u64) -> Result<u64> {
+    calculate_usdc_for_shares(shares, total_shares, total_assets)
+}
+
+
+/// USDC to deposit so that `deposit` mints at least `shares`
+pub fn preview_mint_assets(shares: u64, total_shares: u64, total_assets: u64) -> Result<u64> {
+    if total_shares == 0 || total_assets == 0 {
+        return Ok(shares);
+    }
+
+    mul_div_ceil(shares, total_assets, total_shares)
+}
+
+
+/// cUSDC to burn so that `withdraw` returns at least `assets`
+pub fn preview_withdraw_shares(assets: u64, total_shares: u64, total_assets: u64) -> Result<u64> {
+    require!(assets <= total_assets, AggregatorError::InsufficientBalance);
+
+    if total_assets == 0 {
+        return Ok(0);
+    }
+
+    mul_div_ceil(assets, total_shares, total_assets)
+}
+
+
+/*
+   USDC that can leave the vault in one `withdraw`: idle USDC plus what each protocol can
+   pay out right now, capped by what we hold there and, for Kamino, by the farm shares
+   that aren't behind a cooldown. Same liquidity reads as `withdraw_from_protocols`.
+*/
+pub fn get_withdrawable_liquidity<'info>(
+    config: &AggregatorConfig,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    usdc_in_all_protocol: &[u64],
+    idle_usdc: u64,
+) -> Result<u64> {
+    let account = |index: usize| remaining_accounts.get(index).ok_or(AggregatorError::MissingAccount);
+
+    require!(usdc_in_all_protocol.len() == 5, AggregatorError::InvalidProtocolIndex);
+
+    let current_slot = Clock::get()?.slot;
+    let reserve_accounts = get_reserve_account_infos(remaining_accounts)?;
+
+    let juplend_liquidity = get_juplend_available_liquidity(account(5)?)?;
+    let kamino_liquidity = get_kamino_available_liquidity(account(13)?, &reserve_accounts, Some(current_slot))?;
+    let kamino_withdrawable = get_kamino_withdrawable_usdc(remaining_accounts, &reserve_accounts, current_slot)?;
+    let offsets = SegmentOffsets::new(reserve_accounts.len());
+    let marginfi_accounts = parse_marginfi_accounts(remaining_accounts, &offsets)?;
+    let marginfi_liquidity = if check_marginfi_accounts(config, &marginfi_accounts)? {
+        get_marginfi_available_liquidity(&marginfi_accounts.liquidity_vault)?
+    } else {
+        0
+    };
+    let klend_accounts = parse_klend_reserve_accounts(remaining_accounts, &offsets)?;
+    let klend_liquidity = if check_klend_reserve_accounts(config, &klend_accounts, account(KLEND_PROGRAM_INDEX)?)? {
+        get_klend_available_liquidity(&klend_accounts.reserve)?
+    } else {
+        0
+    };
+    let save_accounts = parse_save_reserve_accounts(remaining_accounts, &offsets)?;
+    let save_liquidity = if check_save_reserve_accounts(config, &save_accounts)? {
+        get_save_available_liquidity(&save_accounts.reserve)?
+    } else {
+        0
+    };
+
+    idle_usdc
+        .checked_add(juplend_liquidity.min(usdc_in_all_protocol[0]))
+        .and_then(|acc| acc.checked_add(kamino_liquidity.available.min(kamino_withdrawable).min(usdc_in_all_protocol[1])))
+        .and_then(|acc| acc.checked_add(marginfi_liquidity.min(usdc_in_all_protocol[2])))
+        .and_then(|acc| acc.checked_add(klend_liquidity.min(usdc_in_all_protocol[3])))
+        .and_then(|acc| acc.checked_add(save_liquidity.min(usdc_in_all_protocol[4])))
+        .ok_or(AggregatorError::MathOverflow.into())
+}
+
+
+/// `value * numerator / denominator` in u128, rounded down
+pub fn mul_div(value: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let result = (value as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(denominator as u128)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    u64::try_from(result).map_err(|_| AggregatorError::MathOverflow.into())
+}
+
+
+/// `value * numerator / denominator` in u128, rounded up
+pub fn mul_div_ceil(value: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let result = (value as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(AggregatorError::MathOverflow)?
+        .div_ceil(denominator as u128);
+
+    u64::try_from(result).map_err(|_| AggregatorError::MathOverflow.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_previews_round_against_the_caller() {
+        // 1_000_000 cUSDC backed by 1_100_003 USDC
+        let (total_shares, total_assets) = (1_000_000, 1_100_003);
+
+        let assets = preview_mint_assets(1_000, total_shares, total_assets).unwrap();
+        assert!(convert_to_shares(assets, total_shares, total_assets).unwrap() >= 1_000);
+        assert!(convert_to_shares(assets - 1, total_shares, total_assets).unwrap() < 1_000);
+
+        let shares = preview_withdraw_shares(1_000, total_shares, total_assets).unwrap();
+        assert!(convert_to_assets(shares, total_shares, total_assets).unwrap() >= 1_000);
+        assert!(convert_to_assets(shares - 1, total_shares, total_assets).unwrap() < 1_000);
+    }
+
+    #[test]
+    fn test_empty_vault_is_one_to_one() {
+        assert_eq!(convert_to_shares(5_000, 0, 0).unwrap(), 5_000);
+        assert_eq!(preview_mint_assets(5_000, 0, 0).unwrap(), 5_000);
+        assert!(preview_withdraw_shares(1, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_conversions_agree_with_previews_at_size() {
+        // 1e13 cUSDC backed by 1.1e13 USDC, u64 products of these overflow
+        let (total_shares, total_assets) = (10_000_000_000_000, 11_000_000_000_000);
+
+        let shares = convert_to_shares(1_000_000_000_000, total_shares, total_assets).unwrap();
+        assert_eq!(shares, 909_090_909_090);
+        assert_eq!(preview_mint_assets(shares, total_shares, total_assets).unwrap(), 999_999_999_999);
+        assert_eq!(convert_to_assets(shares, total_shares, total_assets).unwrap(), 999_999_999_999);
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AggregatorError;
+
+
+/// Token-2022 metadata limits we enforce so wallets can display the share token
+pub const MAX_SHARE_NAME_LEN: usize = 32;
+pub const MAX_SHARE_SYMBOL_LEN: usize = 10;
+pub const MAX_SHARE_URI_LEN: usize = 200;
+
+
+pub fn validate_share_metadata(name: &str, symbol: &str, uri: &str) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_SHARE_NAME_LEN,
+        AggregatorError::InvalidShareMetadata
+    );
+    require!(
+        !symbol.is_empty() && symbol.len() <= MAX_SHARE_SYMBOL_LEN,
+        AggregatorError::InvalidShareMetadata
+    );
+    require!(uri.len() <= MAX_SHARE_URI_LEN, AggregatorError::InvalidShareMetadata);
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_share_metadata() {
+        assert!(validate_share_metadata("Fluid USDC Conservative", "fUSDC-C", "https://fluid.example/c.json").is_ok());
+        assert!(validate_share_metadata("", "fUSDC", "").is_err());
+        assert!(validate_share_metadata("Fluid USDC", "FLUIDUSDCSHARE", "").is_err());
+    }
+}
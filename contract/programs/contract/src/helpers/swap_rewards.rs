@@ -0,0 +1,83 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+use anchor_spl::token_interface::{approve, revoke, Approve, Revoke};
+
+use crate::errors::AggregatorError;
+
+
+/*
+   Calls the configured swap program with caller-built instruction data.
+
+   `swap_accounts` are passed through in order. The config PDA never signs the swap, it
+   owns the vault and every protocol position. Instead the `swap-authority` PDA is made
+   delegate of the reward account for exactly `amount` and signs as the token owner, so the
+   swap can't move anything else. The delegation is revoked afterwards.
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn swap_rewards<'info>(
+    swap_program: &AccountInfo<'info>,
+    swap_accounts: &[AccountInfo<'info>],
+    swap_data: Vec<u8>,
+    amount: u64,
+    reward_token_account: &AccountInfo<'info>,
+    reward_token_program: &AccountInfo<'info>,
+    swap_authority: &AccountInfo<'info>,
+    swap_authority_bump: u8,
+    config: &AccountInfo<'info>,
+    config_bump: u8,
+) -> Result<()> {
+    let config_seeds = &[b"config".as_ref(), &[config_bump]];
+    let config_signer = &[&config_seeds[..]];
+
+    approve(
+        CpiContext::new_with_signer(
+            reward_token_program.clone(),
+            Approve {
+                to: reward_token_account.clone(),
+                delegate: swap_authority.clone(),
+                authority: config.clone(),
+            },
+            config_signer,
+        ),
+        amount,
+    )?;
+
+    let account_metas: Vec<AccountMeta> = swap_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer || account.key == swap_authority.key,
+            is_writable: account.is_writable,
+        })
+        .collect();
+
+    let instruction = Instruction {
+        program_id: *swap_program.key,
+        accounts: account_metas,
+        data: swap_data,
+    };
+
+    let seeds = &[b"swap-authority".as_ref(), &[swap_authority_bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let mut accounts_for_cpi = Vec::with_capacity(swap_accounts.len() + 1);
+    accounts_for_cpi.extend_from_slice(swap_accounts);
+    accounts_for_cpi.push(swap_program.clone());
+
+    invoke_signed(&instruction, &accounts_for_cpi, signer_seeds)
+    .map_err(|e| {
+        msg!("Swap CPI failed with error: {:?}", e);
+        AggregatorError::SwapFailed
+    })?;
+
+    // Whatever the swap didn't use stays with the config
+    revoke(CpiContext::new_with_signer(
+        reward_token_program.clone(),
+        Revoke {
+            source: reward_token_account.clone(),
+            authority: config.clone(),
+        },
+        config_signer,
+    ))?;
+
+    Ok(())
+}
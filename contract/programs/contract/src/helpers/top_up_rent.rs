@@ -0,0 +1,35 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+
+/*
+   Tops `account` up to rent exemption for its current size.
+
+   Needed after anything that grows an account without funding it: the Token-2022
+   metadata extension reallocs the mint, and `migrate_config` reallocs the config.
+*/
+pub fn top_up_rent<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let minimum_balance = Rent::get()?.minimum_balance(account.data_len());
+    let missing_lamports = minimum_balance.saturating_sub(account.lamports());
+
+    if missing_lamports > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            missing_lamports,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::SHARE_PRICE_PRECISION,
+    errors::AggregatorError,
+    helpers::{
+        calculate_total_asset_balance::calculate_total_asset_balance,
+        protocol_apy::get_protocol_apys,
+    },
+    states::{AggregatorConfig, VaultView},
+};
+
+
+pub fn build_vault_view<'info>(
+    config: &AggregatorConfig,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    idle_usdc: u64,
+    total_shares: u64,
+) -> Result<VaultView> {
+    let usdc_in_all_protocol = calculate_total_asset_balance(config, remaining_accounts)?;
+    let total_assets: u64 = usdc_in_all_protocol
+        .iter()
+        .try_fold(idle_usdc, |acc, x| acc.checked_add(*x))
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    let protocol_apys = get_protocol_apys(remaining_accounts, &config.kamino_interest_snapshot)?;
+
+    Ok(VaultView {
+        total_assets,
+        juplend_balance: usdc_in_all_protocol[0],
+        kamino_balance: usdc_in_all_protocol[1],
+        marginfi_balance: usdc_in_all_protocol[2],
+        klend_balance: usdc_in_all_protocol[3],
+        save_balance: usdc_in_all_protocol[4],
+        idle_usdc,
+        total_shares,
+        share_price: calculate_share_price(total_assets, total_shares)?,
+        juplend_apy_bps: protocol_apys.juplend_apy_bps,
+        kamino_apy_bps: protocol_apys.kamino_apy_bps,
+        juplend_bps: config.juplend_allocation_bps,
+        kamino_bps: config.kamino_allocation_bps,
+        marginfi_bps: config.marginfi_allocation_bps,
+        klend_bps: config.klend_allocation_bps,
+        save_bps: config.save_allocation_bps,
+    })
+}
+
+
+/// USDC per cUSDC scaled by SHARE_PRICE_PRECISION, 1:1 before the first deposit
+pub fn calculate_share_price(total_assets: u64, total_shares: u64) -> Result<u64> {
+    if total_shares == 0 {
+        return Ok(SHARE_PRICE_PRECISION as u64);
+    }
+
+    let share_price = (total_assets as u128)
+        .checked_mul(SHARE_PRICE_PRECISION)
+        .ok_or(AggregatorError::MathOverflow)?
+        .checked_div(total_shares as u128)
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    u64::try_from(share_price).map_err(|_| AggregatorError::MathOverflow.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_price() {
+        assert_eq!(calculate_share_price(0, 0).unwrap(), SHARE_PRICE_PRECISION as u64);
+        // 1_050_000 USDC backing 1_000_000 cUSDC -> 1.05
+        assert_eq!(calculate_share_price(1_050_000, 1_000_000).unwrap(), 1_050_000_000_000);
+    }
+}
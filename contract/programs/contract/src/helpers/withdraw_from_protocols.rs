@@ -2,17 +2,41 @@ use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::{
+    constants::{BPS_BASE, MIN_OPERATE_AMOUNT},
     errors::AggregatorError, helpers::{
         calculate_total_asset_balance::calculate_total_asset_balance,
         deposit_to_juplend::Juplend,
-        deposit_to_kamino::KaminoVault
-    }, 
+        deposit_to_kamino::KaminoVault,
+        juplend::get_juplend_liquidity::get_juplend_available_liquidity,
+        kamino::{
+            farm_position::get_kamino_withdrawable_usdc,
+            get_kamino_liquidity::get_kamino_available_liquidity,
+            kamino_reserve_accounts::KLEND_PROGRAM_INDEX,
+        },
+        marginfi::{
+            deposit_to_marginfi::Marginfi,
+            get_marginfi_liquidity::get_marginfi_available_liquidity,
+            marginfi_accounts::{check_marginfi_accounts, parse_marginfi_accounts},
+        },
+        klend::{
+            deposit_to_klend::KlendReserve,
+            get_klend_liquidity::get_klend_available_liquidity,
+            klend_reserve_accounts::{check_klend_reserve_accounts, parse_klend_reserve_accounts},
+        },
+        save::{
+            deposit_to_save::SaveReserve,
+            get_save_liquidity::get_save_available_liquidity,
+            save_reserve_accounts::{check_save_reserve_accounts, parse_save_reserve_accounts},
+        },
+        segment_offsets::SegmentOffsets,
+    },
     states::{AggregatorConfig, ReserveWithdrawAccounts}
 };
 
 
 
-pub fn withdraw_from_protocols<'c, 'info>(
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_from_protocols<'info>(
     usdc_to_withdraw: u64,
     signer: &Signer<'info>,
     remaining_accounts: &'info [AccountInfo<'info>],
@@ -23,12 +47,12 @@ pub fn withdraw_from_protocols<'c, 'info>(
     associated_token_program: AccountInfo<'info>,
     system_program: AccountInfo<'info>,
     rent: AccountInfo<'info>,
-) -> Result<()>
+) -> Result<Vec<u64>>
 {
-    // 1. withdraw from JupLend first (in juplend we pass usdc_amount)
-    // 2. If still insufficient, withdraw from Kamino (we pass shares_amount)
+    // 1. read how much each protocol holds for us and how much it can pay out right now
+    // 2. split the withdrawal so every protocol stays within its liquidity and ends close to target
 
-    let usdc_balance_accross_protocols = calculate_total_asset_balance(remaining_accounts)?;
+    let usdc_balance_accross_protocols = calculate_total_asset_balance(&config, remaining_accounts)?;
     let total_usdc_in_protocols_combined: u64 = usdc_balance_accross_protocols
         .iter()
         .try_fold(0u64, |acc, x| acc.checked_add(*x))
@@ -38,10 +62,6 @@ pub fn withdraw_from_protocols<'c, 'info>(
         return Err(AggregatorError::InsufficientBalance.into());
     }
 
-
-    let juplend_balance = usdc_balance_accross_protocols[0];
-    let kamino_balance = usdc_balance_accross_protocols[1];
-
     let juplend_accounts = Juplend::new(
         &config,
         remaining_accounts,
@@ -53,7 +73,7 @@ pub fn withdraw_from_protocols<'c, 'info>(
     )?;
 
     let kamino_accounts = KaminoVault::new(
-        &signer,
+        signer,
         &config,
         remaining_accounts,
         &vault_usdc,
@@ -64,6 +84,10 @@ pub fn withdraw_from_protocols<'c, 'info>(
         &rent,
     )?;
 
+    let offsets = SegmentOffsets::new(kamino_accounts.reserve_accounts.len());
+    let marginfi_accounts = Marginfi::new(&config, remaining_accounts, &offsets, &vault_usdc, &token_program)?;
+    let klend_accounts = KlendReserve::new(&config, remaining_accounts, &offsets, &vault_usdc, &usdc_mint, &token_program)?;
+    let save_accounts = SaveReserve::new(&config, remaining_accounts, &offsets, &vault_usdc, &token_program)?;
 
     let kamino_user_shares_ata_account_info = InterfaceAccount::<TokenAccount>::try_from(&remaining_accounts[31])?;
     let kamino_vault_state_account_info = &remaining_accounts[13];
@@ -73,39 +97,237 @@ pub fn withdraw_from_protocols<'c, 'info>(
 
     let current_slot = Clock::get()?.slot;
 
+    let juplend_liquidity = get_juplend_available_liquidity(&juplend_accounts.supply_token_reserves_liquidity)?;
+    let kamino_liquidity = get_kamino_available_liquidity(
+        kamino_vault_state_account_info,
+        &reserve_accounts,
+        Some(current_slot),
+    )?;
 
-    if juplend_balance >= usdc_to_withdraw {
-        juplend_accounts.withdraw_from_juplend(usdc_to_withdraw, config.bump)?;
+    // Farm shares behind a cooldown can't leave in this transaction, JupLend covers the rest
+    let kamino_withdrawable = get_kamino_withdrawable_usdc(remaining_accounts, &reserve_accounts, current_slot)?;
+    let marginfi_liquidity = if check_marginfi_accounts(&config, &parse_marginfi_accounts(remaining_accounts, &offsets)?)? {
+        get_marginfi_available_liquidity(&marginfi_accounts.liquidity_vault)?
+    } else {
+        0
+    };
+    let klend_liquidity = if check_klend_reserve_accounts(
+        &config,
+        &parse_klend_reserve_accounts(remaining_accounts, &offsets)?,
+        &remaining_accounts[KLEND_PROGRAM_INDEX],
+    )? {
+        get_klend_available_liquidity(&klend_accounts.reserve)?
     } else {
-        if kamino_balance >=usdc_to_withdraw {
-        
-            kamino_accounts.withdraw_from_kamino_by_shares(
-                &kamino_user_shares_ata_account_info,
-                kamino_user_state_account_info,
-                kamino_vault_state_account_info,
-                &reserve_accounts,
-                current_slot,
-                usdc_to_withdraw,
-                config.bump,
-            )?;
+        0
+    };
+    let save_liquidity = if check_save_reserve_accounts(&config, &parse_save_reserve_accounts(remaining_accounts, &offsets)?)? {
+        get_save_available_liquidity(&save_accounts.reserve)?
+    } else {
+        0
+    };
+
+    let withdraw_split = split_withdraw_amount(
+        usdc_to_withdraw,
+        &usdc_balance_accross_protocols,
+        &[
+            juplend_liquidity,
+            kamino_liquidity.available.min(kamino_withdrawable),
+            marginfi_liquidity,
+            klend_liquidity,
+            save_liquidity,
+        ],
+        &config.allocation_bps(),
+    )?;
+
+    msg!(
+        "Withdraw split - Juplend: {}, Kamino: {}, MarginFi: {}, klend reserve: {}, Save reserve: {}",
+        withdraw_split[0],
+        withdraw_split[1],
+        withdraw_split[2],
+        withdraw_split[3],
+        withdraw_split[4]
+    );
+
+    if withdraw_split[0] > 0 {
+        juplend_accounts.withdraw_from_juplend(withdraw_split[0], config.bump)?;
+    }
+
+    if withdraw_split[1] > 0 {
+        kamino_accounts.withdraw_from_kamino_by_shares(
+            &kamino_user_shares_ata_account_info,
+            kamino_user_state_account_info,
+            kamino_vault_state_account_info,
+            &reserve_accounts,
+            current_slot,
+            withdraw_split[1],
+            config.bump,
+        )?;
+    }
+
+    if withdraw_split[2] > 0 {
+        marginfi_accounts.withdraw_from_marginfi(withdraw_split[2], config.bump)?;
+    }
+
+    if withdraw_split[3] > 0 {
+        klend_accounts.redeem_from_klend(withdraw_split[3], config.bump)?;
+    }
+
+    if withdraw_split[4] > 0 {
+        save_accounts.redeem_from_save(withdraw_split[4], config.bump)?;
+    }
+
+    Ok(withdraw_split)
+}
+
+
+/*
+   Splits `usdc_to_withdraw` across protocols.
+
+   - Each protocol can give at most min(balance, available liquidity).
+   - Amounts are first taken from what each protocol holds above its target for the
+     post-withdraw total, proportionally, so allocations move towards target.
+   - Whatever is left (liquidity caps, rounding) is taken from protocols with spare capacity.
+   - Parts smaller than MIN_OPERATE_AMOUNT are folded into another protocol when possible.
+
+   Fails with InsufficientLiquidity only when the combined liquidity can't cover the withdrawal.
+*/
+pub fn split_withdraw_amount(
+    usdc_to_withdraw: u64,
+    balances: &[u64],
+    liquidity: &[u64],
+    allocation_bps: &[u16],
+) -> Result<Vec<u64>> {
+    require!(
+        balances.len() == liquidity.len() && balances.len() == allocation_bps.len(),
+        AggregatorError::InvalidProtocolIndex
+    );
+
+    let caps: Vec<u64> = balances
+        .iter()
+        .zip(liquidity.iter())
+        .map(|(balance, available)| *balance.min(available))
+        .collect();
+
+    let total_cap = caps
+        .iter()
+        .try_fold(0u64, |acc, x| acc.checked_add(*x))
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    if usdc_to_withdraw > total_cap {
+        msg!("Withdraw {} exceeds combined protocol liquidity {}", usdc_to_withdraw, total_cap);
+        return Err(AggregatorError::InsufficientLiquidity.into());
+    }
+
+    let total_balance = balances
+        .iter()
+        .try_fold(0u64, |acc, x| acc.checked_add(*x))
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    let total_after_withdraw = total_balance.saturating_sub(usdc_to_withdraw);
+
+    // What each protocol holds above its target once the withdrawal is done
+    let mut excess = Vec::with_capacity(balances.len());
+    for (balance, bps) in balances.iter().zip(allocation_bps.iter()) {
+        let target = (total_after_withdraw as u128)
+            .checked_mul(*bps as u128)
+            .ok_or(AggregatorError::MathOverflow)?
+            .checked_div(BPS_BASE as u128)
+            .ok_or(AggregatorError::MathOverflow)? as u64;
+        excess.push(balance.saturating_sub(target));
+    }
+
+    let total_excess = excess
+        .iter()
+        .try_fold(0u128, |acc, x| acc.checked_add(*x as u128))
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    let mut split = vec![0u64; balances.len()];
+
+    if total_excess > 0 {
+        let to_distribute = (usdc_to_withdraw as u128).min(total_excess);
+        for i in 0..split.len() {
+            let share = to_distribute
+                .checked_mul(excess[i] as u128)
+                .ok_or(AggregatorError::MathOverflow)?
+                .checked_div(total_excess)
+                .ok_or(AggregatorError::MathOverflow)? as u64;
+            split[i] = share.min(caps[i]);
+        }
+    }
+
+    // Cover the remainder from whichever protocols still have room, largest first
+    fill_remaining(&mut split, &caps, usdc_to_withdraw)?;
+
+    // Avoid CPIs for dust amounts when another protocol can absorb them
+    for i in 0..split.len() {
+        if split[i] == 0 || split[i] >= MIN_OPERATE_AMOUNT {
+            continue;
         }
-        else {
-
-            juplend_accounts.withdraw_from_juplend(juplend_balance, config.bump)?;
-            
-            kamino_accounts.withdraw_from_kamino_by_shares(
-                &kamino_user_shares_ata_account_info,
-                kamino_user_state_account_info,
-                kamino_vault_state_account_info,
-                &reserve_accounts,
-                current_slot,
-                usdc_to_withdraw - juplend_balance,
-                config.bump,
-            )?;
+        let dust = split[i];
+        let receiver = (0..split.len())
+            .filter(|j| *j != i && split[*j] > 0 && caps[*j] - split[*j] >= dust)
+            .max_by_key(|j| caps[*j] - split[*j]);
+        if let Some(j) = receiver {
+            split[j] += dust;
+            split[i] = 0;
         }
     }
 
+    Ok(split)
+}
+
+
+fn fill_remaining(split: &mut [u64], caps: &[u64], usdc_to_withdraw: u64) -> Result<()> {
+    let mut remaining = usdc_to_withdraw
+        .checked_sub(split.iter().sum::<u64>())
+        .ok_or(AggregatorError::MathOverflow)?;
+
+    while remaining > 0 {
+        let next = (0..split.len())
+            .filter(|i| caps[*i] > split[*i])
+            .max_by_key(|i| caps[*i] - split[*i]);
+
+        let Some(i) = next else {
+            return Err(AggregatorError::InsufficientLiquidity.into());
+        };
+
+        let take = remaining.min(caps[i] - split[i]);
+        split[i] += take;
+        remaining -= take;
+    }
+
     Ok(())
 }
 
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_moves_allocation_towards_target() {
+        // 50/50 target, Juplend is over-allocated by 200
+        let split = split_withdraw_amount(100_000, &[600_000, 400_000], &[u64::MAX, u64::MAX], &[5000, 5000]).unwrap();
+        assert_eq!(split, vec![100_000, 0]);
+    }
+
+    #[test]
+    fn test_split_respects_liquidity() {
+        // Juplend holds everything above target but can only pay out 30_000
+        let split = split_withdraw_amount(100_000, &[600_000, 400_000], &[30_000, u64::MAX], &[5000, 5000]).unwrap();
+        assert_eq!(split, vec![30_000, 70_000]);
+    }
+
+    #[test]
+    fn test_split_fails_when_combined_liquidity_is_short() {
+        let result = split_withdraw_amount(100_000, &[600_000, 400_000], &[30_000, 50_000], &[5000, 5000]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_split_folds_dust() {
+        // Proportional split would leave 250 for Kamino, which is folded into Juplend
+        let split = split_withdraw_amount(100_000, &[550_000, 450_500], &[u64::MAX, u64::MAX], &[5000, 5000]).unwrap();
+        assert_eq!(split, vec![100_000, 0]);
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use bytemuck::Pod;
+
+use crate::errors::AggregatorError;
+
+
+/*
+   Borrowed, typed view over an account another program owns:
+
+   data = [8-byte discriminator] + T as laid out on chain
+
+   Nothing is copied, which matters for the 62KB Kamino VaultState. A wrong discriminator,
+   a buffer shorter than T or data that isn't aligned for T is an InvalidAccountData error.
+*/
+pub fn zero_copy_view<'a, T: Pod>(data: &'a [u8], discriminator: &[u8; 8]) -> Result<&'a T> {
+    let size = std::mem::size_of::<T>();
+
+    if data.len() < 8 + size || data[0..8] != *discriminator {
+        msg!("Expected a {} account of {} bytes, got {}", std::any::type_name::<T>(), 8 + size, data.len());
+        return Err(AggregatorError::InvalidAccountData.into());
+    }
+
+    bytemuck::try_from_bytes(&data[8..8 + size]).map_err(|_| AggregatorError::InvalidAccountData.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_checks_discriminator_and_length() {
+        let mut data = vec![1u8; 8];
+        data.extend_from_slice(&42u64.to_le_bytes());
+
+        assert_eq!(*zero_copy_view::<u64>(&data, &[1; 8]).unwrap(), 42);
+        assert!(zero_copy_view::<u64>(&data, &[2; 8]).is_err());
+        assert!(zero_copy_view::<u64>(&data[..15], &[1; 8]).is_err());
+    }
+}
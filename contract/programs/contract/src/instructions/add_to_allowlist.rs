@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::AllowlistUpdateEvent,
+    states::{AggregatorConfig, Allowlist},
+};
+
+
+
+#[derive(Accounts)]
+pub struct AddToAllowlist<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Wallet being allowed, only its key is used
+    pub wallet: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Allowlist::INIT_SPACE,
+        seeds = [b"allowlist", wallet.key().as_ref()],
+        bump
+    )]
+    pub allowlist: Account<'info, Allowlist>,
+
+    pub system_program: Program<'info, System>,
+}
+
+
+impl<'info> AddToAllowlist<'info> {
+    pub fn add_to_allowlist(&mut self, bumps: AddToAllowlistBumps) -> Result<()> {
+        self.allowlist.wallet = self.wallet.key();
+        self.allowlist.bump = bumps.allowlist;
+
+        emit!(AllowlistUpdateEvent {
+            wallet: self.wallet.key(),
+            allowed: true,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token_interface::{approve, revoke, Approve, Mint, Revoke, TokenAccount, TokenInterface};
+
+use crate::{
+    events::OperatorApprovalEvent,
+    states::{AggregatorConfig, OperatorApproval},
+};
+
+
+#[derive(Accounts)]
+pub struct ApproveOperator<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Any program or wallet the owner wants to redeem on their behalf
+    pub operator: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.cusdc_mint == cusdc_mint.key(),
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + OperatorApproval::INIT_SPACE,
+        seeds = [b"operator", owner.key().as_ref(), operator.key().as_ref()],
+        bump
+    )]
+    pub operator_approval: Account<'info, OperatorApproval>,
+
+    #[account(
+        mut,
+        constraint = owner_cusdc.mint == cusdc_mint.key(),
+        constraint = owner_cusdc.owner == owner.key(),
+        token::token_program = share_token_program
+    )]
+    pub owner_cusdc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"cusdc-mint"],
+        bump,
+        mint::token_program = share_token_program
+    )]
+    pub cusdc_mint: InterfaceAccount<'info, Mint>,
+
+    pub share_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+
+impl<'info> ApproveOperator<'info> {
+    /*
+       Sets the operator's allowance (0 revokes it).
+
+       The operator's own approval PDA becomes the SPL delegate of the owner's cUSDC account
+       for exactly `allowance`, so `withdraw` burns through it and nothing else can. A token
+       account has a single delegate, so approving another operator, or any other `approve`
+       by the owner, moves the delegation away and this operator can't burn until it is
+       approved again.
+    */
+    pub fn approve_operator(&mut self, allowance: u64, bumps: ApproveOperatorBumps) -> Result<()> {
+        let approval = &mut self.operator_approval;
+
+        approval.owner = self.owner.key();
+        approval.operator = self.operator.key();
+        approval.allowance = allowance;
+        approval.bump = bumps.operator_approval;
+
+        if allowance > 0 {
+            approve(
+                CpiContext::new(
+                    self.share_token_program.to_account_info(),
+                    Approve {
+                        to: self.owner_cusdc.to_account_info(),
+                        delegate: self.operator_approval.to_account_info(),
+                        authority: self.owner.to_account_info(),
+                    },
+                ),
+                allowance,
+            )?;
+        } else if self.owner_cusdc.delegate == COption::Some(self.operator_approval.key()) {
+            revoke(CpiContext::new(
+                self.share_token_program.to_account_info(),
+                Revoke {
+                    source: self.owner_cusdc.to_account_info(),
+                    authority: self.owner.to_account_info(),
+                },
+            ))?;
+        }
+
+        emit!(OperatorApprovalEvent {
+            owner: self.owner.key(),
+            operator: self.operator.key(),
+            allowance,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    errors::AggregatorError,
+    events::FarmUnstakeEvent,
+    helpers::kamino::{deposit_to_kamino::KaminoVault, farm_position::get_kamino_farm_position},
+    states::aggregator_config::AggregatorConfig,
+};
+
+
+
+#[derive(Accounts)]
+pub struct CrankKaminoFarm<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    /// Anyone can crank, the amounts only depend on the farm state and the config buffer
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = config.usdc_mint,
+        associated_token::authority = config,
+    )]
+    pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = usdc_mint.key() == config.usdc_mint
+    )]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+
+impl<'info> CrankKaminoFarm<'info> {
+    /*
+       Second half of the two-phase farm exit, for farms with a withdrawal cooldown.
+
+       1. If an earlier unstake is out of its cooldown, withdraw it into the config's shares ATA.
+       2. If nothing is unstaking, unstake enough active shares to bring unstaked plus
+          unstaking shares up to `farm_liquid_buffer_bps` of the position.
+       3. Otherwise, if unstaked plus unstaking shares are above that target (small deposits,
+          a lowered buffer), stake the excess back into the farm.
+
+       remaining_accounts are the protocol accounts, same as deposit.
+    */
+    pub fn crank_kamino_farm(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let kamino_accounts = KaminoVault::new(
+            &self.keeper,
+            &self.config,
+            remaining_accounts,
+            &self.vault_usdc,
+            &self.usdc_mint,
+            &self.token_program,
+            &self.associated_token_program.to_account_info(),
+            &self.system_program.to_account_info(),
+            &self.rent.to_account_info(),
+        )?;
+        require!(kamino_accounts.has_farm(), AggregatorError::NothingToUnstake);
+
+        // Config shares ATA, same index `withdraw_from_protocols` reads
+        let shares_ata = InterfaceAccount::<'info, TokenAccount>::try_from(
+            remaining_accounts.get(31).ok_or(AggregatorError::MissingAccount)?,
+        )?;
+        let (mut position, timing) = get_kamino_farm_position(
+            &shares_ata,
+            &kamino_accounts.config_state,
+            &kamino_accounts.farm_state,
+        )?;
+
+        let mut shares_withdrawn = 0;
+        if position.pending_withdrawal > 0 {
+            require!(position.pending_withdrawal_ready, AggregatorError::FarmCooldownActive);
+
+            kamino_accounts.withdraw_unstaked_from_farm(self.config.bump)?;
+            shares_withdrawn = position.pending_withdrawal;
+            position.unstaked = position.unstaked.saturating_add(shares_withdrawn);
+            position.pending_withdrawal = 0;
+        }
+
+        let shares_unstaked = position.shares_to_unstake(self.config.farm_liquid_buffer_bps)?;
+        if shares_unstaked > 0 {
+            kamino_accounts.unstake_from_farm(shares_unstaked, self.config.bump)?;
+
+            // No cooldown, the shares can come back right away
+            if timing.withdrawal_cooldown == 0 {
+                kamino_accounts.withdraw_unstaked_from_farm(self.config.bump)?;
+            }
+        }
+
+        let mut shares_restaked = 0;
+        if shares_unstaked == 0 && position.pending_withdrawal == 0 {
+            shares_restaked = position.shares_to_restake(self.config.farm_liquid_buffer_bps)?;
+            if shares_restaked > 0 {
+                kamino_accounts.stake_in_farm_by_config(shares_restaked, self.config.bump)?;
+            }
+        }
+
+        require!(
+            shares_withdrawn > 0 || shares_unstaked > 0 || shares_restaked > 0,
+            AggregatorError::NothingToUnstake
+        );
+
+        msg!(
+            "Farm crank - withdrawn: {}, unstaked: {}, restaked: {}",
+            shares_withdrawn,
+            shares_unstaked,
+            shares_restaked
+        );
+
+        emit!(FarmUnstakeEvent {
+            shares_withdrawn,
+            shares_unstaked,
+            shares_restaked,
+            withdrawal_cooldown: timing.withdrawal_cooldown,
+        });
+        Ok(())
+    }
+}
@@ -1,12 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{ 
     associated_token::AssociatedToken, 
-    token_2022::{MintTo, TransferChecked, mint_to, transfer_checked
-    }, 
     token_interface::{
         Mint, 
+        MintTo,
         TokenAccount, 
-        TokenInterface
+        TokenInterface,
+        TransferChecked,
+        mint_to,
+        transfer_checked
     }
 };
 
@@ -15,10 +17,13 @@ use crate::{
     events::DepositEvent, 
     helpers::{
         calculate_shares_to_mint::calculate_shares_to_mint, 
-        calculate_total_asset_balance::calculate_total_asset_balance, 
-        rebalance_allocation::rebalance_allocation
+        protocol_apy::refresh_allocation_targets,
+        rebalance_allocation::{calculate_allocation_drift_bps, is_drift_above_threshold, rebalance_allocation},
+        share_conversion::{get_total_assets, preview_mint_assets},
+        access_control::check_access,
+        holding_period::lock_minted_shares,
     }, 
-    states::aggregator_config::AggregatorConfig
+    states::{aggregator_config::AggregatorConfig, Allowlist, UserPosition}
 };
 
 
@@ -42,13 +47,40 @@ pub struct Deposit<'info> {
     )]
     pub user_usdc: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// CHECK: Receives the minted cUSDC, can be the user or anyone else
+    pub receiver: UncheckedAccount<'info>,
+
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = cusdc_mint,
-        associated_token::authority = user
+        associated_token::authority = receiver,
+        associated_token::token_program = share_token_program
+    )]
+    pub receiver_cusdc: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", receiver.key().as_ref()],
+        bump
+    )]
+    pub receiver_position: Box<Account<'info, UserPosition>>,
+
+    /// Required for the user in AccessMode::Allowlist
+    #[account(
+        seeds = [b"allowlist", user.key().as_ref()],
+        bump = user_allowlist.bump
+    )]
+    pub user_allowlist: Option<Box<Account<'info, Allowlist>>>,
+
+    /// Required for the receiver in AccessMode::Allowlist, unless it is the user
+    #[account(
+        seeds = [b"allowlist", receiver.key().as_ref()],
+        bump = receiver_allowlist.bump
     )]
-    pub user_cusdc: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub receiver_allowlist: Option<Box<Account<'info, Allowlist>>>,
 
     #[account(
         mut,
@@ -60,7 +92,8 @@ pub struct Deposit<'info> {
     #[account(
         mut,
         seeds = [b"cusdc-mint"],
-        bump
+        bump,
+        mint::token_program = share_token_program
     )]
     pub cusdc_mint: Box<InterfaceAccount<'info, Mint>>,
     
@@ -70,6 +103,8 @@ pub struct Deposit<'info> {
     pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
 
     pub token_program: Interface<'info, TokenInterface>,
+    /// Token-2022, or SPL Token for live vaults whose cUSDC predates the Token-2022 mint
+    pub share_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -77,12 +112,89 @@ pub struct Deposit<'info> {
 }
 
 impl<'info> Deposit<'info> {
-    pub fn deposit(&mut self, amount: u64, _bumps: DepositBumps, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+    pub fn deposit(
+        &mut self,
+        amount: u64,
+        user_proof: &[[u8; 32]],
+        receiver_proof: &[[u8; 32]],
+        bumps: DepositBumps,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
 
         require!(amount > 0, AggregatorError::InvalidAmount);
+
+        self.check_access(user_proof, receiver_proof)?;
+        self.receiver_position.bump = bumps.receiver_position;
         
         msg!("Received {} remaining accounts", remaining_accounts.len());
-        
+
+        // Idle USDC left in the vault between rebalances is part of total assets,
+        // the deposit itself is not
+        let (usdc_in_all_protocol, total_usdc_in_protocols_combined) =
+            get_total_assets(&self.config, remaining_accounts, self.vault_usdc.amount)?;
+
+        let cusdc_to_mint  = calculate_shares_to_mint(
+            amount,
+            self.cusdc_mint.supply,
+            total_usdc_in_protocols_combined
+        )?;
+
+        self.settle_deposit(amount, cusdc_to_mint, usdc_in_all_protocol, remaining_accounts)
+    }
+
+    /// Mints exactly `shares`, pulling the USDC they are worth (rounded up)
+    pub fn mint_shares(
+        &mut self,
+        shares: u64,
+        max_assets_in: u64,
+        user_proof: &[[u8; 32]],
+        receiver_proof: &[[u8; 32]],
+        bumps: DepositBumps,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+
+        require!(shares > 0, AggregatorError::InvalidAmount);
+
+        self.check_access(user_proof, receiver_proof)?;
+        self.receiver_position.bump = bumps.receiver_position;
+
+        let (usdc_in_all_protocol, total_usdc_in_protocols_combined) =
+            get_total_assets(&self.config, remaining_accounts, self.vault_usdc.amount)?;
+
+        let amount = preview_mint_assets(
+            shares,
+            self.cusdc_mint.supply,
+            total_usdc_in_protocols_combined
+        )?;
+
+        require!(amount > 0, AggregatorError::InvalidAmount);
+        if amount > max_assets_in {
+            msg!("Minting {} cUSDC needs {} USDC, above max {}", shares, amount, max_assets_in);
+            return Err(AggregatorError::SlippageExceeded.into());
+        }
+
+        self.settle_deposit(amount, shares, usdc_in_all_protocol, remaining_accounts)
+    }
+
+    /// Both the depositor and the cUSDC receiver have to pass the vault's access mode
+    fn check_access(&self, user_proof: &[[u8; 32]], receiver_proof: &[[u8; 32]]) -> Result<()> {
+        check_access(&self.config, &self.user.key(), self.user_allowlist.as_deref().map(|entry| &**entry), user_proof)?;
+
+        if self.receiver.key() != self.user.key() {
+            check_access(&self.config, &self.receiver.key(), self.receiver_allowlist.as_deref().map(|entry| &**entry), receiver_proof)?;
+        }
+
+        Ok(())
+    }
+
+    fn settle_deposit(
+        &mut self,
+        amount: u64,
+        cusdc_to_mint: u64,
+        usdc_in_all_protocol: Vec<u64>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+
         msg!("Transferring USDC to vault");
         transfer_checked(
             CpiContext::new(
@@ -99,22 +211,14 @@ impl<'info> Deposit<'info> {
         )?;
 
         msg!("Transferred USDC to vault");
+
+        // The minted shares are locked on the receiver, shares it already held stay free
+        let position = &mut self.receiver_position;
+        position.owner = self.receiver.key();
+        lock_minted_shares(position, cusdc_to_mint, self.config.min_holding_slots, Clock::get()?.slot)?;
         
         // Reload vault_usdc account to get updated balance after transfer
         self.vault_usdc.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
-        
-        // Get total USDC in all protocols combined
-        let usdc_in_all_protocol = calculate_total_asset_balance(remaining_accounts)?;
-        let total_usdc_in_protocols_combined: u64 = usdc_in_all_protocol
-            .iter()
-            .try_fold(0u64, |acc, x| acc.checked_add(*x))
-            .ok_or(AggregatorError::MathOverflow)?;
-
-        let cusdc_to_mint  = calculate_shares_to_mint(
-            amount,
-            self.cusdc_mint.supply,
-            total_usdc_in_protocols_combined
-        );
 
         let seeds = &[b"config".as_ref(), &[self.config.bump]];
         let signer = &[&seeds[..]];
@@ -123,10 +227,10 @@ impl<'info> Deposit<'info> {
 
         mint_to(
             CpiContext::new_with_signer(
-               self.token_program.to_account_info(),
+               self.share_token_program.to_account_info(),
                 MintTo {
                     mint: self.cusdc_mint.to_account_info(),
-                    to: self.user_cusdc.to_account_info(),
+                    to: self.receiver_cusdc.to_account_info(),
                     authority: self.config.to_account_info(),
                 },
                 signer,
@@ -140,24 +244,44 @@ impl<'info> Deposit<'info> {
             .checked_add(amount)
             .ok_or(AggregatorError::MathOverflow)?;
 
-        msg!("Rebalancing allocation");
-        // Rebalance to all protocols 
-        rebalance_allocation(
-            &self.user,
+        let protocol_apys = refresh_allocation_targets(
+            &mut self.config,
             remaining_accounts,
-            usdc_in_all_protocol,
-            &self.config,
-            &mut self.vault_usdc,
-            &self.usdc_mint,
-            &self.token_program,
-            &self.associated_token_program,
-            &self.system_program,
-            &self.rent.to_account_info()
+            &usdc_in_all_protocol,
+            self.vault_usdc.amount,
         )?;
-        msg!("Rebalanced allocation");
+
+        let drift_bps = calculate_allocation_drift_bps(
+            &usdc_in_all_protocol,
+            self.vault_usdc.amount,
+            &self.config.allocation_bps(),
+        )?;
+
+        if is_drift_above_threshold(&self.config, drift_bps) {
+            msg!("Rebalancing allocation");
+            // Rebalance to all protocols 
+            rebalance_allocation(
+                &self.user,
+                remaining_accounts,
+                usdc_in_all_protocol,
+                &self.config,
+                &mut self.vault_usdc,
+                &self.usdc_mint,
+                &self.token_program,
+                &self.associated_token_program,
+                &self.system_program,
+                &self.rent.to_account_info(),
+                protocol_apys,
+            )?;
+            self.config.last_rebalance_slot = Clock::get()?.slot;
+            msg!("Rebalanced allocation");
+        } else {
+            msg!("Allocation drift {} bps within threshold, skipping rebalance", drift_bps);
+        }
 
         emit!(DepositEvent {
             user: self.user.key(),
+            receiver: self.receiver.key(),
             amount,
             cusdc_minted: cusdc_to_mint,
         });
@@ -0,0 +1,212 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken, 
+    token_interface::{Mint, TokenAccount, TokenInterface}
+};
+
+use crate::{
+    errors::AggregatorError, 
+    events::HarvestEvent, 
+    helpers::{
+        calculate_total_asset_balance::calculate_total_asset_balance, 
+        kamino::{deposit_to_kamino::KaminoVault, harvest_from_kamino::FarmRewardAccounts}, 
+        protocol_apy::refresh_allocation_targets, 
+        rebalance_allocation::rebalance_allocation, 
+        swap_rewards::swap_rewards
+    }, 
+    states::aggregator_config::AggregatorConfig
+};
+
+
+
+#[derive(Accounts)]
+pub struct Harvest<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Box<Account<'info, AggregatorConfig>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = config.usdc_mint,
+        associated_token::authority = config,
+    )]
+    pub vault_usdc: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = usdc_mint.key() == config.usdc_mint
+    )]
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = reward_token_program,
+    )]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Config owned account the farm pays the reward into
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = reward_mint,
+        associated_token::authority = config,
+        associated_token::token_program = reward_token_program,
+    )]
+    pub reward_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Kamino farms global config, validated by the farm program
+    pub farm_global_config: UncheckedAccount<'info>,
+
+    /// CHECK: Farm reward vault, validated by the farm program
+    #[account(mut)]
+    pub rewards_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Farm reward treasury vault, validated by the farm program
+    #[account(mut)]
+    pub rewards_treasury_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Only the swap program set by the authority, not needed when the reward is USDC
+    #[account(
+        executable,
+        address = config.swap_program
+    )]
+    pub swap_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Signs the swap as delegate of `reward_token_account`, owns nothing itself
+    #[account(
+        seeds = [b"swap-authority"],
+        bump
+    )]
+    pub swap_authority: UncheckedAccount<'info>,
+
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+
+impl<'info> Harvest<'info> {
+    /*
+       remaining_accounts = [protocol accounts (same as deposit)] + [swap accounts]
+       where the last `swap_accounts_len` accounts are passed to the swap program as is.
+    */
+    pub fn harvest(
+        &mut self,
+        reward_index: u64,
+        swap_data: Vec<u8>,
+        swap_accounts_len: u8,
+        min_usdc_out: u64,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        swap_authority_bump: u8,
+    ) -> Result<()> {
+        let swap_accounts_len = swap_accounts_len as usize;
+        require!(
+            swap_accounts_len <= remaining_accounts.len(),
+            AggregatorError::InvalidSwapAccounts
+        );
+        let (protocol_accounts, swap_accounts) = remaining_accounts.split_at(remaining_accounts.len() - swap_accounts_len);
+
+        let kamino_accounts = KaminoVault::new(
+            &self.authority,
+            &self.config,
+            protocol_accounts,
+            &self.vault_usdc,
+            &self.usdc_mint,
+            &self.token_program,
+            &self.associated_token_program.to_account_info(),
+            &self.system_program.to_account_info(),
+            &self.rent.to_account_info(),
+        )?;
+
+        // 1. Claim the farm reward into the config owned reward account
+        let reward_before = self.reward_token_account.amount;
+
+        kamino_accounts.harvest_farm_reward(
+            &FarmRewardAccounts {
+                farm_global_config: self.farm_global_config.to_account_info(),
+                reward_mint: self.reward_mint.to_account_info(),
+                reward_token_account: self.reward_token_account.to_account_info(),
+                rewards_vault: self.rewards_vault.to_account_info(),
+                rewards_treasury_vault: self.rewards_treasury_vault.to_account_info(),
+                reward_token_program: self.reward_token_program.to_account_info(),
+            },
+            reward_index,
+            self.config.bump,
+        )?;
+
+        self.reward_token_account.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
+        let reward_harvested = self.reward_token_account.amount.saturating_sub(reward_before);
+        msg!("Harvested {} of reward {}", reward_harvested, self.reward_mint.key());
+
+        // 2. Swap everything in the reward account (including earlier leftovers) into USDC
+        let vault_usdc_before = self.vault_usdc.amount;
+        let reward_to_swap = self.reward_token_account.amount;
+        let mut reward_swapped = 0;
+
+        if self.reward_mint.key() != self.config.usdc_mint && reward_to_swap > 0 {
+            let swap_program = self.swap_program.as_ref().ok_or(AggregatorError::MissingAccount)?;
+
+            swap_rewards(
+                &swap_program.to_account_info(),
+                swap_accounts,
+                swap_data,
+                reward_to_swap,
+                &self.reward_token_account.to_account_info(),
+                &self.reward_token_program.to_account_info(),
+                &self.swap_authority.to_account_info(),
+                swap_authority_bump,
+                &self.config.to_account_info(),
+                self.config.bump,
+            )?;
+
+            self.reward_token_account.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
+            reward_swapped = reward_to_swap.saturating_sub(self.reward_token_account.amount);
+        }
+
+        self.vault_usdc.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
+        let usdc_received = self.vault_usdc.amount.saturating_sub(vault_usdc_before);
+
+        require!(usdc_received >= min_usdc_out, AggregatorError::SlippageExceeded);
+
+        // 3. Put the proceeds to work, they are idle USDC in the vault now
+        let usdc_in_all_protocol = calculate_total_asset_balance(&self.config, protocol_accounts)?;
+
+        let protocol_apys = refresh_allocation_targets(
+            &mut self.config,
+            protocol_accounts,
+            &usdc_in_all_protocol,
+            self.vault_usdc.amount,
+        )?;
+
+        rebalance_allocation(
+            &self.authority,
+            protocol_accounts,
+            usdc_in_all_protocol,
+            &self.config,
+            &mut self.vault_usdc,
+            &self.usdc_mint,
+            &self.token_program,
+            &self.associated_token_program,
+            &self.system_program,
+            &self.rent.to_account_info(),
+            protocol_apys,
+        )?;
+        self.config.last_rebalance_slot = Clock::get()?.slot;
+
+        emit!(HarvestEvent {
+            reward_mint: self.reward_mint.key(),
+            reward_harvested,
+            reward_swapped,
+            usdc_received,
+        });
+
+        Ok(())
+    }
+}
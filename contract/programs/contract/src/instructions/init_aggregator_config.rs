@@ -1,7 +1,16 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{ associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::Token2022,
+    token_interface::{token_metadata_initialize, Mint, TokenAccount, TokenInterface, TokenMetadataInitialize},
+};
 
-use crate::{constants::BPS_BASE, errors::AggregatorError, states::aggregator_config::AggregatorConfig};
+use crate::{
+    constants::{BPS_BASE, CONFIG_VERSION, DEFAULT_FARM_LIQUID_BUFFER_BPS},
+    errors::AggregatorError,
+    helpers::{share_metadata::validate_share_metadata, top_up_rent::top_up_rent},
+    states::aggregator_config::{AccessMode, AggregatorConfig, AllocationBounds, AllocationMode, KaminoInterestSnapshot},
+};
 
 
 
@@ -22,15 +31,19 @@ pub struct InitAggregatorConfig<'info> {
 
     pub usdc_mint: InterfaceAccount<'info, Mint>,
 
+    /// Token-2022 share mint carrying its own name, symbol and URI
     #[account(
         init,
         payer = authority,
         mint::decimals = 6,
         mint::authority = config,
+        mint::token_program = share_token_program,
+        extensions::metadata_pointer::authority = config,
+        extensions::metadata_pointer::metadata_address = cusdc_mint,
         seeds = [b"cusdc-mint"],
         bump
     )]
-    pub cusdc_mint: InterfaceAccount<'info, Mint>,
+    pub cusdc_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         init,
@@ -40,16 +53,35 @@ pub struct InitAggregatorConfig<'info> {
     )]
     pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        init,
+        payer = authority,
+        token::mint = usdc_mint,
+        token::authority = config,
+        seeds = [b"fee-vault"],
+        bump
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
     pub token_program: Interface<'info, TokenInterface>,
+    pub share_token_program: Program<'info, Token2022>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 
 impl<'info> InitAggregatorConfig<'info> {
-    pub fn init_aggregator_config(&mut self, juplend_allocation_bps: u16, bumps: InitAggregatorConfigBumps) -> Result<()> {
+    pub fn init_aggregator_config(
+        &mut self,
+        juplend_allocation_bps: u16,
+        share_name: String,
+        share_symbol: String,
+        share_uri: String,
+        bumps: InitAggregatorConfigBumps,
+    ) -> Result<()> {
         
         require!(juplend_allocation_bps <= BPS_BASE, AggregatorError::InvalidAllocation);
+        validate_share_metadata(&share_name, &share_symbol, &share_uri)?;
 
         let config = &mut self.config;
         config.authority = self.authority.key();
@@ -60,7 +92,59 @@ impl<'info> InitAggregatorConfig<'info> {
         config.kamino_allocation_bps = BPS_BASE - juplend_allocation_bps;
         config.bump = bumps.config;
         config.total_deposits = 0;
+        config.drift_threshold_bps = 0;
+        config.rebalance_cooldown_slots = 0;
+        config.last_rebalance_slot = 0;
+        config.keeper_tip = 0;
+        config.allocation_mode = AllocationMode::Fixed;
+        config.juplend_bounds = AllocationBounds { min_bps: 0, max_bps: BPS_BASE };
+        config.kamino_bounds = AllocationBounds { min_bps: 0, max_bps: BPS_BASE };
+        config.swap_program = Pubkey::default();
+        config.access_mode = AccessMode::Permissionless;
+        config.merkle_root = [0; 32];
+        config.min_holding_slots = 0;
+        config.version = CONFIG_VERSION;
+        config.farm_liquid_buffer_bps = DEFAULT_FARM_LIQUID_BUFFER_BPS;
+        config.kamino_interest_snapshot = KaminoInterestSnapshot::default();
+        config.marginfi_account = Pubkey::default();
+        config.marginfi_allocation_bps = 0;
+        config.klend_reserve = Pubkey::default();
+        config.klend_allocation_bps = 0;
+        config.save_reserve = Pubkey::default();
+        config.save_allocation_bps = 0;
+        config.marginfi_bank = Pubkey::default();
+        config.reserved = [0; 32];
+
+        self.initialize_share_metadata(share_name, share_symbol, share_uri)?;
 
         Ok(())
     }
+
+    fn initialize_share_metadata(&self, name: String, symbol: String, uri: String) -> Result<()> {
+        let seeds = &[b"config".as_ref(), &[self.config.bump]];
+        let signer = &[&seeds[..]];
+
+        token_metadata_initialize(
+            CpiContext::new_with_signer(
+                self.share_token_program.to_account_info(),
+                TokenMetadataInitialize {
+                    program_id: self.share_token_program.to_account_info(),
+                    metadata: self.cusdc_mint.to_account_info(),
+                    update_authority: self.config.to_account_info(),
+                    mint_authority: self.config.to_account_info(),
+                    mint: self.cusdc_mint.to_account_info(),
+                },
+                signer,
+            ),
+            name,
+            symbol,
+            uri,
+        )?;
+
+        top_up_rent(
+            &self.cusdc_mint.to_account_info(),
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
 }
\ No newline at end of file
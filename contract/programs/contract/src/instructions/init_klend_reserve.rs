@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    errors::AggregatorError,
+    events::KlendReserveInitEvent,
+    helpers::kamino::kamino_account_reader::load_reserve,
+    states::aggregator_config::AggregatorConfig,
+};
+
+
+
+#[derive(Accounts)]
+pub struct InitKlendReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    /// Pays for the cToken account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: klend reserve lending USDC, its mints are checked against the reserve data
+    #[account(constraint = *reserve.owner == klend_program.key())]
+    pub reserve: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = collateral_token_program)]
+    pub reserve_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Holds the reserve's cTokens for the config PDA
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = reserve_collateral_mint,
+        associated_token::authority = config,
+        associated_token::token_program = collateral_token_program,
+    )]
+    pub ctoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Owner of the reserve, must be an executable program
+    #[account(executable)]
+    pub klend_program: UncheckedAccount<'info>,
+
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+
+impl<'info> InitKlendReserve<'info> {
+    pub fn init_klend_reserve(&mut self) -> Result<()> {
+        require_keys_eq!(
+            self.config.klend_reserve,
+            Pubkey::default(),
+            AggregatorError::KlendReserveAlreadyInitialized
+        );
+
+        {
+            let reserve_data = self.reserve.try_borrow_data()?;
+            let reserve = load_reserve(&reserve_data)?;
+
+            require_keys_eq!(reserve.liquidity.mint_pubkey, self.config.usdc_mint, AggregatorError::InvalidKlendReserve);
+            require_keys_eq!(
+                reserve.collateral.mint_pubkey,
+                self.reserve_collateral_mint.key(),
+                AggregatorError::InvalidKlendReserve
+            );
+        }
+
+        self.config.klend_reserve = self.reserve.key();
+
+        emit!(KlendReserveInitEvent {
+            reserve: self.reserve.key(),
+            ctoken_account: self.ctoken_account.key(),
+        });
+        Ok(())
+    }
+}
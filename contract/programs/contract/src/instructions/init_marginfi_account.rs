@@ -0,0 +1,140 @@
+use anchor_lang::prelude::{instruction::Instruction, program::invoke_signed, *};
+
+use crate::{
+    errors::AggregatorError,
+    events::MarginfiAccountInitEvent,
+    helpers::marginfi::marginfi_account_reader::load_bank,
+    states::aggregator_config::AggregatorConfig,
+};
+
+
+fn get_marginfi_account_initialize_discriminator() -> Vec<u8> {
+    vec![43, 78, 61, 255, 148, 52, 249, 154]
+}
+
+
+
+#[derive(Accounts)]
+pub struct InitMarginfiAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    /// Pays for the marginfi account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: marginfi group the USDC bank belongs to, validated by the marginfi program
+    #[account(constraint = *marginfi_group.owner == marginfi_program.key())]
+    pub marginfi_group: UncheckedAccount<'info>,
+
+    /// CHECK: USDC bank of the group the account will lend to, checked against the bank data
+    #[account(constraint = *bank.owner == marginfi_program.key())]
+    pub bank: UncheckedAccount<'info>,
+
+    /// CHECK: Fresh keypair signing the transaction, created and owned by the marginfi program with
+    /// the config PDA as authority. Or the config's existing account when only the bank is pinned
+    #[account(mut)]
+    pub marginfi_account: UncheckedAccount<'info>,
+
+    /// CHECK: Owner of the group, must be an executable program
+    #[account(executable)]
+    pub marginfi_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+
+impl<'info> InitMarginfiAccount<'info> {
+    /*
+       Creates the config's marginfi account and pins the USDC bank it lends to. A config
+       migrated to v7 that already owns a marginfi account only gets its bank pinned.
+    */
+    pub fn init_marginfi_account(&mut self) -> Result<()> {
+        require_keys_eq!(
+            self.config.marginfi_bank,
+            Pubkey::default(),
+            AggregatorError::MarginfiAccountAlreadyInitialized
+        );
+
+        {
+            let bank_data = self.bank.try_borrow_data()?;
+            let bank = load_bank(&bank_data)?;
+
+            require_keys_eq!(bank.mint, self.config.usdc_mint, AggregatorError::InvalidMarginfiBank);
+            require_keys_eq!(bank.group, self.marginfi_group.key(), AggregatorError::InvalidMarginfiBank);
+        }
+
+        if self.config.marginfi_account == Pubkey::default() {
+            self.create_marginfi_account()?;
+            self.config.marginfi_account = self.marginfi_account.key();
+        } else {
+            require_keys_eq!(
+                self.marginfi_account.key(),
+                self.config.marginfi_account,
+                AggregatorError::InvalidMarginfiAccount
+            );
+            require_keys_eq!(
+                *self.marginfi_account.owner,
+                self.marginfi_program.key(),
+                AggregatorError::InvalidMarginfiAccount
+            );
+        }
+
+        self.config.marginfi_bank = self.bank.key();
+
+        emit!(MarginfiAccountInitEvent {
+            marginfi_account: self.marginfi_account.key(),
+            bank: self.bank.key(),
+        });
+        Ok(())
+    }
+
+    fn create_marginfi_account(&self) -> Result<()> {
+        require!(self.marginfi_account.is_signer, ErrorCode::AccountNotSigner);
+
+        let account_metas = vec![
+            // marginfi_group (readonly)
+            AccountMeta::new_readonly(self.marginfi_group.key(), false),
+            // marginfi_account (mutable, signer)
+            AccountMeta::new(self.marginfi_account.key(), true),
+            // authority (signer)
+            AccountMeta::new_readonly(self.config.key(), true),
+            // fee_payer (mutable, signer)
+            AccountMeta::new(self.authority.key(), true),
+            // system_program
+            AccountMeta::new_readonly(self.system_program.key(), false),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.marginfi_program.key(),
+            accounts: account_metas,
+            data: get_marginfi_account_initialize_discriminator(),
+        };
+
+        let seeds = &[b"config".as_ref(), &[self.config.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        invoke_signed(
+            &instruction,
+            &[
+                self.marginfi_group.to_account_info(),
+                self.marginfi_account.to_account_info(),
+                self.config.to_account_info(),
+                self.authority.to_account_info(),
+                self.system_program.to_account_info(),
+            ],
+            signer_seeds,
+        )
+        .map_err(|e| {
+            msg!("MarginFi account initialize CPI failed with error: {:?}", e);
+            AggregatorError::CpiToLendingProgramFailed
+        })?;
+
+        Ok(())
+    }
+}
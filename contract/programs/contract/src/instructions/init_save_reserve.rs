@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    errors::AggregatorError,
+    events::SaveReserveInitEvent,
+    helpers::save::save_reserve_reader::read_save_reserve,
+    states::aggregator_config::AggregatorConfig,
+};
+
+
+
+#[derive(Accounts)]
+pub struct InitSaveReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    /// Pays for the cToken account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Save reserve lending USDC, its mints are checked against the reserve data
+    #[account(constraint = *reserve.owner == save_program.key())]
+    pub reserve: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub reserve_collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// Holds the reserve's cTokens for the config PDA
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = reserve_collateral_mint,
+        associated_token::authority = config,
+        associated_token::token_program = token_program,
+    )]
+    pub ctoken_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Owner of the reserve, must be an executable program
+    #[account(executable)]
+    pub save_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+
+impl<'info> InitSaveReserve<'info> {
+    pub fn init_save_reserve(&mut self) -> Result<()> {
+        require_keys_eq!(
+            self.config.save_reserve,
+            Pubkey::default(),
+            AggregatorError::SaveReserveAlreadyInitialized
+        );
+
+        let reserve = read_save_reserve(&self.reserve.try_borrow_data()?)?;
+        require_keys_eq!(reserve.liquidity_mint, self.config.usdc_mint, AggregatorError::InvalidSaveReserve);
+        require_keys_eq!(
+            reserve.collateral_mint,
+            self.reserve_collateral_mint.key(),
+            AggregatorError::InvalidSaveReserve
+        );
+
+        self.config.save_reserve = self.reserve.key();
+
+        emit!(SaveReserveInitEvent {
+            reserve: self.reserve.key(),
+            ctoken_account: self.ctoken_account.key(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    helpers::{access_control::check_access, share_conversion::get_total_assets},
+    states::{AggregatorConfig, Allowlist},
+};
+
+
+#[derive(Accounts)]
+pub struct MaxDeposit<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.cusdc_mint == cusdc_mint.key(),
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    /// CHECK: Wallet the cUSDC would be minted to
+    pub receiver: UncheckedAccount<'info>,
+
+    /// Required for the receiver in AccessMode::Allowlist
+    #[account(
+        seeds = [b"allowlist", receiver.key().as_ref()],
+        bump = receiver_allowlist.bump
+    )]
+    pub receiver_allowlist: Option<Account<'info, Allowlist>>,
+
+    #[account(
+        seeds = [b"cusdc-mint"],
+        bump
+    )]
+    pub cusdc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        associated_token::mint = config.usdc_mint,
+        associated_token::authority = config,
+    )]
+    pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'info> MaxDeposit<'info> {
+    /*
+       USDC that can be deposited for `receiver`: 0 when the access mode doesn't let it hold
+       cUSDC, otherwise only total assets and the cUSDC supply have to stay within u64.
+    */
+    pub fn max_deposit(&self, receiver_proof: &[[u8; 32]], remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        if check_access(&self.config, &self.receiver.key(), self.receiver_allowlist.as_deref(), receiver_proof).is_err() {
+            return Ok(0);
+        }
+
+        let (_, total_assets) = get_total_assets(&self.config, remaining_accounts, self.vault_usdc.amount)?;
+        let total_shares = self.cusdc_mint.supply;
+
+        let max_for_assets = u64::MAX - total_assets;
+        if total_shares == 0 || total_assets == 0 {
+            return Ok(max_for_assets);
+        }
+
+        // Largest deposit whose shares, rounded down, still fit next to the supply
+        let max_for_shares = ((u64::MAX - total_shares) as u128 * total_assets as u128 / total_shares as u128)
+            .min(u64::MAX as u128) as u64;
+
+        Ok(max_for_assets.min(max_for_shares))
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    helpers::share_conversion::{convert_to_assets, get_total_assets, get_withdrawable_liquidity},
+    states::AggregatorConfig,
+};
+
+
+#[derive(Accounts)]
+pub struct MaxWithdraw<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.cusdc_mint == cusdc_mint.key(),
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    #[account(
+        constraint = owner_cusdc.mint == cusdc_mint.key(),
+    )]
+    pub owner_cusdc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"cusdc-mint"],
+        bump
+    )]
+    pub cusdc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        associated_token::mint = config.usdc_mint,
+        associated_token::authority = config,
+    )]
+    pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'info> MaxWithdraw<'info> {
+    /// USDC the owner of `owner_cusdc` can withdraw to themselves right now, limited by protocol
+    /// liquidity. Redeeming to yourself isn't gated by the access mode
+    pub fn max_withdraw(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let (usdc_in_all_protocol, total_assets) = get_total_assets(&self.config, remaining_accounts, self.vault_usdc.amount)?;
+
+        let owner_assets = convert_to_assets(self.owner_cusdc.amount, self.cusdc_mint.supply, total_assets)?;
+        let withdrawable = get_withdrawable_liquidity(&self.config, remaining_accounts, &usdc_in_all_protocol, self.vault_usdc.amount)?;
+
+        Ok(owner_assets.min(withdrawable))
+    }
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::ConfigMigrationEvent,
+    helpers::{
+        config_migration::{apply_config_migrations, CONFIG_ACCOUNT_SIZE},
+        top_up_rent::top_up_rent,
+    },
+    states::aggregator_config::AggregatorConfig,
+};
+
+
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// Pays for the extra space, checked against the stored authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Read by hand, an older layout is too short for `Account<AggregatorConfig>`
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+
+impl<'info> MigrateConfig<'info> {
+    /*
+       Upgrades a live config in place: grows the account to the current layout, funds the
+       extra rent and fills fields added since its version with their defaults.
+    */
+    pub fn migrate_config(&mut self) -> Result<()> {
+        let config_info = self.config.to_account_info();
+
+        if config_info.data_len() < CONFIG_ACCOUNT_SIZE {
+            config_info.resize(CONFIG_ACCOUNT_SIZE)?;
+            top_up_rent(
+                &config_info,
+                &self.authority.to_account_info(),
+                &self.system_program.to_account_info(),
+            )?;
+        }
+
+        let mut config = AggregatorConfig::try_deserialize(&mut &config_info.try_borrow_data()?[..])?;
+        // Same check `has_one = authority` does on the other admin instructions
+        require_keys_eq!(config.authority, self.authority.key(), ErrorCode::ConstraintHasOne);
+
+        let from_version = apply_config_migrations(&mut config)?;
+        config.try_serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ConfigMigrationEvent {
+            from_version,
+            to_version: config.version,
+            account_size: CONFIG_ACCOUNT_SIZE as u64,
+        });
+        Ok(())
+    }
+}
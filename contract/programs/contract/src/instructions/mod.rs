@@ -14,4 +14,67 @@ pub mod update_strategy;
 pub use update_strategy::*;
 
 pub mod view;
-pub use view::*;
\ No newline at end of file
+pub use view::*;
+
+pub mod update_rebalance_params;
+pub use update_rebalance_params::*;
+
+pub mod update_allocation_mode;
+pub use update_allocation_mode::*;
+
+pub mod update_swap_program;
+pub use update_swap_program::*;
+
+pub mod harvest;
+pub use harvest::*;
+
+pub mod view_vault;
+pub use view_vault::*;
+
+pub mod preview;
+pub use preview::*;
+
+pub mod max_withdraw;
+pub use max_withdraw::*;
+
+pub mod max_deposit;
+pub use max_deposit::*;
+
+pub mod approve_operator;
+pub use approve_operator::*;
+
+pub mod update_access_mode;
+pub use update_access_mode::*;
+
+pub mod add_to_allowlist;
+pub use add_to_allowlist::*;
+
+pub mod remove_from_allowlist;
+pub use remove_from_allowlist::*;
+
+pub mod update_holding_period;
+pub use update_holding_period::*;
+
+pub mod update_share_metadata;
+pub use update_share_metadata::*;
+
+pub mod migrate_config;
+pub use migrate_config::*;
+
+pub mod update_farm_buffer;
+pub use update_farm_buffer::*;
+
+pub mod crank_kamino_farm;
+pub use crank_kamino_farm::*;
+
+pub mod init_marginfi_account;
+pub use init_marginfi_account::*;
+
+pub mod init_klend_reserve;
+pub use init_klend_reserve::*;
+
+pub mod init_save_reserve;
+pub use init_save_reserve::*;
+
+pub mod update_fixed_allocation;
+pub use update_fixed_allocation::*;
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    helpers::{
+        protocol_apy::compute_allocation_targets,
+        share_conversion::{
+            convert_to_assets, convert_to_shares, get_total_assets, preview_mint_assets, preview_withdraw_shares,
+        },
+    },
+    states::{AggregatorConfig, AllocationPreview},
+};
+
+
+/// Shared by the read-only conversion instructions, results go out as return data
+#[derive(Accounts)]
+pub struct Preview<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.cusdc_mint == cusdc_mint.key(),
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    #[account(
+        seeds = [b"cusdc-mint"],
+        bump
+    )]
+    pub cusdc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        associated_token::mint = config.usdc_mint,
+        associated_token::authority = config,
+    )]
+    pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'info> Preview<'info> {
+    fn total_assets(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let (_, total_assets) = get_total_assets(&self.config, remaining_accounts, self.vault_usdc.amount)?;
+        Ok(total_assets)
+    }
+
+    /// cUSDC minted for depositing `assets`
+    pub fn preview_deposit(&self, assets: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let total_assets = self.total_assets(remaining_accounts)?;
+        convert_to_shares(assets, self.cusdc_mint.supply, total_assets)
+    }
+
+    /// USDC to deposit to mint at least `shares`
+    pub fn preview_mint(&self, shares: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let total_assets = self.total_assets(remaining_accounts)?;
+        preview_mint_assets(shares, self.cusdc_mint.supply, total_assets)
+    }
+
+    /// cUSDC to burn to withdraw at least `assets`
+    pub fn preview_withdraw(&self, assets: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let total_assets = self.total_assets(remaining_accounts)?;
+        preview_withdraw_shares(assets, self.cusdc_mint.supply, total_assets)
+    }
+
+    /// USDC returned for burning `shares`
+    pub fn preview_redeem(&self, shares: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let total_assets = self.total_assets(remaining_accounts)?;
+        convert_to_assets(shares, self.cusdc_mint.supply, total_assets)
+    }
+
+    pub fn convert_to_shares(&self, assets: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let total_assets = self.total_assets(remaining_accounts)?;
+        convert_to_shares(assets, self.cusdc_mint.supply, total_assets)
+    }
+
+    pub fn convert_to_assets(&self, shares: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let total_assets = self.total_assets(remaining_accounts)?;
+        convert_to_assets(shares, self.cusdc_mint.supply, total_assets)
+    }
+
+    /// Targets the allocation mode would pick for the current balances, so clients can
+    /// see the optimizer's split before a rebalance applies it
+    pub fn preview_allocation(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<AllocationPreview> {
+        let (usdc_in_all_protocol, total_assets) = get_total_assets(&self.config, remaining_accounts, self.vault_usdc.amount)?;
+
+        let (juplend_bps, kamino_bps, apys) =
+            compute_allocation_targets(&self.config, remaining_accounts, &usdc_in_all_protocol, self.vault_usdc.amount)?
+                .unwrap_or((self.config.juplend_allocation_bps, self.config.kamino_allocation_bps, Default::default()));
+
+        Ok(AllocationPreview {
+            total_assets,
+            juplend_bps,
+            kamino_bps,
+            marginfi_bps: self.config.marginfi_allocation_bps,
+            klend_bps: self.config.klend_allocation_bps,
+            save_bps: self.config.save_allocation_bps,
+            juplend_apy_bps: apys.juplend_apy_bps,
+            kamino_apy_bps: apys.kamino_apy_bps,
+        })
+    }
+}
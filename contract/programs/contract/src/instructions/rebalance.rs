@@ -1,10 +1,17 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
+use anchor_spl::{
+    associated_token::AssociatedToken, 
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked}
+};
 
 use crate::{
+    errors::AggregatorError, 
+    events::KeeperTipEvent, 
     helpers::{
       calculate_total_asset_balance::calculate_total_asset_balance, 
-      rebalance_allocation::rebalance_allocation
+      kamino::get_kamino_net_apy::{read_kamino_vault_fees, roll_kamino_interest_snapshot},
+      protocol_apy::refresh_allocation_targets,
+        rebalance_allocation::{calculate_allocation_drift_bps, is_drift_above_threshold, rebalance_allocation}
     }, 
     states::aggregator_config::AggregatorConfig
 };
@@ -18,11 +25,11 @@ pub struct Rebalance<'info> {
         mut,
         seeds = [b"config"],
         bump = config.bump,
-        has_one = authority
     )]
     pub config: Account<'info, AggregatorConfig>,
 
-    pub authority: Signer<'info>,
+    /// The authority can always rebalance, anyone else only once the drift exceeds the threshold
+    pub keeper: Signer<'info>,
 
     #[account(
         mut,
@@ -38,8 +45,29 @@ pub struct Rebalance<'info> {
     )]
     pub cusdc_mint: InterfaceAccount<'info, Mint>,
 
+    #[account(
+        constraint = usdc_mint.key() == config.usdc_mint
+    )]
     pub usdc_mint: InterfaceAccount<'info, Mint>,
 
+    /// Source of the keeper tip, optional
+    #[account(
+        mut,
+        seeds = [b"fee-vault"],
+        bump,
+        token::mint = config.usdc_mint,
+        token::authority = config,
+    )]
+    pub fee_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Receives the keeper tip, optional
+    #[account(
+        mut,
+        constraint = keeper_usdc.mint == config.usdc_mint,
+        constraint = keeper_usdc.owner == keeper.key()
+    )]
+    pub keeper_usdc: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -52,10 +80,42 @@ impl<'info> Rebalance<'info> {
     pub fn rebalance(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
 
 
-        let usdc_in_all_protocol = calculate_total_asset_balance(remaining_accounts)?;
+        let usdc_in_all_protocol = calculate_total_asset_balance(&self.config, remaining_accounts)?;
+
+        let protocol_apys = refresh_allocation_targets(
+            &mut self.config,
+            remaining_accounts,
+            &usdc_in_all_protocol,
+            self.vault_usdc.amount,
+        )?;
+
+        let drift_bps = calculate_allocation_drift_bps(
+            &usdc_in_all_protocol,
+            self.vault_usdc.amount,
+            &self.config.allocation_bps(),
+        )?;
+
+        let current_slot = Clock::get()?.slot;
+        let is_keeper = self.keeper.key() != self.config.authority;
+
+        if is_keeper {
+            require!(
+                is_drift_above_threshold(&self.config, drift_bps),
+                AggregatorError::DriftWithinThreshold
+            );
+
+            let next_rebalance_slot = self.config.last_rebalance_slot
+                .saturating_add(self.config.rebalance_cooldown_slots);
+            require!(
+                current_slot >= next_rebalance_slot,
+                AggregatorError::RebalanceCooldown
+            );
+        }
+
+        msg!("Allocation drift: {} bps", drift_bps);
 
         rebalance_allocation(
-            &self.authority,
+            &self.keeper,
             remaining_accounts,
             usdc_in_all_protocol,
             &self.config,
@@ -64,10 +124,61 @@ impl<'info> Rebalance<'info> {
             &self.token_program,
             &self.associated_token_program,
             &self.system_program,
-            &self.rent.to_account_info()
+            &self.rent.to_account_info(),
+            protocol_apys,
         )?;
 
+        self.config.last_rebalance_slot = current_slot;
+
+        // Kamino charges fees whenever we move funds, so its interest counter is fresh here
+        let vault_fees = read_kamino_vault_fees(remaining_accounts.get(13).ok_or(AggregatorError::MissingAccount)?)?;
+        if roll_kamino_interest_snapshot(&mut self.config.kamino_interest_snapshot, &vault_fees) {
+            msg!("Kamino interest snapshot moved to {}", self.config.kamino_interest_snapshot.timestamp);
+        }
+
+        if is_keeper {
+            self.pay_keeper_tip(drift_bps)?;
+        }
+
         Ok(())
 
     }
-}
\ No newline at end of file
+
+    fn pay_keeper_tip(&self, drift_bps: u16) -> Result<()> {
+        let (Some(fee_vault), Some(keeper_usdc)) = (&self.fee_vault, &self.keeper_usdc) else {
+            return Ok(());
+        };
+
+        // Never pay more than the fee vault holds
+        let tip = self.config.keeper_tip.min(fee_vault.amount);
+        if tip == 0 {
+            return Ok(());
+        }
+
+        let seeds = &[b"config".as_ref(), &[self.config.bump]];
+        let signer = &[&seeds[..]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: fee_vault.to_account_info(),
+                    to: keeper_usdc.to_account_info(),
+                    authority: self.config.to_account_info(),
+                    mint: self.usdc_mint.to_account_info(),
+                },
+                signer,
+            ),
+            tip,
+            self.usdc_mint.decimals
+        )?;
+
+        emit!(KeeperTipEvent {
+            keeper: self.keeper.key(),
+            drift_bps,
+            tip,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::AllowlistUpdateEvent,
+    states::{AggregatorConfig, Allowlist},
+};
+
+
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"allowlist", allowlist.wallet.as_ref()],
+        bump = allowlist.bump
+    )]
+    pub allowlist: Account<'info, Allowlist>,
+}
+
+
+impl<'info> RemoveFromAllowlist<'info> {
+    pub fn remove_from_allowlist(&mut self) -> Result<()> {
+        emit!(AllowlistUpdateEvent {
+            wallet: self.allowlist.wallet,
+            allowed: false,
+        });
+        Ok(())
+    }
+}
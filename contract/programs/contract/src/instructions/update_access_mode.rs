@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::AccessModeUpdateEvent,
+    states::aggregator_config::{AccessMode, AggregatorConfig},
+};
+
+
+
+#[derive(Accounts)]
+pub struct UpdateAccessMode<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+
+impl<'info> UpdateAccessMode<'info> {
+    pub fn update_access_mode(&mut self, access_mode: AccessMode, merkle_root: [u8; 32]) -> Result<()> {
+        let config = &mut self.config;
+        config.access_mode = access_mode;
+        config.merkle_root = merkle_root;
+
+        emit!(AccessModeUpdateEvent {
+            access_mode,
+            merkle_root,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::AllocationModeUpdateEvent, 
+    helpers::protocol_apy::juplend_bps_range, 
+    states::aggregator_config::{AggregatorConfig, AllocationBounds, AllocationMode}
+};
+
+
+
+#[derive(Accounts)]
+pub struct UpdateAllocationMode<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+
+impl<'info> UpdateAllocationMode<'info> {
+    pub fn update_allocation_mode(
+        &mut self,
+        allocation_mode: AllocationMode,
+        juplend_bounds: AllocationBounds,
+        kamino_bounds: AllocationBounds,
+    ) -> Result<()> {
+        // Rejects bounds that can't both be met
+        juplend_bps_range(&juplend_bounds, &kamino_bounds)?;
+
+        let config = &mut self.config;
+        config.allocation_mode = allocation_mode;
+        config.juplend_bounds = juplend_bounds;
+        config.kamino_bounds = kamino_bounds;
+
+        emit!(AllocationModeUpdateEvent {
+            allocation_mode,
+            juplend_bounds,
+            kamino_bounds,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::BPS_BASE,
+    errors::AggregatorError,
+    events::FarmBufferUpdateEvent,
+    states::aggregator_config::AggregatorConfig,
+};
+
+
+
+#[derive(Accounts)]
+pub struct UpdateFarmBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+
+impl<'info> UpdateFarmBuffer<'info> {
+    pub fn update_farm_buffer(&mut self, farm_liquid_buffer_bps: u16) -> Result<()> {
+        require!(
+            farm_liquid_buffer_bps <= BPS_BASE,
+            AggregatorError::InvalidAllocation
+        );
+
+        self.config.farm_liquid_buffer_bps = farm_liquid_buffer_bps;
+
+        emit!(FarmBufferUpdateEvent {
+            farm_liquid_buffer_bps,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::BPS_BASE,
+    errors::AggregatorError,
+    events::AllocationUpdateEvent,
+    helpers::protocol_apy::{floating_share_bps, split_floating_bps},
+    states::aggregator_config::{AggregatorConfig, FixedProtocol},
+};
+
+
+
+#[derive(Accounts)]
+pub struct UpdateFixedAllocation<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+
+impl<'info> UpdateFixedAllocation<'info> {
+    pub fn update_fixed_allocation(&mut self, protocol: FixedProtocol, bps: u16) -> Result<()> {
+        let config = &mut self.config;
+
+        // The other fixed targets stay, the protocol can only take what they leave
+        let current_bps = *config.fixed_allocation_bps_mut(protocol);
+        require!(
+            bps <= BPS_BASE - (config.fixed_allocation_bps() - current_bps),
+            AggregatorError::InvalidAllocation
+        );
+
+        // Nothing to deposit into before the protocol's init instruction ran
+        let (protocol_account, not_initialized) = match protocol {
+            FixedProtocol::Marginfi => (config.marginfi_account, AggregatorError::InvalidMarginfiAccount),
+            FixedProtocol::Klend => (config.klend_reserve, AggregatorError::InvalidKlendReserve),
+            FixedProtocol::Save => (config.save_reserve, AggregatorError::InvalidSaveReserve),
+        };
+        if bps != 0 && protocol_account == Pubkey::default() {
+            return Err(not_initialized.into());
+        }
+
+        // JupLend and Kamino keep their split of whatever the fixed targets leave
+        let juplend_share_bps = floating_share_bps(config.juplend_allocation_bps, config.floating_allocation_bps());
+
+        *config.fixed_allocation_bps_mut(protocol) = bps;
+        let (juplend_bps, kamino_bps) = split_floating_bps(juplend_share_bps, config.floating_allocation_bps());
+        config.juplend_allocation_bps = juplend_bps;
+        config.kamino_allocation_bps = kamino_bps;
+
+        emit!(AllocationUpdateEvent {
+            juplend_bps,
+            kamino_bps,
+            marginfi_bps: config.marginfi_allocation_bps,
+            klend_bps: config.klend_allocation_bps,
+            save_bps: config.save_allocation_bps,
+        });
+        Ok(())
+    }
+}
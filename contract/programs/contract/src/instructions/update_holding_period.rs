@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::HoldingPeriodUpdateEvent,
+    states::aggregator_config::AggregatorConfig,
+};
+
+
+
+#[derive(Accounts)]
+pub struct UpdateHoldingPeriod<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+
+impl<'info> UpdateHoldingPeriod<'info> {
+    pub fn update_holding_period(&mut self, min_holding_slots: u64) -> Result<()> {
+        self.config.min_holding_slots = min_holding_slots;
+
+        emit!(HoldingPeriodUpdateEvent {
+            min_holding_slots,
+        });
+        Ok(())
+    }
+}
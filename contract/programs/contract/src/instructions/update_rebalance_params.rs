@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::BPS_BASE, 
+    errors::AggregatorError, 
+    events::RebalanceParamsUpdateEvent, 
+    states::aggregator_config::AggregatorConfig
+};
+
+
+
+#[derive(Accounts)]
+pub struct UpdateRebalanceParams<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+
+impl<'info> UpdateRebalanceParams<'info> {
+    pub fn update_rebalance_params(
+        &mut self,
+        drift_threshold_bps: u16,
+        rebalance_cooldown_slots: u64,
+        keeper_tip: u64,
+    ) -> Result<()> {
+        require!(
+            drift_threshold_bps <= BPS_BASE,
+            AggregatorError::InvalidAllocation
+        );
+
+        let config = &mut self.config;
+        config.drift_threshold_bps = drift_threshold_bps;
+        config.rebalance_cooldown_slots = rebalance_cooldown_slots;
+        config.keeper_tip = keeper_tip;
+
+        emit!(RebalanceParamsUpdateEvent {
+            drift_threshold_bps,
+            rebalance_cooldown_slots,
+            keeper_tip,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::Token2022,
+    token_interface::{
+        spl_token_metadata_interface::state::Field, token_metadata_update_field, Mint, TokenMetadataUpdateField,
+    },
+};
+
+use crate::{
+    events::ShareMetadataUpdateEvent,
+    helpers::{share_metadata::validate_share_metadata, top_up_rent::top_up_rent},
+    states::aggregator_config::AggregatorConfig,
+};
+
+
+
+#[derive(Accounts)]
+pub struct UpdateShareMetadata<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority,
+        constraint = config.cusdc_mint == cusdc_mint.key(),
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    /// Pays for the mint growing when the new values are longer
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"cusdc-mint"],
+        bump,
+        mint::token_program = share_token_program,
+    )]
+    pub cusdc_mint: InterfaceAccount<'info, Mint>,
+
+    pub share_token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+
+impl<'info> UpdateShareMetadata<'info> {
+    pub fn update_share_metadata(&mut self, name: String, symbol: String, uri: String) -> Result<()> {
+        validate_share_metadata(&name, &symbol, &uri)?;
+
+        let seeds = &[b"config".as_ref(), &[self.config.bump]];
+        let signer = &[&seeds[..]];
+
+        for (field, value) in [
+            (Field::Name, name.clone()),
+            (Field::Symbol, symbol.clone()),
+            (Field::Uri, uri.clone()),
+        ] {
+            token_metadata_update_field(
+                CpiContext::new_with_signer(
+                    self.share_token_program.to_account_info(),
+                    TokenMetadataUpdateField {
+                        program_id: self.share_token_program.to_account_info(),
+                        metadata: self.cusdc_mint.to_account_info(),
+                        update_authority: self.config.to_account_info(),
+                    },
+                    signer,
+                ),
+                field,
+                value,
+            )?;
+        }
+
+        top_up_rent(
+            &self.cusdc_mint.to_account_info(),
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+
+        emit!(ShareMetadataUpdateEvent { name, symbol, uri });
+        Ok(())
+    }
+}
@@ -2,7 +2,6 @@ use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 
 use crate::{
-    constants::BPS_BASE, 
     errors::AggregatorError, 
     events::AllocationUpdateEvent, 
     states::aggregator_config::AggregatorConfig
@@ -36,20 +35,25 @@ pub struct UpdateStrategy<'info> {
 
 impl<'info> UpdateStrategy<'info> {
     pub fn update_strategy(&mut self, new_juplend_bps: u16) -> Result<()> {
+        // The fixed allocations (MarginFi, klend, Save) keep their bps, JupLend and Kamino split the floating remainder
+        let floating_bps = self.config.floating_allocation_bps();
         require!(
-            new_juplend_bps <= 10000,
+            new_juplend_bps <= floating_bps,
             AggregatorError::InvalidAllocation
         );
 
         let config = &mut self.config;
         config.juplend_allocation_bps = new_juplend_bps;
-        config.kamino_allocation_bps = BPS_BASE - new_juplend_bps;
+        config.kamino_allocation_bps = floating_bps - new_juplend_bps;
 
     
 
         emit!(AllocationUpdateEvent {
             juplend_bps: new_juplend_bps,
-            kamino_bps: BPS_BASE - new_juplend_bps,
+            kamino_bps: floating_bps - new_juplend_bps,
+            marginfi_bps: config.marginfi_allocation_bps,
+            klend_bps: config.klend_allocation_bps,
+            save_bps: config.save_allocation_bps,
         });
         Ok(())
     }
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::SwapProgramUpdateEvent, 
+    states::aggregator_config::AggregatorConfig
+};
+
+
+
+#[derive(Accounts)]
+pub struct UpdateSwapProgram<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Only stored, must be an executable program
+    #[account(executable)]
+    pub swap_program: UncheckedAccount<'info>,
+}
+
+
+impl<'info> UpdateSwapProgram<'info> {
+    pub fn update_swap_program(&mut self) -> Result<()> {
+        self.config.swap_program = self.swap_program.key();
+
+        emit!(SwapProgramUpdateEvent {
+            swap_program: self.swap_program.key(),
+        });
+        Ok(())
+    }
+}
@@ -5,10 +5,16 @@ use crate::{
     errors::AggregatorError, 
     events::ViewEvent, 
     helpers::{
-        calculate_total_asset_balance::calculate_total_asset_balance, 
-        calculate_usdc_for_shares::calculate_usdc_for_shares
+        calculate_usdc_for_shares::calculate_usdc_for_shares,
+        juplend::get_juplend_apy::get_juplend_rewards_apr_bps,
+        kamino::get_kamino_farm_rewards::get_kamino_unclaimed_rewards,
+        vault_view::build_vault_view,
     }, 
-    states::AggregatorConfig
+    states::{
+        AggregatorConfig, UserView,
+        lending::Lending,
+        lending_rewards_rate_model::LendingRewardsRateModel,
+    }
 };
 
 
@@ -35,28 +41,69 @@ pub struct View<'info> {
     )]
     pub cusdc_mint: InterfaceAccount<'info, Mint>,
 
+    #[account(
+        associated_token::mint = config.usdc_mint,
+        associated_token::authority = config,
+    )]
+    pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
+
 }
 
 impl<'info> View<'info> {
-    pub fn view(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+    pub fn view(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<UserView> {
 
-        let usdc_in_all_protocol = calculate_total_asset_balance(remaining_accounts)?;
-        let total_usdc_in_protocols_combined: u64 = usdc_in_all_protocol
-            .iter()
-            .try_fold(0u64, |acc, x| acc.checked_add(*x))
-            .ok_or(AggregatorError::MathOverflow)?;
+        let vault = build_vault_view(
+            &self.config,
+            remaining_accounts,
+            self.vault_usdc.amount,
+            self.cusdc_mint.supply,
+        )?;
 
-        let user_yeild = calculate_usdc_for_shares(
+        let user_value = calculate_usdc_for_shares(
             self.user_cusdc.amount, 
             self.cusdc_mint.supply, 
-            total_usdc_in_protocols_combined
-        );
+            vault.total_assets
+        )?;
+
+        let account = |index: usize| remaining_accounts.get(index).ok_or(AggregatorError::MissingAccount);
+
+        // Kamino farm rewards sit outside the share price until they are harvested
+        let mut unclaimed_rewards = get_kamino_unclaimed_rewards(account(23)?, account(30)?)?;
+        if self.cusdc_mint.supply > 0 {
+            for reward in unclaimed_rewards.iter_mut() {
+                reward.user_amount = (reward.amount as u128)
+                    .checked_mul(self.user_cusdc.amount as u128)
+                    .ok_or(AggregatorError::MathOverflow)?
+                    .checked_div(self.cusdc_mint.supply as u128)
+                    .ok_or(AggregatorError::MathOverflow)? as u64;
+            }
+        }
+
+        let jup_lending = Lending::try_deserialize(&mut &account(0)?.try_borrow_data()?[..])?;
+        let jup_rewards_rate_model = LendingRewardsRateModel::try_deserialize(&mut &account(1)?.try_borrow_data()?[..])?;
+        let f_token_mint = InterfaceAccount::<'info, Mint>::try_from(account(2)?)?;
+
+        let juplend_rewards_apr_bps = get_juplend_rewards_apr_bps(
+            &jup_lending,
+            &jup_rewards_rate_model,
+            f_token_mint.supply,
+        )?;
 
         emit!(ViewEvent {
             user: self.authority.key(),
-            user_yeild: user_yeild,
+            // Named before the typo was noticed, kept so indexers keep decoding the event
+            user_yeild: user_value,
+            unclaimed_rewards: unclaimed_rewards.clone(),
+            juplend_rewards_apr_bps,
         });
 
-        Ok(())
+        Ok(UserView {
+            vault,
+            user: self.authority.key(),
+            user_shares: self.user_cusdc.amount,
+            user_value,
+            unclaimed_rewards,
+            juplend_rewards_apr_bps,
+        })
     }
 }
\ No newline at end of file
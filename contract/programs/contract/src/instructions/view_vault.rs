@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    helpers::vault_view::build_vault_view,
+    states::{AggregatorConfig, VaultView},
+};
+
+
+#[derive(Accounts)]
+pub struct ViewVault<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.cusdc_mint == cusdc_mint.key(),
+    )]
+    pub config: Account<'info, AggregatorConfig>,
+
+    #[account(
+        seeds = [b"cusdc-mint"],
+        bump
+    )]
+    pub cusdc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        associated_token::mint = config.usdc_mint,
+        associated_token::authority = config,
+    )]
+    pub vault_usdc: InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'info> ViewVault<'info> {
+    pub fn view_vault(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<VaultView> {
+        build_vault_view(
+            &self.config,
+            remaining_accounts,
+            self.vault_usdc.amount,
+            self.cusdc_mint.supply,
+        )
+    }
+}
@@ -1,25 +1,30 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
 use anchor_spl::{ 
     associated_token::AssociatedToken, 
-    token_2022::{
+    token_interface::{
         Burn, 
+        Mint, 
+        TokenAccount, 
+        TokenInterface,
         TransferChecked, 
         burn, 
         transfer_checked
-    }, 
-    token_interface::{Mint, TokenAccount, TokenInterface}
+    }
 };
 
 use crate::{
     errors::AggregatorError, 
     events::WithdrawEvent, 
     helpers::{
-        calculate_total_asset_balance::calculate_total_asset_balance, 
         calculate_usdc_for_shares::calculate_usdc_for_shares, 
-        rebalance_allocation::rebalance_allocation, 
+        protocol_apy::refresh_allocation_targets,
+        rebalance_allocation::{calculate_allocation_drift_bps, is_drift_above_threshold, rebalance_allocation}, 
+        share_conversion::{get_total_assets, preview_withdraw_shares},
+        holding_period::{check_holding_period, read_user_position},
+        access_control::check_access,
         withdraw_from_protocols::withdraw_from_protocols
     }, 
-    states::aggregator_config::AggregatorConfig
+    states::{aggregator_config::AggregatorConfig, Allowlist, OperatorApproval}
 };
 
 
@@ -33,22 +38,48 @@ pub struct Withdraw<'info> {
     )]
     pub config: Account<'info, AggregatorConfig>,
 
+    /// The owner, or an operator the owner approved
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// CHECK: Owner of the cUSDC being redeemed, checked against `owner_cusdc`
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: The owner's UserPosition PDA, may not exist if they never received a deposit
+    #[account(
+        seeds = [b"position", owner.key().as_ref()],
+        bump
+    )]
+    pub owner_position: UncheckedAccount<'info>,
+
+    /// Required when `user` is not the owner
+    #[account(
+        mut,
+        seeds = [b"operator", owner.key().as_ref(), user.key().as_ref()],
+        bump = operator_approval.bump,
+    )]
+    pub operator_approval: Option<Box<Account<'info, OperatorApproval>>>,
+
+    /// Required for the owner in AccessMode::Allowlist, when paying out to someone else
+    #[account(
+        seeds = [b"allowlist", owner.key().as_ref()],
+        bump = owner_allowlist.bump
+    )]
+    pub owner_allowlist: Option<Box<Account<'info, Allowlist>>>,
+
     #[account(
         mut,
-        constraint = user_usdc.mint == config.usdc_mint,
-        constraint = user_usdc.owner == user.key()
+        constraint = receiver_usdc.mint == config.usdc_mint,
     )]
-    pub user_usdc: InterfaceAccount<'info, TokenAccount>,
+    pub receiver_usdc: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        constraint = user_cusdc.mint == config.cusdc_mint,
-        constraint = user_cusdc.owner == user.key()
+        constraint = owner_cusdc.mint == config.cusdc_mint,
+        constraint = owner_cusdc.owner == owner.key(),
+        token::token_program = share_token_program
     )]
-    pub user_cusdc: InterfaceAccount<'info, TokenAccount>,
+    pub owner_cusdc: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
@@ -60,7 +91,8 @@ pub struct Withdraw<'info> {
     #[account(
         mut,
         seeds = [b"cusdc-mint"],
-        bump
+        bump,
+        mint::token_program = share_token_program
     )]
     pub cusdc_mint: InterfaceAccount<'info, Mint>,
 
@@ -70,6 +102,8 @@ pub struct Withdraw<'info> {
     pub usdc_mint: InterfaceAccount<'info, Mint>,
 
     pub token_program: Interface<'info, TokenInterface>,
+    /// Token-2022, or SPL Token for live vaults whose cUSDC predates the Token-2022 mint
+    pub share_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -78,56 +112,113 @@ pub struct Withdraw<'info> {
 
 impl<'info> Withdraw<'info> {
 
-    pub fn withdraw(&mut self, cusdc_amount: u64, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> 
+    pub fn withdraw(&mut self, cusdc_amount: u64, owner_proof: &[[u8; 32]], remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> 
     {
         require!(cusdc_amount > 0, AggregatorError::InvalidAmount);
 
-        let config = &self.config;
+        self.check_owner_access(owner_proof)?;
 
-        // Get total USDC in all protocols combined
-        let usdc_in_all_protocol = calculate_total_asset_balance(remaining_accounts)?;
-        let total_usdc_in_protocols_combined: u64 = usdc_in_all_protocol
-            .iter()
-            .try_fold(0u64, |acc, x| acc.checked_add(*x))
-            .ok_or(AggregatorError::MathOverflow)?;
+        // Idle USDC left in the vault between rebalances is part of total assets
+        let (usdc_in_all_protocol, total_usdc_in_protocols_combined) =
+            get_total_assets(&self.config, remaining_accounts, self.vault_usdc.amount)?;
         
         // calculate the usdc to withdraw based on cusdc shares
         let usdc_to_withdraw = calculate_usdc_for_shares(
             cusdc_amount,
             self.cusdc_mint.supply,
             total_usdc_in_protocols_combined
-        );
+        )?;
 
-        withdraw_from_protocols(
-            usdc_to_withdraw,
-            &self.user,
-            remaining_accounts,
-            self.config.clone(),
-            self.vault_usdc.clone(),
-            self.usdc_mint.clone(),
-            self.token_program.clone(),
-            self.associated_token_program.to_account_info(),
-            self.system_program.to_account_info(),
-            self.rent.to_account_info(),
+        self.settle_withdraw(cusdc_amount, usdc_to_withdraw, usdc_in_all_protocol, remaining_accounts)
+    }
+
+    /// Pays out exactly `usdc_amount`, burning the cUSDC it is worth (rounded up)
+    pub fn withdraw_assets(
+        &mut self,
+        usdc_amount: u64,
+        max_shares_in: u64,
+        owner_proof: &[[u8; 32]],
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> 
+    {
+        require!(usdc_amount > 0, AggregatorError::InvalidAmount);
+
+        self.check_owner_access(owner_proof)?;
+
+        let (usdc_in_all_protocol, total_usdc_in_protocols_combined) =
+            get_total_assets(&self.config, remaining_accounts, self.vault_usdc.amount)?;
+
+        let cusdc_amount = preview_withdraw_shares(
+            usdc_amount,
+            self.cusdc_mint.supply,
+            total_usdc_in_protocols_combined
         )?;
 
+        if cusdc_amount > max_shares_in {
+            msg!("Withdrawing {} USDC burns {} cUSDC, above max {}", usdc_amount, cusdc_amount, max_shares_in);
+            return Err(AggregatorError::SlippageExceeded.into());
+        }
+        require!(cusdc_amount <= self.owner_cusdc.amount, AggregatorError::InsufficientBalance);
+
+        self.settle_withdraw(cusdc_amount, usdc_amount, usdc_in_all_protocol, remaining_accounts)
+    }
+
+    /// cUSDC can be transferred to anyone, so a holder paying out to another wallet has to pass
+    /// the vault's access mode too. Owners always redeem to themselves, so removing a wallet
+    /// from the allowlist or rotating the Merkle root never freezes shares it already holds
+    fn check_owner_access(&self, owner_proof: &[[u8; 32]]) -> Result<()> {
+        if self.receiver_usdc.owner == self.owner.key() {
+            return Ok(());
+        }
+
+        check_access(&self.config, &self.owner.key(), self.owner_allowlist.as_deref().map(|entry| &**entry), owner_proof)
+    }
+
+    fn settle_withdraw(
+        &mut self,
+        cusdc_amount: u64,
+        usdc_to_withdraw: u64,
+        usdc_in_all_protocol: Vec<u64>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> 
+    {
+        if let Some(position) = read_user_position(&self.owner_position)? {
+            check_holding_period(
+                &position,
+                self.config.min_holding_slots,
+                Clock::get()?.slot,
+                self.owner_cusdc.amount,
+                cusdc_amount,
+            )?;
+        }
+
+        // Pay out of idle USDC first and only pull the rest from the protocols
+        let usdc_from_protocols = usdc_to_withdraw.saturating_sub(self.vault_usdc.amount);
+
+        let withdraw_split = if usdc_from_protocols > 0 {
+            withdraw_from_protocols(
+                usdc_from_protocols,
+                &self.user,
+                remaining_accounts,
+                self.config.clone(),
+                self.vault_usdc.clone(),
+                self.usdc_mint.clone(),
+                self.token_program.clone(),
+                self.associated_token_program.to_account_info(),
+                self.system_program.to_account_info(),
+                self.rent.to_account_info(),
+            )?
+        } else {
+            vec![0; usdc_in_all_protocol.len()]
+        };
+
         // burn the cusdc amount
-        burn(
-            CpiContext::new(
-                self.token_program.to_account_info(),
-                Burn {
-                    mint: self.cusdc_mint.to_account_info(),
-                    from: self.user_cusdc.to_account_info(),
-                    authority: self.user.to_account_info(),
-                },
-            ),
-            cusdc_amount,
-        )?;
+        self.burn_owner_shares(cusdc_amount)?;
 
         self.vault_usdc.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
 
 
-        let seeds = &[b"config".as_ref(), &[config.bump]];
+        let seeds = &[b"config".as_ref(), &[self.config.bump]];
         let signer = &[&seeds[..]];
 
         transfer_checked(
@@ -135,7 +226,7 @@ impl<'info> Withdraw<'info> {
                 self.token_program.to_account_info(),
                 TransferChecked {
                     from: self.vault_usdc.to_account_info(),
-                    to: self.user_usdc.to_account_info(),
+                    to: self.receiver_usdc.to_account_info(),
                     authority: self.config.to_account_info(),
                     mint: self.usdc_mint.to_account_info(),
                 },
@@ -146,27 +237,110 @@ impl<'info> Withdraw<'info> {
         )?;
 
         self.vault_usdc.reload().map_err(|_| AggregatorError::AccountReloadFailed)?;
+
+        // Protocol balances after the withdrawal, so the rebalance doesn't undo it
+        let usdc_in_all_protocol: Vec<u64> = usdc_in_all_protocol
+            .iter()
+            .zip(withdraw_split.iter())
+            .map(|(balance, withdrawn)| balance.saturating_sub(*withdrawn))
+            .collect();
         
-        // Rebalance JupLend and Kamino allocation
-        rebalance_allocation(
-            &self.user,
+        let protocol_apys = refresh_allocation_targets(
+            &mut self.config,
             remaining_accounts,
-            usdc_in_all_protocol,
-            &self.config,
-            &mut self.vault_usdc,
-            &self.usdc_mint,
-            &self.token_program,
-            &self.associated_token_program,
-            &self.system_program,
-            &self.rent.to_account_info()
+            &usdc_in_all_protocol,
+            self.vault_usdc.amount,
         )?;
 
+        let drift_bps = calculate_allocation_drift_bps(
+            &usdc_in_all_protocol,
+            self.vault_usdc.amount,
+            &self.config.allocation_bps(),
+        )?;
+
+        // Rebalance JupLend and Kamino allocation once the drift is out of tolerance
+        if is_drift_above_threshold(&self.config, drift_bps) {
+            rebalance_allocation(
+                &self.user,
+                remaining_accounts,
+                usdc_in_all_protocol,
+                &self.config,
+                &mut self.vault_usdc,
+                &self.usdc_mint,
+                &self.token_program,
+                &self.associated_token_program,
+                &self.system_program,
+                &self.rent.to_account_info(),
+                protocol_apys,
+            )?;
+            self.config.last_rebalance_slot = Clock::get()?.slot;
+        } else {
+            msg!("Allocation drift {} bps within threshold, skipping rebalance", drift_bps);
+        }
+
         emit!(WithdrawEvent {
             user: self.user.key(),
+            owner: self.owner.key(),
+            receiver: self.receiver_usdc.owner,
             cusdc_burned: cusdc_amount,
             usdc_returned: usdc_to_withdraw,
         });
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    /*
+       The owner burns their own cUSDC. An operator burns through its approval PDA, which the
+       owner made delegate of `owner_cusdc` in `approve_operator`, and uses up its allowance.
+    */
+    fn burn_owner_shares(&mut self, cusdc_amount: u64) -> Result<()> {
+        if self.user.key() == self.owner.key() {
+            return burn(
+                CpiContext::new(
+                    self.share_token_program.to_account_info(),
+                    Burn {
+                        mint: self.cusdc_mint.to_account_info(),
+                        from: self.owner_cusdc.to_account_info(),
+                        authority: self.user.to_account_info(),
+                    },
+                ),
+                cusdc_amount,
+            );
+        }
+
+        let approval = self.operator_approval
+            .as_mut()
+            .ok_or(AggregatorError::InvalidOperatorApproval)?;
+
+        require!(
+            approval.owner == self.owner.key() && approval.operator == self.user.key(),
+            AggregatorError::InvalidOperatorApproval
+        );
+        require!(approval.allowance >= cusdc_amount, AggregatorError::AllowanceExceeded);
+        // The owner approved someone else since, the delegation is gone
+        require!(
+            self.owner_cusdc.delegate == COption::Some(approval.key()),
+            AggregatorError::InvalidOperatorApproval
+        );
+
+        approval.allowance -= cusdc_amount;
+
+        let owner_key = self.owner.key();
+        let user_key = self.user.key();
+        let seeds = &[b"operator".as_ref(), owner_key.as_ref(), user_key.as_ref(), &[approval.bump]];
+        let signer = &[&seeds[..]];
+
+        burn(
+            CpiContext::new_with_signer(
+                self.share_token_program.to_account_info(),
+                Burn {
+                    mint: self.cusdc_mint.to_account_info(),
+                    from: self.owner_cusdc.to_account_info(),
+                    authority: approval.to_account_info(),
+                },
+                signer,
+            ),
+            cusdc_amount,
+        )
+    }
+}
@@ -3,6 +3,7 @@ use anchor_lang::prelude::*;
 pub mod errors;
 pub mod instructions;
 use crate::instructions::*;
+use crate::states::{AccessMode, AllocationBounds, AllocationMode, AllocationPreview, FixedProtocol, UserView, VaultView};
 
 pub mod states;
 pub mod helpers;
@@ -16,21 +17,123 @@ declare_id!("AUQt43E6brmpbQ1zeWCtSvZX9zBUEkoYmHrthQk8eA6W");
 pub mod contract {
     use super::*;
  
-    pub fn init_aggregator_config(ctx: Context<InitAggregatorConfig>, juplend_allocation_bps: u16) -> Result<()> {
-        ctx.accounts.init_aggregator_config(juplend_allocation_bps, ctx.bumps)?;
+    pub fn init_aggregator_config(
+        ctx: Context<InitAggregatorConfig>,
+        juplend_allocation_bps: u16,
+        share_name: String,
+        share_symbol: String,
+        share_uri: String,
+    ) -> Result<()> {
+        ctx.accounts.init_aggregator_config(juplend_allocation_bps, share_name, share_symbol, share_uri, ctx.bumps)?;
         Ok(())
     }
 
-    pub fn deposit<'info>(ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>, amount: u64) -> Result<()> {
+    pub fn update_share_metadata(
+        ctx: Context<UpdateShareMetadata>,
+        share_name: String,
+        share_symbol: String,
+        share_uri: String,
+    ) -> Result<()> {
+        ctx.accounts.update_share_metadata(share_name, share_symbol, share_uri)?;
+        Ok(())
+    }
+
+    pub fn deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>,
+        amount: u64,
+        user_proof: Vec<[u8; 32]>,
+        receiver_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
         
-        ctx.accounts.deposit(amount, ctx.bumps, ctx.remaining_accounts)?;
+        ctx.accounts.deposit(amount, &user_proof, &receiver_proof, ctx.bumps, ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn mint_shares<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>,
+        shares: u64,
+        max_assets_in: u64,
+        user_proof: Vec<[u8; 32]>,
+        receiver_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.mint_shares(shares, max_assets_in, &user_proof, &receiver_proof, ctx.bumps, ctx.remaining_accounts)?;
         Ok(())
     }
 
-    pub fn withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>, cusdc_amount: u64) -> Result<()> 
+    pub fn withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>, cusdc_amount: u64, owner_proof: Vec<[u8; 32]>) -> Result<()> 
     {
-        ctx.accounts.withdraw(cusdc_amount, ctx.remaining_accounts)?;
+        ctx.accounts.withdraw(cusdc_amount, &owner_proof, ctx.remaining_accounts)?;
+
+        Ok(())
+    }
+
+    pub fn withdraw_assets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>,
+        usdc_amount: u64,
+        max_shares_in: u64,
+        owner_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.withdraw_assets(usdc_amount, max_shares_in, &owner_proof, ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn approve_operator(ctx: Context<ApproveOperator>, allowance: u64) -> Result<()> {
+        ctx.accounts.approve_operator(allowance, ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn update_access_mode(ctx: Context<UpdateAccessMode>, access_mode: AccessMode, merkle_root: [u8; 32]) -> Result<()> {
+        ctx.accounts.update_access_mode(access_mode, merkle_root)?;
+        Ok(())
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>) -> Result<()> {
+        ctx.accounts.add_to_allowlist(ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        ctx.accounts.remove_from_allowlist()?;
+        Ok(())
+    }
+
+    pub fn update_holding_period(ctx: Context<UpdateHoldingPeriod>, min_holding_slots: u64) -> Result<()> {
+        ctx.accounts.update_holding_period(min_holding_slots)?;
+        Ok(())
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        ctx.accounts.migrate_config()?;
+        Ok(())
+    }
+
+    pub fn update_farm_buffer(ctx: Context<UpdateFarmBuffer>, farm_liquid_buffer_bps: u16) -> Result<()> {
+        ctx.accounts.update_farm_buffer(farm_liquid_buffer_bps)?;
+        Ok(())
+    }
+
+    pub fn crank_kamino_farm<'info>(ctx: Context<'_, '_, 'info, 'info, CrankKaminoFarm<'info>>) -> Result<()> {
+        ctx.accounts.crank_kamino_farm(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn init_marginfi_account(ctx: Context<InitMarginfiAccount>) -> Result<()> {
+        ctx.accounts.init_marginfi_account()?;
+        Ok(())
+    }
+
+    pub fn init_klend_reserve(ctx: Context<InitKlendReserve>) -> Result<()> {
+        ctx.accounts.init_klend_reserve()?;
+        Ok(())
+    }
+
+    pub fn init_save_reserve(ctx: Context<InitSaveReserve>) -> Result<()> {
+        ctx.accounts.init_save_reserve()?;
+        Ok(())
+    }
 
+    pub fn update_fixed_allocation(ctx: Context<UpdateFixedAllocation>, protocol: FixedProtocol, bps: u16) -> Result<()> {
+        ctx.accounts.update_fixed_allocation(protocol, bps)?;
         Ok(())
     }
 
@@ -44,9 +147,84 @@ pub mod contract {
         Ok(())
     }
 
-    pub fn view<'info>(ctx: Context<'_, '_, 'info, 'info, View<'info>>) -> Result<()> {
-        ctx.accounts.view(ctx.remaining_accounts)?;
+    pub fn update_rebalance_params(
+        ctx: Context<UpdateRebalanceParams>,
+        drift_threshold_bps: u16,
+        rebalance_cooldown_slots: u64,
+        keeper_tip: u64,
+    ) -> Result<()> {
+        ctx.accounts.update_rebalance_params(drift_threshold_bps, rebalance_cooldown_slots, keeper_tip)?;
+        Ok(())
+    }
+
+    pub fn update_allocation_mode(
+        ctx: Context<UpdateAllocationMode>,
+        allocation_mode: AllocationMode,
+        juplend_bounds: AllocationBounds,
+        kamino_bounds: AllocationBounds,
+    ) -> Result<()> {
+        ctx.accounts.update_allocation_mode(allocation_mode, juplend_bounds, kamino_bounds)?;
         Ok(())
     }
+
+    pub fn update_swap_program(ctx: Context<UpdateSwapProgram>) -> Result<()> {
+        ctx.accounts.update_swap_program()?;
+        Ok(())
+    }
+
+    pub fn harvest<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Harvest<'info>>,
+        reward_index: u64,
+        swap_data: Vec<u8>,
+        swap_accounts_len: u8,
+        min_usdc_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.harvest(reward_index, swap_data, swap_accounts_len, min_usdc_out, ctx.remaining_accounts, ctx.bumps.swap_authority)?;
+        Ok(())
+    }
+
+    pub fn view<'info>(ctx: Context<'_, '_, 'info, 'info, View<'info>>) -> Result<UserView> {
+        ctx.accounts.view(ctx.remaining_accounts)
+    }
+
+    pub fn preview_deposit<'info>(ctx: Context<'_, '_, 'info, 'info, Preview<'info>>, assets: u64) -> Result<u64> {
+        ctx.accounts.preview_deposit(assets, ctx.remaining_accounts)
+    }
+
+    pub fn preview_mint<'info>(ctx: Context<'_, '_, 'info, 'info, Preview<'info>>, shares: u64) -> Result<u64> {
+        ctx.accounts.preview_mint(shares, ctx.remaining_accounts)
+    }
+
+    pub fn preview_withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, Preview<'info>>, assets: u64) -> Result<u64> {
+        ctx.accounts.preview_withdraw(assets, ctx.remaining_accounts)
+    }
+
+    pub fn preview_redeem<'info>(ctx: Context<'_, '_, 'info, 'info, Preview<'info>>, shares: u64) -> Result<u64> {
+        ctx.accounts.preview_redeem(shares, ctx.remaining_accounts)
+    }
+
+    pub fn convert_to_shares<'info>(ctx: Context<'_, '_, 'info, 'info, Preview<'info>>, assets: u64) -> Result<u64> {
+        ctx.accounts.convert_to_shares(assets, ctx.remaining_accounts)
+    }
+
+    pub fn convert_to_assets<'info>(ctx: Context<'_, '_, 'info, 'info, Preview<'info>>, shares: u64) -> Result<u64> {
+        ctx.accounts.convert_to_assets(shares, ctx.remaining_accounts)
+    }
+
+    pub fn max_deposit<'info>(ctx: Context<'_, '_, 'info, 'info, MaxDeposit<'info>>, receiver_proof: Vec<[u8; 32]>) -> Result<u64> {
+        ctx.accounts.max_deposit(&receiver_proof, ctx.remaining_accounts)
+    }
+
+    pub fn max_withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, MaxWithdraw<'info>>) -> Result<u64> {
+        ctx.accounts.max_withdraw(ctx.remaining_accounts)
+    }
+
+    pub fn view_vault<'info>(ctx: Context<'_, '_, 'info, 'info, ViewVault<'info>>) -> Result<VaultView> {
+        ctx.accounts.view_vault(ctx.remaining_accounts)
+    }
+
+    pub fn preview_allocation<'info>(ctx: Context<'_, '_, 'info, 'info, Preview<'info>>) -> Result<AllocationPreview> {
+        ctx.accounts.preview_allocation(ctx.remaining_accounts)
+    }
 }
 
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::BPS_BASE;
+
 
 #[account]
 #[derive(InitSpace)]
@@ -12,4 +14,130 @@ pub struct AggregatorConfig {
     pub kamino_allocation_bps: u16,
     pub total_deposits: u64,
     pub bump: u8,
-}
\ No newline at end of file
+    /// Allocation drift (bps of total assets) tolerated before rebalancing
+    pub drift_threshold_bps: u16,
+    /// Minimum slots between two keeper rebalances
+    pub rebalance_cooldown_slots: u64,
+    pub last_rebalance_slot: u64,
+    /// USDC paid from the fee vault to a keeper that rebalances a drifted vault
+    pub keeper_tip: u64,
+    pub allocation_mode: AllocationMode,
+    pub juplend_bounds: AllocationBounds,
+    pub kamino_bounds: AllocationBounds,
+    /// Program used by `harvest` to swap farm rewards into USDC
+    pub swap_program: Pubkey,
+    pub access_mode: AccessMode,
+    /// Root of the allowlisted wallets when `access_mode` is MerkleRoot
+    pub merkle_root: [u8; 32],
+    /// Slots a deposit has to sit before the receiver can withdraw
+    pub min_holding_slots: u64,
+    /// Layout version, bumped by `migrate_config`. 0 for configs created before versioning
+    pub version: u8,
+    /// Share of the Kamino farm position `crank_kamino_farm` keeps unstaked for withdrawals
+    pub farm_liquid_buffer_bps: u16,
+    /// Kamino vault interest at the start of the window its realized APY is measured over
+    pub kamino_interest_snapshot: KaminoInterestSnapshot,
+    /// marginfi account owned by the config PDA, default until `init_marginfi_account`
+    pub marginfi_account: Pubkey,
+    /// Bps of total assets lent to the MarginFi bank, set by `update_fixed_allocation`
+    pub marginfi_allocation_bps: u16,
+    /// klend reserve the config PDA holds cTokens of directly, default until `init_klend_reserve`
+    pub klend_reserve: Pubkey,
+    /// Bps of total assets deposited straight into `klend_reserve`, skipping the Kamino vault's fees
+    pub klend_allocation_bps: u16,
+    /// Save (Solend) reserve the config PDA holds cTokens of, default until `init_save_reserve`
+    pub save_reserve: Pubkey,
+    /// Bps of total assets deposited into `save_reserve`
+    pub save_allocation_bps: u16,
+    /// USDC bank `marginfi_account` lends to, set with it by `init_marginfi_account`
+    pub marginfi_bank: Pubkey,
+    /// Space for future fields, so upgrades only have to carve from here
+    pub reserved: [u8; 32],
+}
+
+
+impl AggregatorConfig {
+    /// Targets in protocol order [JupLend, Kamino, MarginFi, klend reserve, Save reserve]
+    pub fn allocation_bps(&self) -> Vec<u16> {
+        vec![
+            self.juplend_allocation_bps,
+            self.kamino_allocation_bps,
+            self.marginfi_allocation_bps,
+            self.klend_allocation_bps,
+            self.save_allocation_bps,
+        ]
+    }
+
+    /// Sum of the targets the authority fixes by hand (MarginFi, klend reserve and Save reserve)
+    pub fn fixed_allocation_bps(&self) -> u16 {
+        self.marginfi_allocation_bps
+            .saturating_add(self.klend_allocation_bps)
+            .saturating_add(self.save_allocation_bps)
+    }
+
+    /// Target of one of the protocols the authority fixes by hand
+    pub fn fixed_allocation_bps_mut(&mut self, protocol: FixedProtocol) -> &mut u16 {
+        match protocol {
+            FixedProtocol::Marginfi => &mut self.marginfi_allocation_bps,
+            FixedProtocol::Klend => &mut self.klend_allocation_bps,
+            FixedProtocol::Save => &mut self.save_allocation_bps,
+        }
+    }
+
+    /// Bps left to JupLend and Kamino once the fixed targets are taken out
+    pub fn floating_allocation_bps(&self) -> u16 {
+        BPS_BASE.saturating_sub(self.fixed_allocation_bps())
+    }
+
+    /// Address of the config PDA, for callers that only hold the deserialized config
+    pub fn address(&self) -> Result<Pubkey> {
+        Ok(Pubkey::create_program_address(&[b"config", &[self.bump]], &crate::ID)
+            .map_err(|_| ProgramError::InvalidSeeds)?)
+    }
+}
+
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AllocationMode {
+    /// Targets are the bps set by `update_strategy`
+    Fixed,
+    /// Targets lean towards the protocol with the higher supply APY, within bounds
+    ApyDriven,
+    /// Targets equalize supply rates after accounting for our own deposits, within bounds
+    RateOptimized,
+}
+
+
+/// Protocols whose target `update_fixed_allocation` sets, JupLend and Kamino split what they leave
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FixedProtocol {
+    Marginfi,
+    /// The klend reserve held directly, outside the Kamino vault
+    Klend,
+    Save,
+}
+
+
+/// Share of total assets (in bps) a protocol may hold when targets are computed on-chain
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct AllocationBounds {
+    pub min_bps: u16,
+    pub max_bps: u16,
+}
+
+/// Kamino vault `cumulative_earned_interest_sf` at its `last_fee_charge_timestamp`, 0s until the first rebalance
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct KaminoInterestSnapshot {
+    pub cumulative_earned_interest_sf: u128,
+    pub timestamp: u64,
+}
+
+/// Who may deposit into the vault and receive the minted cUSDC
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AccessMode {
+    Permissionless,
+    /// Wallets with an `Allowlist` PDA managed by the authority
+    Allowlist,
+    /// Wallets proven against `merkle_root`
+    MerkleRoot,
+}
@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+
+/// Marks `wallet` as allowed in AccessMode::Allowlist vaults.
+/// Seeds: [b"allowlist", wallet]
+#[account]
+#[derive(InitSpace)]
+pub struct Allowlist {
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
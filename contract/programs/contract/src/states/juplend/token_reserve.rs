@@ -1,5 +1,12 @@
 use anchor_lang::prelude::*;
 
+use bytemuck::{Pod, Zeroable};
+use static_assertions::const_assert_eq;
+
+
+pub const TOKEN_RESERVE_SIZE: usize = 184;
+
+
 /// Token configuration and exchange prices
 #[repr(C, packed)]
 #[derive(Copy, Clone)]
@@ -27,3 +34,8 @@ pub struct TokenReserve {
     pub interacting_timestamp: u64,
     pub interacting_balance: u64,
 }
+
+unsafe impl Zeroable for TokenReserve {}
+unsafe impl Pod for TokenReserve {}
+
+const_assert_eq!(std::mem::size_of::<TokenReserve>(), TOKEN_RESERVE_SIZE);
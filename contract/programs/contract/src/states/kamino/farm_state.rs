@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use static_assertions::const_assert_eq;
+
+use crate::{constants::MAX_REWARDS_TOKENS, states::kamino::PodU128};
+
+
+pub const FARM_STATE_SIZE: usize = 8328;
+pub const REWARD_INFO_SIZE: usize = 704;
+
+pub const REWARD_SCHEDULE_POINTS: usize = 20;
+
+/// `time_unit` value for farms that count warmup and cooldown in slots
+pub const TIME_UNIT_SLOTS: u8 = 1;
+
+
+#[derive(Debug, PartialEq, Eq)]
+#[zero_copy(unsafe)]
+#[repr(C)]
+pub struct FarmTokenInfo {
+    pub mint: Pubkey,
+    pub decimals: u64,
+    pub token_program: Pubkey,
+    pub _padding: [u64; 6],
+}
+
+
+#[derive(Debug, PartialEq, Eq)]
+#[zero_copy(unsafe)]
+#[repr(C)]
+pub struct RewardPerTimeUnitPoint {
+    pub ts_start: u64,
+    pub reward_per_time_unit: u64,
+}
+
+
+#[derive(Debug, PartialEq, Eq)]
+#[zero_copy(unsafe)]
+#[repr(C)]
+pub struct RewardInfo {
+    pub token: FarmTokenInfo,
+    pub rewards_vault: Pubkey,
+    pub rewards_available: u64,
+    pub reward_schedule_curve: [RewardPerTimeUnitPoint; REWARD_SCHEDULE_POINTS],
+    pub min_claim_duration_seconds: u64,
+    pub last_issuance_ts: u64,
+    pub rewards_issued_unclaimed: u64,
+    pub rewards_issued_cumulative: u64,
+    /// Rewards accrued per staked share so far, scaled by WAD
+    pub reward_per_share_scaled: PodU128,
+    pub _placeholder_0: u64,
+    pub reward_type: u8,
+    pub rewards_per_second_decimals: u8,
+    pub _padding_0: [u8; 6],
+    pub _padding_1: [u64; 20],
+}
+
+
+/// Kamino farms farm, the vault's kTokens are staked in one of these
+#[derive(PartialEq, Eq)]
+#[zero_copy(unsafe)]
+#[repr(C)]
+pub struct FarmState {
+    pub farm_admin: Pubkey,
+    pub global_config: Pubkey,
+    pub token: FarmTokenInfo,
+    pub reward_infos: [RewardInfo; MAX_REWARDS_TOKENS],
+    pub num_reward_tokens: u64,
+    pub num_users: u64,
+    pub total_staked_amount: u64,
+
+    pub farm_vault: Pubkey,
+    pub farm_vaults_authority: Pubkey,
+    pub farm_vaults_authority_bump: u64,
+    pub delegate_authority: Pubkey,
+
+    /// Unit of the warmup and cooldown periods and of the users' pending timestamps, see TIME_UNIT_SLOTS
+    pub time_unit: u8,
+    pub is_farm_frozen: u8,
+    pub is_farm_delegated: u8,
+    pub is_reward_user_once_enabled: u8,
+    pub is_harvesting_permissionless: u8,
+    pub _padding_0: [u8; 3],
+
+    pub withdraw_authority: Pubkey,
+    /// Time a stake stays pending before it becomes active
+    pub deposit_warmup_period: u32,
+    /// Time an unstake stays pending before it can be withdrawn
+    pub withdrawal_cooldown_period: u32,
+
+    pub total_active_stake_scaled: PodU128,
+    pub total_pending_stake_scaled: PodU128,
+    pub total_pending_amount: u64,
+
+    pub slashed_amount_current: u64,
+    pub slashed_amount_cumulative: u64,
+    pub slashed_amount_spill_address: Pubkey,
+
+    pub locking_mode: u64,
+    pub locking_start_timestamp: u64,
+    pub locking_duration: u64,
+    pub locking_early_withdrawal_penalty_bps: u64,
+
+    pub deposit_cap_amount: u64,
+
+    pub scope_prices: Pubkey,
+    pub scope_oracle_price_id: u64,
+    pub scope_oracle_max_age: u64,
+
+    pub pending_farm_admin: Pubkey,
+    pub strategy_id: Pubkey,
+    pub delegated_rps_admin: Pubkey,
+    pub vault_id: Pubkey,
+    pub second_delegated_authority: Pubkey,
+
+    pub _padding: [u64; 74],
+}
+
+unsafe impl Zeroable for FarmState {}
+unsafe impl Pod for FarmState {}
+
+const_assert_eq!(std::mem::size_of::<FarmState>(), FARM_STATE_SIZE);
+const_assert_eq!(std::mem::size_of::<RewardInfo>(), REWARD_INFO_SIZE);
@@ -4,6 +4,9 @@ pub use vault_state::*;
 pub mod reserve;
 pub use reserve::*;
 
+pub mod pod_u128;
+pub use pod_u128::*;
+
 
 pub mod reserve_accounts;
 pub use reserve_accounts::*;
@@ -14,3 +17,6 @@ pub use fraction_extras::*;
 
 pub mod user_state;
 pub use user_state::*;
+
+pub mod farm_state;
+pub use farm_state::*;
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use bytemuck::{Pod, Zeroable};
+
+
+/// u128 kept as little-endian bytes. Solana aligns u128 to 8 bytes and the host to 16,
+/// so the typed Kamino layouts hold it as bytes to get the on-chain layout everywhere.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PodU128([u8; 16]);
+
+unsafe impl Zeroable for PodU128 {}
+unsafe impl Pod for PodU128 {}
+
+impl PodU128 {
+    pub const fn new(value: u128) -> Self {
+        Self(value.to_le_bytes())
+    }
+
+    pub const fn get(&self) -> u128 {
+        u128::from_le_bytes(self.0)
+    }
+}
+
+impl From<u128> for PodU128 {
+    fn from(value: u128) -> Self {
+        Self::new(value)
+    }
+}
+
+impl AnchorDeserialize for PodU128 {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut bytes = [0u8; 16];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl AnchorSerialize for PodU128 {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.0)
+    }
+}
+
+// Only reached through the Kamino layouts, which aren't part of our IDL
+#[cfg(feature = "idl-build")]
+impl anchor_lang::IdlBuild for PodU128 {}
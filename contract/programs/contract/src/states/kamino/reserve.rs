@@ -2,7 +2,14 @@ use anchor_lang::{
     prelude::{ Pubkey, *},
 };
 use borsh::{BorshDeserialize, BorshSerialize};
+use bytemuck::{Zeroable, Pod};
 use derivative::Derivative;
+use static_assertions::const_assert_eq;
+
+use crate::states::kamino::PodU128;
+
+
+pub const RESERVE_SIZE: usize = 8616;
 
 
 #[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Eq)]
@@ -10,13 +17,13 @@ use derivative::Derivative;
 #[repr(C)]
 pub struct LastUpdate {
 
-    slot: u64,
+    pub slot: u64,
 
-    stale: u8,
+    pub stale: u8,
 
-    price_status: u8,
+    pub price_status: u8,
 
-    placeholder: [u8; 6],
+    pub placeholder: [u8; 6],
 }
 
 
@@ -67,9 +74,9 @@ pub struct ReserveLiquidity {
 
     pub available_amount: u64,
 
-    pub borrowed_amount_sf: u128,
+    pub borrowed_amount_sf: PodU128,
 
-    pub market_price_sf: u128,
+    pub market_price_sf: PodU128,
 
     pub market_price_last_updated_ts: u64,
 
@@ -83,18 +90,18 @@ pub struct ReserveLiquidity {
 
     pub cumulative_borrow_rate_bsf: BigFractionBytes,
 
-    pub accumulated_protocol_fees_sf: u128,
+    pub accumulated_protocol_fees_sf: PodU128,
 
-    pub accumulated_referrer_fees_sf: u128,
+    pub accumulated_referrer_fees_sf: PodU128,
 
-    pub pending_referrer_fees_sf: u128,
+    pub pending_referrer_fees_sf: PodU128,
 
-    pub absolute_referral_rate_sf: u128,
+    pub absolute_referral_rate_sf: PodU128,
 
     pub token_program: Pubkey,
 
     pub padding2: [u64; 51],
-    pub padding3: [u128; 32],
+    pub padding3: [PodU128; 32],
 }
 
 
@@ -108,8 +115,8 @@ pub struct ReserveCollateral {
     pub mint_total_supply: u64,
 
     pub supply_vault: Pubkey,
-    pub padding1: [u128; 32],
-    pub padding2: [u128; 32],
+    pub padding1: [PodU128; 32],
+    pub padding2: [PodU128; 32],
 }
 
 
@@ -322,22 +329,7 @@ pub struct PythConfiguration {
 }
 
 
+unsafe impl Zeroable for Reserve {}
+unsafe impl Pod for Reserve {}
 
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
+const_assert_eq!(std::mem::size_of::<Reserve>(), RESERVE_SIZE);
@@ -1,55 +1,50 @@
 use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use static_assertions::const_assert_eq;
 
-use crate::constants::MAX_REWARDS_TOKENS;
+use crate::{constants::MAX_REWARDS_TOKENS, states::kamino::PodU128};
 
 
+pub const USER_STATE_SIZE: usize = 912;
 
 
-#[derive(Debug, Eq, PartialEq)]
+/// Kamino farms user stake, stake amounts and tallies are scaled by WAD
+#[derive(Debug, PartialEq, Eq)]
+#[zero_copy(unsafe)]
+#[repr(C)]
 pub struct UserState {
     pub user_id: u64,
     pub farm_state: Pubkey,
     pub owner: Pubkey,
 
-
     pub is_farm_delegated: u8,
     pub _padding_0: [u8; 7],
 
-
-
-    pub rewards_tally_scaled: [u128; MAX_REWARDS_TOKENS],
-
+    pub rewards_tally_scaled: [PodU128; MAX_REWARDS_TOKENS],
     pub rewards_issued_unclaimed: [u64; MAX_REWARDS_TOKENS],
     pub last_claim_ts: [u64; MAX_REWARDS_TOKENS],
 
+    /// Staked and earning rewards
+    pub active_stake_scaled: PodU128,
 
-
-    pub active_stake_scaled: u128,
-
-
-
-    pub pending_deposit_stake_scaled: u128,
-
-
+    /// Staked but still in the farm's deposit warmup
+    pub pending_deposit_stake_scaled: PodU128,
     pub pending_deposit_stake_ts: u64,
 
-
-
-    pub pending_withdrawal_unstake_scaled: u128,
-
+    /// Unstaked but still in the farm's withdrawal cooldown, withdrawable from `pending_withdrawal_unstake_ts`
+    pub pending_withdrawal_unstake_scaled: PodU128,
     pub pending_withdrawal_unstake_ts: u64,
 
     pub bump: u64,
-
     pub delegatee: Pubkey,
-
     pub last_stake_ts: u64,
 
-
-
-
-
     pub rewards_issued_cumulative: [u64; MAX_REWARDS_TOKENS],
 
     pub _padding_1: [u64; 40],
-}
\ No newline at end of file
+}
+
+unsafe impl Zeroable for UserState {}
+unsafe impl Pod for UserState {}
+
+const_assert_eq!(std::mem::size_of::<UserState>(), USER_STATE_SIZE);
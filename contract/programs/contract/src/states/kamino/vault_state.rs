@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
 
 use bytemuck::{Zeroable, Pod};
+use static_assertions::const_assert_eq;
+
+use crate::states::kamino::PodU128;
 
 pub const MAX_RESERVES: usize = 25;
 pub const VAULT_STATE_SIZE: usize = 62544;
@@ -72,24 +75,22 @@ impl AnchorSerialize for ConfigPadding127 {
 
 #[repr(transparent)]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub struct Padding242([u128; 242]);
+pub struct Padding242([PodU128; 242]);
 
 unsafe impl Zeroable for Padding242 {}
 unsafe impl Pod for Padding242 {}
 
 impl Default for Padding242 {
     fn default() -> Self {
-        Self([0u128; 242])
+        Self([PodU128::default(); 242])
     }
 }
 
 impl AnchorDeserialize for Padding242 {
     fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
-        let mut array = [0u128; 242];
+        let mut array = [PodU128::default(); 242];
         for item in array.iter_mut() {
-            let mut bytes = [0u8; 16];
-            reader.read_exact(&mut bytes)?;
-            *item = u128::from_le_bytes(bytes);
+            *item = PodU128::deserialize_reader(reader)?;
         }
         Ok(Self(array))
     }
@@ -98,7 +99,7 @@ impl AnchorDeserialize for Padding242 {
 impl AnchorSerialize for Padding242 {
     fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
         for item in self.0.iter() {
-            writer.write_all(&item.to_le_bytes())?;
+            item.serialize(writer)?;
         }
         Ok(())
     }
@@ -140,24 +141,22 @@ impl AnchorSerialize for StatePadding128 {
 
 #[repr(transparent)]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub struct Padding256([u128; 256]);
+pub struct Padding256([PodU128; 256]);
 
 unsafe impl Zeroable for Padding256 {}
 unsafe impl Pod for Padding256 {}
 
 impl Default for Padding256 {
     fn default() -> Self {
-        Self([0u128; 256])
+        Self([PodU128::default(); 256])
     }
 }
 
 impl AnchorDeserialize for Padding256 {
     fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
-        let mut array = [0u128; 256];
+        let mut array = [PodU128::default(); 256];
         for item in array.iter_mut() {
-            let mut bytes = [0u8; 16];
-            reader.read_exact(&mut bytes)?;
-            *item = u128::from_le_bytes(bytes);
+            *item = PodU128::deserialize_reader(reader)?;
         }
         Ok(Self(array))
     }
@@ -166,7 +165,7 @@ impl AnchorDeserialize for Padding256 {
 impl AnchorSerialize for Padding256 {
     fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
         for item in self.0.iter() {
-            writer.write_all(&item.to_le_bytes())?;
+            item.serialize(writer)?;
         }
         Ok(())
     }
@@ -175,7 +174,7 @@ impl AnchorSerialize for Padding256 {
 
 
 #[repr(C)]
-#[derive(AnchorDeserialize, PartialEq, Eq)]
+#[derive(AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub struct VaultState {
     // Admin
     pub vault_admin_authority: Pubkey,
@@ -202,9 +201,9 @@ pub struct VaultState {
     pub performance_fee_bps: u64,
     pub management_fee_bps: u64,
     pub last_fee_charge_timestamp: u64,
-    pub prev_aum_sf: u128,
+    pub prev_aum_sf: PodU128,
     // todo: should we split this into pending_mgmt_fee and pending_perf_fee?
-    pub pending_fees_sf: u128,
+    pub pending_fees_sf: PodU128,
 
     pub vault_allocation_strategy: [VaultAllocation; MAX_RESERVES],
     pub padding_1: Padding256,
@@ -218,12 +217,11 @@ pub struct VaultState {
 
     pub pending_admin: Pubkey,
 
-    pub cumulative_earned_interest_sf: u128, // this represents the raw total interest earned by the vault, including the fees
-    pub cumulative_mgmt_fees_sf: u128,
-    pub cumulative_perf_fees_sf: u128,
+    pub cumulative_earned_interest_sf: PodU128, // this represents the raw total interest earned by the vault, including the fees
+    pub cumulative_mgmt_fees_sf: PodU128,
+    pub cumulative_perf_fees_sf: PodU128,
 
     pub name: Name,
-    pub name_padding: [u8; 8],  // Explicit padding after Name to align next Pubkey
     pub vault_lookup_table: Pubkey,
     pub vault_farm: Pubkey,
 
@@ -239,7 +237,7 @@ pub struct VaultState {
 
 
 #[repr(C)]
-#[derive(AnchorDeserialize, Debug, PartialEq, Eq)]
+#[derive(AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct VaultAllocation {
     pub reserve: Pubkey,
     pub ctoken_vault: Pubkey,
@@ -253,10 +251,16 @@ pub struct VaultAllocation {
 
     pub ctoken_allocation: u64,
     pub last_invest_slot: u64,
-    pub token_target_allocation_sf: u128,
+    pub token_target_allocation_sf: PodU128,
 
     pub state_padding: StatePadding128,
 }
 
+unsafe impl Zeroable for VaultState {}
+unsafe impl Pod for VaultState {}
+
+unsafe impl Zeroable for VaultAllocation {}
+unsafe impl Pod for VaultAllocation {}
 
-  
\ No newline at end of file
+const_assert_eq!(std::mem::size_of::<VaultState>(), VAULT_STATE_SIZE);
+const_assert_eq!(std::mem::size_of::<VaultAllocation>(), VAULT_ALLOCATION_SIZE);
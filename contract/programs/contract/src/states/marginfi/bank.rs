@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::states::marginfi::WrappedI80F48;
+
+
+/// Leading fields of marginfi's BankConfig, up to the deposit limit
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BankConfig {
+    pub asset_weight_init: WrappedI80F48,
+    pub asset_weight_maint: WrappedI80F48,
+    pub liability_weight_init: WrappedI80F48,
+    pub liability_weight_maint: WrappedI80F48,
+
+    /// In native token units, 0 for no limit
+    pub deposit_limit: u64,
+}
+
+
+/// Leading fields of a marginfi Bank, the rest of the 1856-byte account isn't read
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bank {
+    pub mint: Pubkey,
+    pub mint_decimals: u8,
+    pub group: Pubkey,
+    pub _pad0: [u8; 7],
+
+    /// Native tokens one asset share is worth, grows as interest accrues
+    pub asset_share_value: WrappedI80F48,
+    pub liability_share_value: WrappedI80F48,
+
+    pub liquidity_vault: Pubkey,
+    pub liquidity_vault_bump: u8,
+    pub liquidity_vault_authority_bump: u8,
+
+    pub insurance_vault: Pubkey,
+    pub insurance_vault_bump: u8,
+    pub insurance_vault_authority_bump: u8,
+    pub _pad1: [u8; 4],
+    pub collected_insurance_fees_outstanding: WrappedI80F48,
+
+    pub fee_vault: Pubkey,
+    pub fee_vault_bump: u8,
+    pub fee_vault_authority_bump: u8,
+    pub _pad2: [u8; 6],
+    pub collected_group_fees_outstanding: WrappedI80F48,
+
+    pub total_liability_shares: WrappedI80F48,
+    pub total_asset_shares: WrappedI80F48,
+
+    pub last_update: i64,
+
+    pub config: BankConfig,
+}
+
+unsafe impl Zeroable for BankConfig {}
+unsafe impl Pod for BankConfig {}
+
+unsafe impl Zeroable for Bank {}
+unsafe impl Pod for Bank {}
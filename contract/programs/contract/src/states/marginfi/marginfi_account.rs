@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::states::marginfi::WrappedI80F48;
+
+
+pub const MAX_LENDING_ACCOUNT_BALANCES: usize = 16;
+
+
+/// One bank position of a marginfi account
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Balance {
+    pub active: u8,
+    pub bank_pk: Pubkey,
+    pub bank_asset_tag: u8,
+    pub _pad0: [u8; 6],
+
+    pub asset_shares: WrappedI80F48,
+    pub liability_shares: WrappedI80F48,
+    pub emissions_outstanding: WrappedI80F48,
+
+    pub last_update: u64,
+    pub _padding: [u64; 1],
+}
+
+
+/// Leading fields of a MarginfiAccount, through its balances
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MarginfiAccount {
+    pub group: Pubkey,
+    pub authority: Pubkey,
+    pub balances: [Balance; MAX_LENDING_ACCOUNT_BALANCES],
+}
+
+unsafe impl Zeroable for Balance {}
+unsafe impl Pod for Balance {}
+
+unsafe impl Zeroable for MarginfiAccount {}
+unsafe impl Pod for MarginfiAccount {}
@@ -0,0 +1,8 @@
+pub mod wrapped_i80f48;
+pub use wrapped_i80f48::*;
+
+pub mod bank;
+pub use bank::*;
+
+pub mod marginfi_account;
+pub use marginfi_account::*;
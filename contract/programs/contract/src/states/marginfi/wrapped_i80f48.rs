@@ -0,0 +1,25 @@
+use bytemuck::{Pod, Zeroable};
+use fixed::types::I80F48;
+
+
+/// marginfi's fixed-point I80F48 as stored on chain, 16 little-endian bytes
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct WrappedI80F48 {
+    pub value: [u8; 16],
+}
+
+unsafe impl Zeroable for WrappedI80F48 {}
+unsafe impl Pod for WrappedI80F48 {}
+
+impl WrappedI80F48 {
+    pub fn get(&self) -> I80F48 {
+        I80F48::from_le_bytes(self.value)
+    }
+}
+
+impl From<I80F48> for WrappedI80F48 {
+    fn from(value: I80F48) -> Self {
+        Self { value: value.to_le_bytes() }
+    }
+}
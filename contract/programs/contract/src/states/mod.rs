@@ -1,8 +1,23 @@
 pub mod aggregator_config;
 pub use aggregator_config::*;
 
+pub mod operator_approval;
+pub use operator_approval::*;
+
+pub mod allowlist;
+pub use allowlist::*;
+
+pub mod user_position;
+pub use user_position::*;
+
 pub mod juplend;
 pub use juplend::*;
 
 pub mod kamino;
-pub use kamino::*;
\ No newline at end of file
+pub use kamino::*;
+
+pub mod marginfi;
+pub use marginfi::*;
+
+pub mod vault_view;
+pub use vault_view::*;
@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+
+/// Lets `operator` redeem up to `allowance` of `owner`'s cUSDC.
+/// Seeds: [b"operator", owner, operator]. The PDA is the SPL delegate it burns through.
+#[account]
+#[derive(InitSpace)]
+pub struct OperatorApproval {
+    pub owner: Pubkey,
+    pub operator: Pubkey,
+    /// cUSDC the operator can still burn on the owner's behalf
+    pub allowance: u64,
+    pub bump: u8,
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+
+/// cUSDC minted to `owner` that is still inside the holding period.
+/// Seeds: [b"position", owner]
+#[account]
+#[derive(InitSpace)]
+pub struct UserPosition {
+    pub owner: Pubkey,
+    /// Slot of the last deposit that minted cUSDC to `owner`, whoever paid for it
+    pub last_deposit_slot: u64,
+    pub bump: u8,
+    /// cUSDC minted to `owner` since the lock last expired, locked until
+    /// `last_deposit_slot + min_holding_slots`
+    pub locked_shares: u64,
+}
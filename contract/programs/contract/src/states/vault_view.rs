@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::events::UnclaimedReward;
+
+
+/// Returned by `view_vault` (and inside `UserView`) through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VaultView {
+    /// Idle + every protocol, in USDC
+    pub total_assets: u64,
+    pub juplend_balance: u64,
+    pub kamino_balance: u64,
+    pub marginfi_balance: u64,
+    /// USDC behind the cTokens held directly in the klend reserve
+    pub klend_balance: u64,
+    /// USDC behind the cTokens held in the Save reserve
+    pub save_balance: u64,
+    /// USDC sitting in the vault, not deployed
+    pub idle_usdc: u64,
+    pub total_shares: u64,
+    /// USDC per cUSDC, scaled by SHARE_PRICE_PRECISION
+    pub share_price: u64,
+    pub juplend_apy_bps: u64,
+    /// Net of the Kamino vault's performance and management fees
+    pub kamino_apy_bps: u64,
+    pub juplend_bps: u16,
+    pub kamino_bps: u16,
+    pub marginfi_bps: u16,
+    pub klend_bps: u16,
+    pub save_bps: u16,
+}
+
+
+/// Returned by `preview_allocation` through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllocationPreview {
+    /// Idle + every protocol, in USDC
+    pub total_assets: u64,
+    /// Targets the next rebalance would move to, the stored ones in Fixed mode
+    pub juplend_bps: u16,
+    pub kamino_bps: u16,
+    pub marginfi_bps: u16,
+    pub klend_bps: u16,
+    pub save_bps: u16,
+    /// Rates behind the split, after our own move in RateOptimized mode. Zero in Fixed mode
+    pub juplend_apy_bps: u64,
+    pub kamino_apy_bps: u64,
+}
+
+
+/// Returned by `view` through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserView {
+    pub vault: VaultView,
+    pub user: Pubkey,
+    pub user_shares: u64,
+    /// What `user_shares` redeem for right now, in USDC
+    pub user_value: u64,
+    pub unclaimed_rewards: Vec<UnclaimedReward>,
+    pub juplend_rewards_apr_bps: u64,
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked
+};
+
+
+declare_id!("3wMXMpwBi7TjTxhrMuDA8mJeyen1g24FZkDctm5dBCVC");
+
+/*
+   Test-only swap: takes `amount_in` of the input mint and pays the same amount of
+   whole tokens of the output mint out of a pool owned by the `pool` PDA.
+   The pool's output account has to be funded by the test setup.
+*/
+#[program]
+pub mod mock_swap {
+    use super::*;
+
+    pub fn swap(ctx: Context<Swap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+        ctx.accounts.swap(amount_in, min_amount_out, ctx.bumps)
+    }
+}
+
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    /// Owner of `source` or its delegate, the token program checks which
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = input_mint,
+        token::token_program = input_token_program,
+    )]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = output_mint,
+        token::token_program = output_token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns the pool token accounts
+    #[account(
+        seeds = [b"pool"],
+        bump
+    )]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = input_mint,
+        token::authority = pool_authority,
+        token::token_program = input_token_program,
+    )]
+    pub pool_input: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = output_mint,
+        token::authority = pool_authority,
+        token::token_program = output_token_program,
+    )]
+    pub pool_output: InterfaceAccount<'info, TokenAccount>,
+
+    pub input_mint: InterfaceAccount<'info, Mint>,
+    pub output_mint: InterfaceAccount<'info, Mint>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+}
+
+
+impl<'info> Swap<'info> {
+    pub fn swap(&mut self, amount_in: u64, min_amount_out: u64, bumps: SwapBumps) -> Result<()> {
+        // Same number of whole tokens on both sides
+        let amount_out = (amount_in as u128)
+            .checked_mul(10u128.pow(self.output_mint.decimals as u32))
+            .and_then(|v| v.checked_div(10u128.pow(self.input_mint.decimals as u32)))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(MockSwapError::MathOverflow)?;
+
+        require!(amount_out >= min_amount_out, MockSwapError::SlippageExceeded);
+
+        transfer_checked(
+            CpiContext::new(
+                self.input_token_program.to_account_info(),
+                TransferChecked {
+                    from: self.source.to_account_info(),
+                    to: self.pool_input.to_account_info(),
+                    authority: self.owner.to_account_info(),
+                    mint: self.input_mint.to_account_info(),
+                },
+            ),
+            amount_in,
+            self.input_mint.decimals,
+        )?;
+
+        let seeds = &[b"pool".as_ref(), &[bumps.pool_authority]];
+        let signer = &[&seeds[..]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.output_token_program.to_account_info(),
+                TransferChecked {
+                    from: self.pool_output.to_account_info(),
+                    to: self.destination.to_account_info(),
+                    authority: self.pool_authority.to_account_info(),
+                    mint: self.output_mint.to_account_info(),
+                },
+                signer,
+            ),
+            amount_out,
+            self.output_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
+
+
+#[error_code]
+pub enum MockSwapError {
+    #[msg("Math overflow detected")]
+    MathOverflow,
+    #[msg("Swap returned less than the minimum amount out")]
+    SlippageExceeded,
+}
@@ -0,0 +1,149 @@
+// Captures the golden vectors `helpers/golden_vectors.rs` checks the balance readers against.
+//
+// Run against a Surfpool mainnet fork:
+//
+//   surfpool start
+//   npx ts-node scripts/capture-golden-vectors.ts
+//
+// The fork is moved SKIP_SLOTS ahead so the accounts go stale, the stale accounts are read, and
+// klend's `refresh_reserve` and JupLend's `update_rate` run in one transaction. The accounts before
+// and after are written to tests/fixtures/golden, so the Rust tests can check that our readers
+// run on the stale accounts land on the prices the programs wrote.
+
+import { Connection, Keypair, PublicKey, Transaction, TransactionInstruction, sendAndConfirmTransaction } from "@solana/web3.js";
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+
+const RPC_URL = process.env.RPC_URL ?? "http://127.0.0.1:8899";
+const WALLET = process.env.ANCHOR_WALLET ?? path.join(os.homedir(), ".config/solana/id.json");
+// About a day of slots
+const SKIP_SLOTS = Number(process.env.SKIP_SLOTS ?? 216_000);
+// Vault shares priced in the Kamino vector
+const KAMINO_SHARES = 1_000_000_000_000;
+
+const OUT_DIR = path.join(__dirname, "../tests/fixtures/golden");
+
+// Same accounts as tests/contract.ts
+const usdcMint = new PublicKey("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+const lendingProgram = new PublicKey("jup3YeL8QhtSx1e253b2FDvsMNC87fDrgQZivbrndc9");
+const jupLending = new PublicKey("2vVYHYM8VYnvZqQWpTJSj8o8DBf1wM8pVs3bsTgYZiqJ");
+const jlusdcMint = new PublicKey("9BEcn9aPEmhSPbPQeFGjidRiEKki46fVQDyPpSQXPA2D");
+const supplyTokenReserveLiquidity = new PublicKey("94vK29npVbyRHXH63rRcTiSr26SFhrQTzbpNJuhQEDu");
+const rewardsRateModel = new PublicKey("5xSPBiD3TibamAnwHDhZABdB4z4F9dcj5PnbteroBTTd");
+const klendProgram = new PublicKey("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+const vaultState = new PublicKey("HDsayqAsDWy3QvANGqh2yNraqcD8Fnjgh73Mhb3WRS5E");
+const vaultReserves = [
+  new PublicKey("Ga4rZytCpq1unD4DbEJ5bkHeUz9g3oh9AAFEi6vSauXp"),
+  new PublicKey("D6q6wuQSrifJKZYpR1M8R4YawnLDtDsMmWM1NbBmgJ59"),
+];
+
+const UPDATE_RATE_DISCRIMINATOR = Buffer.from([24, 225, 53, 189, 72, 212, 225, 178]);
+const REFRESH_RESERVE_DISCRIMINATOR = Buffer.from([2, 218, 138, 235, 79, 201, 25, 102]);
+
+// Reserve offsets, discriminator included
+const RESERVE_LENDING_MARKET_OFFSET = 32;
+// pyth, switchboard price, switchboard twap, scope
+const RESERVE_ORACLE_OFFSETS = [5224, 5160, 5192, 5112];
+
+// Surfpool cheatcodes aren't in web3.js, send them through its raw RPC client
+const rpc = async (connection: Connection, method: string, params: unknown[]) => {
+  const response = await (connection as any)._rpcRequest(method, params);
+  if (response.error) {
+    throw new Error(`${method}: ${JSON.stringify(response.error)}`);
+  }
+  return response.result;
+};
+
+const readAccounts = async (connection: Connection, keys: PublicKey[]) => {
+  const infos = await connection.getMultipleAccountsInfo(keys);
+  return infos.map((info, i) => {
+    if (!info) {
+      throw new Error(`Account ${keys[i].toBase58()} not found`);
+    }
+    return { pubkey: keys[i].toBase58(), data: info.data.toString("base64") };
+  });
+};
+
+const refreshReserveIx = (reserve: PublicKey, data: Buffer) => {
+  const readPubkey = (offset: number) => new PublicKey(data.subarray(offset, offset + 32));
+  const oracles = RESERVE_ORACLE_OFFSETS.map((offset) => {
+    const oracle = readPubkey(offset);
+    return oracle.equals(PublicKey.default) ? klendProgram : oracle;
+  });
+
+  return new TransactionInstruction({
+    programId: klendProgram,
+    keys: [
+      { pubkey: reserve, isSigner: false, isWritable: true },
+      { pubkey: readPubkey(RESERVE_LENDING_MARKET_OFFSET), isSigner: false, isWritable: false },
+      ...oracles.map((oracle) => ({ pubkey: oracle, isSigner: false, isWritable: false })),
+    ],
+    data: REFRESH_RESERVE_DISCRIMINATOR,
+  });
+};
+
+const updateRateIx = () =>
+  new TransactionInstruction({
+    programId: lendingProgram,
+    keys: [
+      { pubkey: jupLending, isSigner: false, isWritable: true },
+      { pubkey: usdcMint, isSigner: false, isWritable: false },
+      { pubkey: jlusdcMint, isSigner: false, isWritable: false },
+      { pubkey: supplyTokenReserveLiquidity, isSigner: false, isWritable: false },
+      { pubkey: rewardsRateModel, isSigner: false, isWritable: false },
+    ],
+    data: UPDATE_RATE_DISCRIMINATOR,
+  });
+
+const main = async () => {
+  const connection = new Connection(RPC_URL, "confirmed");
+  const payer = Keypair.fromSecretKey(Uint8Array.from(JSON.parse(fs.readFileSync(WALLET, "utf8"))));
+
+  const slot = await connection.getSlot();
+  await rpc(connection, "surfnet_timeTravel", [{ absoluteSlot: slot + SKIP_SLOTS }]);
+
+  const juplendKeys = [jupLending, supplyTokenReserveLiquidity, rewardsRateModel, jlusdcMint];
+  const [vaultBefore] = await readAccounts(connection, [vaultState]);
+  const reservesBefore = await readAccounts(connection, vaultReserves);
+  const [lendingBefore, tokenReserveBefore, rewardsRateModelBefore, fTokenMintBefore] = await readAccounts(connection, juplendKeys);
+
+  const transaction = new Transaction().add(
+    ...vaultReserves.map((reserve, i) => refreshReserveIx(reserve, Buffer.from(reservesBefore[i].data, "base64"))),
+    updateRateIx(),
+  );
+  const signature = await sendAndConfirmTransaction(connection, transaction, [payer]);
+
+  const reservesAfter = await readAccounts(connection, vaultReserves);
+  const [lendingAfter] = await readAccounts(connection, [jupLending]);
+  const capturedSlot = (await connection.getTransaction(signature, { maxSupportedTransactionVersion: 0 })).slot;
+
+  fs.mkdirSync(OUT_DIR, { recursive: true });
+  const write = (name: string, vector: object) => {
+    const file = path.join(OUT_DIR, `${name}-${capturedSlot}.json`);
+    fs.writeFileSync(file, JSON.stringify({ slot: capturedSlot, signature, ...vector }, null, 2) + "\n");
+    console.log(`Wrote ${file}`);
+  };
+
+  write("kamino", {
+    kind: "kamino",
+    shares: KAMINO_SHARES,
+    before: { vault_state: vaultBefore.data, reserves: reservesBefore },
+    after: { reserves: reservesAfter },
+  });
+  write("juplend", {
+    kind: "juplend",
+    before: {
+      lending: lendingBefore.data,
+      token_reserve: tokenReserveBefore.data,
+      rewards_rate_model: rewardsRateModelBefore.data,
+      f_token_mint: fTokenMintBefore.data,
+    },
+    after: { lending: lendingAfter.data },
+  });
+};
+
+main().catch((err) => {
+  console.error(err);
+  process.exit(1);
+});
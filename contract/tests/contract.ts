@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Contract } from "../target/types/contract";
 import { AccountMeta, AddressLookupTableAccount, AddressLookupTableProgram, ComputeBudgetProgram, Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram, SYSVAR_INSTRUCTIONS_PUBKEY, SYSVAR_RENT_PUBKEY, TransactionInstruction, TransactionMessage, VersionedTransaction } from "@solana/web3.js";
-import { ASSOCIATED_TOKEN_PROGRAM_ID, createAssociatedTokenAccount, getAssociatedTokenAddressSync, getOrCreateAssociatedTokenAccount, TOKEN_PROGRAM_ID, transferChecked } from "@solana/spl-token";
+import { ASSOCIATED_TOKEN_PROGRAM_ID, createAssociatedTokenAccount, getAccount, getAssociatedTokenAddressSync, getOrCreateAssociatedTokenAccount, getTokenMetadata, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID, transferChecked } from "@solana/spl-token";
 import { BN } from "bn.js";
 import * as os from "os";
 import * as path from "path";
@@ -13,6 +13,7 @@ describe("contract", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
 
   const program = anchor.workspace.contract as Program<Contract>;
+  const MOCK_SWAP_PROGRAM_ID = new PublicKey("3wMXMpwBi7TjTxhrMuDA8mJeyen1g24FZkDctm5dBCVC");
   const provider = anchor.getProvider();
 
   let owner: Keypair;
@@ -23,6 +24,8 @@ describe("contract", () => {
   let signerUSDC: PublicKey;
   let signerCUSDC: PublicKey;
   let vaultUSDC: PublicKey;
+  let feeVault: PublicKey;
+  let signerPosition: PublicKey;
 
   let ownerUSDC: PublicKey;
 
@@ -85,14 +88,49 @@ describe("contract", () => {
 
   let instructionSysvar: PublicKey;
 
+
+  // MarginFi Protocol accounts
+
+  let marginfiProgram: PublicKey;
+  let marginfiGroup: PublicKey;
+  let marginfiBank: PublicKey;
+  let marginfiLiquidityVault: PublicKey;
+  let marginfiLiquidityVaultAuthority: PublicKey;
+  let marginfiBankOracle: PublicKey;
+  let marginfiAccount: Keypair;
+
+  // Direct klend reserve accounts
+
+  let klendReserve: PublicKey;
+  let klendLendingMarket: PublicKey;
+  let klendLendingMarketAuthority: PublicKey;
+  let klendReserveLiquiditySupply: PublicKey;
+  let klendReserveCollateralMint: PublicKey;
+  let klendCtokenAccount: PublicKey;
+  let klendOracles: PublicKey[];
+
+  // Save reserve accounts
+
+  let saveProgram: PublicKey;
+  let saveReserve: PublicKey;
+  let saveLendingMarket: PublicKey;
+  let saveLendingMarketAuthority: PublicKey;
+  let saveReserveLiquiditySupply: PublicKey;
+  let saveReserveCollateralMint: PublicKey;
+  let saveCtokenAccount: PublicKey;
+  let saveOracles: PublicKey[];
+
   let jupLendingAccounts: AccountMeta[];
   let kaminoAccounts: AccountMeta[];
+  let marginfiAccounts: AccountMeta[];
+  let klendAccounts: AccountMeta[];
+  let saveAccounts: AccountMeta[];
 
   // Event listeners
   let eventListeners: Array<number> = [];
   let capturedEvents: Array<any> = [];
 
-  const setupEventListener = (eventName: "depositEvent" | "withdrawEvent" | "rebalanceEvent" | "allocationUpdateEvent" | "viewEvent") => {
+  const setupEventListener = (eventName: "depositEvent" | "withdrawEvent" | "rebalanceEvent" | "allocationUpdateEvent" | "allocationModeUpdateEvent" | "rebalanceParamsUpdateEvent" | "keeperTipEvent" | "harvestEvent" | "swapProgramUpdateEvent" | "operatorApprovalEvent" | "accessModeUpdateEvent" | "allowlistUpdateEvent" | "holdingPeriodUpdateEvent" | "shareMetadataUpdateEvent" | "farmBufferUpdateEvent" | "farmUnstakeEvent" | "marginfiAccountInitEvent" | "klendReserveInitEvent" | "saveReserveInitEvent" | "viewEvent") => {
     const listener = program.addEventListener(eventName, (event, slot, signature) => {
       capturedEvents.push({
         name: eventName,
@@ -150,14 +188,86 @@ describe("contract", () => {
         const evt = eventData.event;
         console.log(`│   JupLend Balance: ${String(evt.juplendBalance).padEnd(52)} │`);
         console.log(`│   Kamino Balance: ${String(evt.kaminoBalance).padEnd(53)} │`);
+        console.log(`│   MarginFi Balance: ${String(evt.marginfiBalance).padEnd(51)} │`);
+        console.log(`│   klend Balance: ${String(evt.klendBalance).padEnd(54)} │`);
+        console.log(`│   Save Balance: ${String(evt.saveBalance).padEnd(55)} │`);
+        console.log(`│   JupLend APY BPS: ${String(evt.juplendApyBps).padEnd(52)} │`);
+        console.log(`│   Kamino APY BPS: ${String(evt.kaminoApyBps).padEnd(53)} │`);
       } else if (eventData.name === "allocationUpdateEvent") {
         const evt = eventData.event;
         console.log(`│   JupLend BPS: ${String(evt.juplendBps).padEnd(56)} │`);
         console.log(`│   Kamino BPS: ${String(evt.kaminoBps).padEnd(57)} │`);
+        console.log(`│   MarginFi BPS: ${String(evt.marginfiBps).padEnd(55)} │`);
+        console.log(`│   klend BPS: ${String(evt.klendBps).padEnd(58)} │`);
+        console.log(`│   Save BPS: ${String(evt.saveBps).padEnd(59)} │`);
+      } else if (eventData.name === "allocationModeUpdateEvent") {
+        const evt = eventData.event;
+        console.log(`│   Mode: ${JSON.stringify(evt.allocationMode).padEnd(63)} │`);
+        console.log(`│   JupLend Bounds: ${`${evt.juplendBounds.minBps}-${evt.juplendBounds.maxBps}`.padEnd(53)} │`);
+        console.log(`│   Kamino Bounds: ${`${evt.kaminoBounds.minBps}-${evt.kaminoBounds.maxBps}`.padEnd(54)} │`);
+      } else if (eventData.name === "rebalanceParamsUpdateEvent") {
+        const evt = eventData.event;
+        console.log(`│   Drift Threshold BPS: ${String(evt.driftThresholdBps).padEnd(48)} │`);
+        console.log(`│   Cooldown Slots: ${String(evt.rebalanceCooldownSlots).padEnd(53)} │`);
+        console.log(`│   Keeper Tip: ${String(evt.keeperTip).padEnd(57)} │`);
+      } else if (eventData.name === "keeperTipEvent") {
+        const evt = eventData.event;
+        console.log(`│   Keeper: ${String(evt.keeper).substring(0, 59).padEnd(59)} │`);
+        console.log(`│   Drift BPS: ${String(evt.driftBps).padEnd(58)} │`);
+        console.log(`│   Tip: ${String(evt.tip).padEnd(64)} │`);
+      } else if (eventData.name === "harvestEvent") {
+        const evt = eventData.event;
+        console.log(`│   Reward Mint: ${String(evt.rewardMint).substring(0, 56).padEnd(56)} │`);
+        console.log(`│   Reward Harvested: ${String(evt.rewardHarvested).padEnd(51)} │`);
+        console.log(`│   Reward Swapped: ${String(evt.rewardSwapped).padEnd(53)} │`);
+        console.log(`│   USDC Received: ${String(evt.usdcReceived).padEnd(54)} │`);
+      } else if (eventData.name === "swapProgramUpdateEvent") {
+        const evt = eventData.event;
+        console.log(`│   Swap Program: ${String(evt.swapProgram).substring(0, 55).padEnd(55)} │`);
+      } else if (eventData.name === "operatorApprovalEvent") {
+        const evt = eventData.event;
+        console.log(`│   Owner: ${String(evt.owner).substring(0, 61).padEnd(61)} │`);
+        console.log(`│   Operator: ${String(evt.operator).substring(0, 58).padEnd(58)} │`);
+        console.log(`│   Allowance: ${String(evt.allowance).padEnd(57)} │`);
+      } else if (eventData.name === "accessModeUpdateEvent") {
+        const evt = eventData.event;
+        console.log(`│   Access Mode: ${JSON.stringify(evt.accessMode).padEnd(56)} │`);
+      } else if (eventData.name === "allowlistUpdateEvent") {
+        const evt = eventData.event;
+        console.log(`│   Wallet: ${String(evt.wallet).substring(0, 60).padEnd(60)} │`);
+        console.log(`│   Allowed: ${String(evt.allowed).padEnd(59)} │`);
+      } else if (eventData.name === "holdingPeriodUpdateEvent") {
+        const evt = eventData.event;
+        console.log(`│   Min Holding Slots: ${String(evt.minHoldingSlots).padEnd(51)} │`);
+      } else if (eventData.name === "shareMetadataUpdateEvent") {
+        const evt = eventData.event;
+        console.log(`│   Name: ${String(evt.name).substring(0, 60).padEnd(60)} │`);
+        console.log(`│   Symbol: ${String(evt.symbol).padEnd(58)} │`);
+      } else if (eventData.name === "farmBufferUpdateEvent") {
+        const evt = eventData.event;
+        console.log(`│   Farm Liquid Buffer BPS: ${String(evt.farmLiquidBufferBps).padEnd(45)} │`);
+      } else if (eventData.name === "farmUnstakeEvent") {
+        const evt = eventData.event;
+        console.log(`│   Shares Withdrawn: ${String(evt.sharesWithdrawn).padEnd(51)} │`);
+        console.log(`│   Shares Unstaked: ${String(evt.sharesUnstaked).padEnd(52)} │`);
+        console.log(`│   Shares Restaked: ${String(evt.sharesRestaked).padEnd(52)} │`);
+        console.log(`│   Withdrawal Cooldown: ${String(evt.withdrawalCooldown).padEnd(48)} │`);
+      } else if (eventData.name === "marginfiAccountInitEvent") {
+        const evt = eventData.event;
+        console.log(`│   MarginFi Account: ${String(evt.marginfiAccount).substring(0, 50)} │`);
+        console.log(`│   Bank: ${String(evt.bank).substring(0, 62).padEnd(62)} │`);
+      } else if (eventData.name === "klendReserveInitEvent" || eventData.name === "saveReserveInitEvent") {
+        const evt = eventData.event;
+        console.log(`│   Reserve: ${String(evt.reserve).substring(0, 59).padEnd(59)} │`);
+        console.log(`│   cToken Account: ${String(evt.ctokenAccount).substring(0, 52).padEnd(52)} │`);
       } else if (eventData.name === "viewEvent") {
         const evt = eventData.event;
         console.log(`│   User: ${String(evt.user).substring(0, 57)} │`);
         console.log(`│   User Yeild: ${String(evt.userYeild).padEnd(55)} │`);
+        console.log(`│   JupLend Rewards APR BPS: ${String(evt.juplendRewardsAprBps).padEnd(44)} │`);
+        for (const reward of evt.unclaimedRewards) {
+          console.log(`│   Unclaimed ${String(reward.rewardMint).substring(0, 12)}...: ${`${reward.userAmount} / ${reward.amount}`.padEnd(43)} │`);
+        }
       }
       
       console.log("└" + "─".repeat(78) + "┘");
@@ -217,6 +327,112 @@ describe("contract", () => {
     }
   }
 
+  // Reads the return data of the last instruction through simulation
+  const simulateReturnBuffer = async (transaction: VersionedTransaction): Promise<Buffer> => {
+    const simulation = await provider.connection.simulateTransaction(transaction, { commitment: 'confirmed' });
+    if (simulation.value.err) {
+      simulation.value.logs?.forEach((log, idx) => console.error(`  ${idx}: ${log}`));
+      throw new Error(`Simulation failed: ${JSON.stringify(simulation.value.err)}`);
+    }
+
+    const returnData = simulation.value.returnData;
+    assert.equal(returnData.programId, program.programId.toBase58());
+    return Buffer.from(returnData.data[0], "base64");
+  }
+
+  const simulateReturnData = async (transaction: VersionedTransaction, typeName: "userView" | "vaultView" | "allocationPreview") => {
+    return program.coder.types.decode(typeName, await simulateReturnBuffer(transaction));
+  }
+
+  const simulateReturnU64 = async (ix: TransactionInstruction) => {
+    const transaction = await buildVersionedTransaction(ix);
+    return new BN(await simulateReturnBuffer(transaction), "le");
+  }
+
+  const fetchVaultView = async () => {
+    const viewVaultIx = await program.methods.viewVault()
+      .accountsStrict({ config: configPDA, cusdcMint: cusdcMint, vaultUsdc: vaultUSDC })
+      .remainingAccounts([...jupLendingAccounts, ...kaminoAccounts, ...marginfiAccounts, ...klendAccounts, ...saveAccounts])
+      .instruction();
+    return simulateReturnData(await buildVersionedTransaction(viewVaultIx), "vaultView");
+  }
+
+  // A fixed-allocation protocol should sit within the rebalance threshold of its share of total assets
+  const assertNearTarget = (balance: BN, targetBps: number, totalAssets: BN) => {
+    const target = totalAssets.muln(targetBps).divn(10_000);
+    const tolerance = totalAssets.muln(200).divn(10_000);
+    assert.isTrue(balance.gtn(0));
+    assert.isTrue(balance.sub(target).abs().lte(tolerance), `balance ${balance} not within ${tolerance} of ${target}`);
+  }
+
+  // A fixed-allocation segment before its init instruction ran, the config still holds the default key
+  const unsetSegment = (segment: AccountMeta[]): AccountMeta[] => [
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ...segment.slice(1),
+  ]
+
+  // Deposits `amount` and redeems every share it minted, returns the USDC that came back
+  const depositAndRedeem = async (amount: BN, fixedSegments: AccountMeta[]) => {
+    const remainingAccounts = [...jupLendingAccounts, ...kaminoAccounts, ...fixedSegments];
+    const usdcBefore = new BN((await provider.connection.getTokenAccountBalance(signerUSDC)).value.amount);
+    const cusdcBefore = new BN((await provider.connection.getTokenAccountBalance(signerCUSDC)).value.amount);
+
+    const depositIx = await program.methods.deposit(amount, [], [])
+      .accountsStrict({
+        user: signer.publicKey,
+        config: configPDA,
+        userUsdc: signerUSDC,
+        receiver: signer.publicKey,
+        receiverCusdc: signerCUSDC,
+        receiverPosition: signerPosition,
+        userAllowlist: null,
+        receiverAllowlist: null,
+        vaultUsdc: vaultUSDC,
+        cusdcMint: cusdcMint,
+        usdcMint: usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .remainingAccounts(remainingAccounts)
+      .instruction();
+    await sendTransaction(await buildVersionedTransaction(depositIx));
+
+    const minted = new BN((await provider.connection.getTokenAccountBalance(signerCUSDC)).value.amount).sub(cusdcBefore);
+    assert.isTrue(minted.gtn(0));
+
+    const withdrawIx = await program.methods.withdraw(minted, [])
+      .accountsStrict({
+        config: configPDA,
+        user: signer.publicKey,
+        owner: signer.publicKey,
+        ownerPosition: signerPosition,
+        operatorApproval: null,
+        ownerAllowlist: null,
+        receiverUsdc: signerUSDC,
+        ownerCusdc: signerCUSDC,
+        vaultUsdc: vaultUSDC,
+        cusdcMint: cusdcMint,
+        usdcMint: usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .remainingAccounts(remainingAccounts)
+      .instruction();
+    await sendTransaction(await buildVersionedTransaction(withdrawIx));
+
+    const cusdcAfter = new BN((await provider.connection.getTokenAccountBalance(signerCUSDC)).value.amount);
+    assert.equal(cusdcAfter.toString(), cusdcBefore.toString());
+
+    const usdcAfter = new BN((await provider.connection.getTokenAccountBalance(signerUSDC)).value.amount);
+    return usdcAfter.sub(usdcBefore.sub(amount));
+  }
+
   const buildVersionedTransaction = async (ix: TransactionInstruction): Promise<VersionedTransaction> => {
 
     const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
@@ -337,9 +553,11 @@ describe("contract", () => {
     
     signerUSDC = getAssociatedTokenAddressSync(usdcMint, signer.publicKey);
     ownerUSDC = getAssociatedTokenAddressSync(usdcMint, owner.publicKey);
-    signerCUSDC = getAssociatedTokenAddressSync(cusdcMint, signer.publicKey);
+    signerCUSDC = getAssociatedTokenAddressSync(cusdcMint, signer.publicKey, false, TOKEN_2022_PROGRAM_ID);
 
     vaultUSDC = getAssociatedTokenAddressSync(usdcMint, configPDA, true);
+    feeVault = PublicKey.findProgramAddressSync([Buffer.from("fee-vault")], program.programId)[0];
+    signerPosition = PublicKey.findProgramAddressSync([Buffer.from("position"), signer.publicKey.toBuffer()], program.programId)[0];
 
     // Juplend Protocol accounts
 
@@ -438,6 +656,44 @@ describe("contract", () => {
 
     instructionSysvar = SYSVAR_INSTRUCTIONS_PUBKEY;
 
+    // MarginFi main group and its USDC bank, cloned from mainnet
+    marginfiProgram = new PublicKey("MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA");
+    marginfiGroup = new PublicKey("4qp6Fx6tnZkY5Wropq9wUYgtFxXKwE6viZxFHg3rdAG8");
+    marginfiBank = new PublicKey("2s37akK2eyBbp8DZgCm7RtsaEz8eJP3Nxd4urLHQv7yB");
+    marginfiLiquidityVault = PublicKey.findProgramAddressSync([Buffer.from("liquidity_vault"), marginfiBank.toBuffer()], marginfiProgram)[0];
+    marginfiLiquidityVaultAuthority = PublicKey.findProgramAddressSync([Buffer.from("liquidity_vault_auth"), marginfiBank.toBuffer()], marginfiProgram)[0];
+    // Pyth USDC/USD feed the bank prices with
+    marginfiBankOracle = new PublicKey("Dpw1EAVrSB1ibxiDQyTAW6Zip3J4Btk2x4SgApQCeFbX");
+    marginfiAccount = Keypair.generate();
+
+    // Main market USDC reserve, held directly. Mints, supply vault and oracles are read from the reserve
+    klendReserve = new PublicKey("D6q6wuQSrifJKZYpR1M8R4YawnLDtDsMmWM1NbBmgJ59");
+    klendLendingMarket = new PublicKey("7u3HeHxYDLhnCoErrtycNokbQYbWGzLs6JSDqGAv5PfF");
+    klendLendingMarketAuthority = PublicKey.findProgramAddressSync([Buffer.from("lma"), klendLendingMarket.toBuffer()], klendProgram)[0];
+    const klendReserveData = (await provider.connection.getAccountInfo(klendReserve)).data;
+    const readPubkey = (offset: number) => new PublicKey(klendReserveData.subarray(offset, offset + 32));
+    klendReserveLiquiditySupply = readPubkey(160);
+    klendReserveCollateralMint = readPubkey(2560);
+    klendCtokenAccount = getAssociatedTokenAddressSync(klendReserveCollateralMint, configPDA, true, TOKEN_PROGRAM_ID);
+    // pyth, switchboard price, switchboard twap, scope, klend's program id where the reserve has none
+    klendOracles = [5224, 5160, 5192, 5112].map((offset) => {
+      const oracle = readPubkey(offset);
+      return oracle.equals(PublicKey.default) ? klendProgram : oracle;
+    });
+
+    // Save main pool USDC reserve. Mints, supply vault and oracles are read from the reserve
+    saveProgram = new PublicKey("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+    saveReserve = new PublicKey("BgxfHJDzm44T7XG68MYKx7YisTjZu73tVovyZSjJMpmw");
+    saveLendingMarket = new PublicKey("4UpD2fh7xH3VP9QQaXtsS1YY3bxzWhtfpks7FatyKvdY");
+    saveLendingMarketAuthority = PublicKey.findProgramAddressSync([saveLendingMarket.toBuffer()], saveProgram)[0];
+    const saveReserveData = (await provider.connection.getAccountInfo(saveReserve)).data;
+    const readSavePubkey = (offset: number) => new PublicKey(saveReserveData.subarray(offset, offset + 32));
+    saveReserveLiquiditySupply = readSavePubkey(75);
+    saveReserveCollateralMint = readSavePubkey(227);
+    saveCtokenAccount = getAssociatedTokenAddressSync(saveReserveCollateralMint, configPDA, true, TOKEN_PROGRAM_ID);
+    // pyth, switchboard, and the Save program id in place of an extra oracle
+    saveOracles = [readSavePubkey(107), readSavePubkey(139), saveProgram];
+
 
     await createAssociatedTokenAccount(
       provider.connection,
@@ -473,6 +729,7 @@ describe("contract", () => {
       cusdcMint,
       usdcMint,
       TOKEN_PROGRAM_ID,
+      TOKEN_2022_PROGRAM_ID,
       ASSOCIATED_TOKEN_PROGRAM_ID,
       SystemProgram.programId,
       SYSVAR_RENT_PUBKEY,
@@ -527,6 +784,31 @@ describe("contract", () => {
       reserveLiquiditySupplyVault2,
       reserveCollateralMint2,
       collateralTokenProgram2,
+      // MarginFi accounts
+      marginfiGroup,
+      marginfiAccount.publicKey,
+      marginfiBank,
+      marginfiLiquidityVault,
+      marginfiLiquidityVaultAuthority,
+      marginfiBankOracle,
+      marginfiProgram,
+      // klend reserve accounts
+      klendReserve,
+      klendLendingMarket,
+      klendLendingMarketAuthority,
+      klendReserveLiquiditySupply,
+      klendReserveCollateralMint,
+      klendCtokenAccount,
+      ...klendOracles,
+      // Save reserve accounts
+      saveProgram,
+      saveReserve,
+      saveLendingMarket,
+      saveLendingMarketAuthority,
+      saveReserveLiquiditySupply,
+      saveReserveCollateralMint,
+      saveCtokenAccount,
+      ...saveOracles,
     ];
 
     await createLookupTable(addressesToAdd);
@@ -703,6 +985,7 @@ describe("contract", () => {
        isSigner: false,
        isWritable: false
      },
+     // One group of 7 accounts per reserve in the vault's allocation strategy, in strategy order
      {
        pubkey: reserveAccount1,
        isSigner: false,
@@ -774,6 +1057,130 @@ describe("contract", () => {
        isWritable: false
      }
    ]
+   // 7 accounts, right after the Kamino reserve groups
+   marginfiAccounts = [
+     {
+       pubkey: marginfiGroup,
+       isSigner: false,
+       isWritable: false
+     },
+     {
+       pubkey: marginfiAccount.publicKey,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: marginfiBank,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: marginfiLiquidityVault,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: marginfiLiquidityVaultAuthority,
+       isSigner: false,
+       isWritable: false
+     },
+     {
+       pubkey: marginfiBankOracle,
+       isSigner: false,
+       isWritable: false
+     },
+     {
+       pubkey: marginfiProgram,
+       isSigner: false,
+       isWritable: false
+     }
+   ]
+   // 11 accounts, right after the MarginFi accounts
+   klendAccounts = [
+     {
+       pubkey: klendReserve,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: klendLendingMarket,
+       isSigner: false,
+       isWritable: false
+     },
+     {
+       pubkey: klendLendingMarketAuthority,
+       isSigner: false,
+       isWritable: false
+     },
+     {
+       pubkey: klendReserveLiquiditySupply,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: klendReserveCollateralMint,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: klendCtokenAccount,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: TOKEN_PROGRAM_ID,
+       isSigner: false,
+       isWritable: false
+     },
+     ...klendOracles.map((oracle) => ({
+       pubkey: oracle,
+       isSigner: false,
+       isWritable: false
+     }))
+   ]
+   // 10 accounts, right after the klend reserve accounts
+   saveAccounts = [
+     {
+       pubkey: saveReserve,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: saveLendingMarket,
+       isSigner: false,
+       isWritable: false
+     },
+     {
+       pubkey: saveLendingMarketAuthority,
+       isSigner: false,
+       isWritable: false
+     },
+     {
+       pubkey: saveReserveLiquiditySupply,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: saveReserveCollateralMint,
+       isSigner: false,
+       isWritable: true
+     },
+     {
+       pubkey: saveCtokenAccount,
+       isSigner: false,
+       isWritable: true
+     },
+     ...saveOracles.map((oracle) => ({
+       pubkey: oracle,
+       isSigner: false,
+       isWritable: false
+     })),
+     {
+       pubkey: saveProgram,
+       isSigner: false,
+       isWritable: false
+     }
+   ]
 
   })
   
@@ -783,6 +1190,21 @@ describe("contract", () => {
     setupEventListener("withdrawEvent");
     setupEventListener("rebalanceEvent");
     setupEventListener("allocationUpdateEvent");
+    setupEventListener("allocationModeUpdateEvent");
+    setupEventListener("rebalanceParamsUpdateEvent");
+    setupEventListener("keeperTipEvent");
+    setupEventListener("harvestEvent");
+    setupEventListener("swapProgramUpdateEvent");
+    setupEventListener("operatorApprovalEvent");
+    setupEventListener("accessModeUpdateEvent");
+    setupEventListener("allowlistUpdateEvent");
+    setupEventListener("holdingPeriodUpdateEvent");
+    setupEventListener("shareMetadataUpdateEvent");
+    setupEventListener("farmBufferUpdateEvent");
+    setupEventListener("farmUnstakeEvent");
+    setupEventListener("marginfiAccountInitEvent");
+    setupEventListener("klendReserveInitEvent");
+    setupEventListener("saveReserveInitEvent");
     setupEventListener("viewEvent");
   });
   
@@ -795,12 +1217,14 @@ describe("contract", () => {
  
 
   it("Initialize aggregator config", async () => {
-    const tx = await program.methods.initAggregatorConfig(5000).accountsStrict({
+    const tx = await program.methods.initAggregatorConfig(5000, "Fluid USDC", "fUSDC", "").accountsStrict({
       authority: signer.publicKey,
       usdcMint: usdcMint,
       tokenProgram: TOKEN_PROGRAM_ID,
+      shareTokenProgram: TOKEN_2022_PROGRAM_ID,
       cusdcMint: cusdcMint,
       vaultUsdc: vaultUSDC,
+      feeVault: feeVault,
       config: configPDA,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
@@ -812,6 +1236,113 @@ describe("contract", () => {
      });
 
     console.log("Your transaction signature", tx);
+
+    const metadata = await getTokenMetadata(provider.connection, cusdcMint, 'confirmed', TOKEN_2022_PROGRAM_ID);
+    assert.equal(metadata.name, "Fluid USDC");
+    assert.equal(metadata.symbol, "fUSDC");
+  });
+
+  it("Update share metadata", async () => {
+    await program.methods.updateShareMetadata("Fluid USDC Vault", "fUSDC", "https://fluid.example/fusdc.json")
+      .accountsStrict({
+        config: configPDA,
+        authority: signer.publicKey,
+        cusdcMint: cusdcMint,
+        shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    const metadata = await getTokenMetadata(provider.connection, cusdcMint, 'confirmed', TOKEN_2022_PROGRAM_ID);
+    assert.equal(metadata.name, "Fluid USDC Vault");
+    assert.equal(metadata.uri, "https://fluid.example/fusdc.json");
+  });
+
+  it("Init MarginFi account", async () => {
+    await program.methods.initMarginfiAccount()
+      .accountsStrict({
+        config: configPDA,
+        authority: signer.publicKey,
+        marginfiGroup: marginfiGroup,
+        bank: marginfiBank,
+        marginfiAccount: marginfiAccount.publicKey,
+        marginfiProgram: marginfiProgram,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer, marginfiAccount])
+      .rpc({ commitment: 'confirmed' });
+
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.marginfiAccount.toBase58(), marginfiAccount.publicKey.toBase58());
+    assert.equal(config.marginfiBank.toBase58(), marginfiBank.toBase58());
+    assert.equal(config.marginfiAllocationBps, 0);
+  });
+
+  it("Withdraw with klend not set up", async () => {
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.klendReserve.toBase58(), PublicKey.default.toBase58());
+
+    const redeemed = await depositAndRedeem(new BN(10_000_000), [
+      ...marginfiAccounts,
+      ...unsetSegment(klendAccounts),
+      ...unsetSegment(saveAccounts),
+    ]);
+    assert.isTrue(redeemed.gte(new BN(9_990_000)), `redeemed ${redeemed}`);
+  });
+
+  it("Init klend reserve", async () => {
+    await program.methods.initKlendReserve()
+      .accountsStrict({
+        config: configPDA,
+        authority: signer.publicKey,
+        reserve: klendReserve,
+        reserveCollateralMint: klendReserveCollateralMint,
+        ctokenAccount: klendCtokenAccount,
+        klendProgram: klendProgram,
+        collateralTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.klendReserve.toBase58(), klendReserve.toBase58());
+    assert.equal(config.klendAllocationBps, 0);
+  });
+
+  it("Withdraw with Save not set up", async () => {
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.saveReserve.toBase58(), PublicKey.default.toBase58());
+
+    const redeemed = await depositAndRedeem(new BN(10_000_000), [
+      ...marginfiAccounts,
+      ...klendAccounts,
+      ...unsetSegment(saveAccounts),
+    ]);
+    assert.isTrue(redeemed.gte(new BN(9_990_000)), `redeemed ${redeemed}`);
+  });
+
+  it("Init Save reserve", async () => {
+    await program.methods.initSaveReserve()
+      .accountsStrict({
+        config: configPDA,
+        authority: signer.publicKey,
+        reserve: saveReserve,
+        reserveCollateralMint: saveReserveCollateralMint,
+        ctokenAccount: saveCtokenAccount,
+        saveProgram: saveProgram,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.saveReserve.toBase58(), saveReserve.toBase58());
+    assert.equal(config.saveAllocationBps, 0);
   });
 
   it("Deposit", async ()=> {
@@ -819,11 +1350,16 @@ describe("contract", () => {
       user: signer.publicKey,
       config: configPDA,
       userUsdc: signerUSDC,
-      userCusdc: signerCUSDC,
+      receiver: signer.publicKey,
+      receiverCusdc: signerCUSDC,
+      receiverPosition: signerPosition,
+      userAllowlist: null,
+      receiverAllowlist: null,
       vaultUsdc: vaultUSDC,
       cusdcMint: cusdcMint,
       usdcMint: usdcMint,
       tokenProgram: TOKEN_PROGRAM_ID,
+      shareTokenProgram: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
       rent: SYSVAR_RENT_PUBKEY
@@ -832,11 +1368,14 @@ describe("contract", () => {
     
 
     // Build the instruction using Anchor
-    const depositIx = await program.methods.deposit(new BN(100_000_000))
+    const depositIx = await program.methods.deposit(new BN(100_000_000), [], [])
       .accountsStrict(accounts)
       .remainingAccounts([
         ...jupLendingAccounts,
-        ...kaminoAccounts
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
       ])
       .signers([signer])
       .instruction();
@@ -861,6 +1400,320 @@ describe("contract", () => {
     // assert.isTrue(Number(userSharesBalance.value.amount) > 0);
   })
 
+  it("Mint shares", async () => {
+    const accounts = {
+      user: signer.publicKey,
+      config: configPDA,
+      userUsdc: signerUSDC,
+      receiver: signer.publicKey,
+      receiverCusdc: signerCUSDC,
+      receiverPosition: signerPosition,
+      userAllowlist: null,
+      receiverAllowlist: null,
+      vaultUsdc: vaultUSDC,
+      cusdcMint: cusdcMint,
+      usdcMint: usdcMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY
+    };
+
+    const sharesToMint = new BN(10_000_000);
+    const cusdcBefore = await provider.connection.getTokenAccountBalance(signerCUSDC);
+
+    const mintIx = await program.methods.mintShares(sharesToMint, new BN(11_000_000), [], [])
+      .accountsStrict(accounts)
+      .remainingAccounts([
+        ...jupLendingAccounts,
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
+      ])
+      .signers([signer])
+      .instruction();
+
+    const transaction = await buildVersionedTransaction(mintIx);
+    await sendTransaction(transaction);
+
+    const cusdcAfter = await provider.connection.getTokenAccountBalance(signerCUSDC);
+    assert.equal(
+      new BN(cusdcAfter.value.amount).sub(new BN(cusdcBefore.value.amount)).toString(),
+      sharesToMint.toString()
+    );
+  })
+
+  it("Allowlist gated deposit", async () => {
+    const [signerAllowlist] = PublicKey.findProgramAddressSync(
+      [Buffer.from("allowlist"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const setAccessMode = (accessMode: any) => program.methods.updateAccessMode(accessMode, Array(32).fill(0))
+      .accountsStrict({ config: configPDA, authority: signer.publicKey })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    await setAccessMode({ allowlist: {} });
+
+    await program.methods.addToAllowlist()
+      .accountsStrict({
+        config: configPDA,
+        authority: signer.publicKey,
+        wallet: signer.publicKey,
+        allowlist: signerAllowlist,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    const depositIx = await program.methods.deposit(new BN(1_000_000), [], [])
+      .accountsStrict({
+        user: signer.publicKey,
+        config: configPDA,
+        userUsdc: signerUSDC,
+        receiver: signer.publicKey,
+        receiverCusdc: signerCUSDC,
+        receiverPosition: signerPosition,
+        userAllowlist: signerAllowlist,
+        receiverAllowlist: null,
+        vaultUsdc: vaultUSDC,
+        cusdcMint: cusdcMint,
+        usdcMint: usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .remainingAccounts([
+        ...jupLendingAccounts,
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
+      ])
+      .instruction();
+
+    await sendTransaction(await buildVersionedTransaction(depositIx));
+
+    // Wallets outside the allowlist can't take deposits
+    const maxDeposit = async (receiver: PublicKey, receiverAllowlist: PublicKey | null) => simulateReturnU64(
+      await program.methods.maxDeposit([])
+        .accountsStrict({ config: configPDA, receiver, receiverAllowlist, cusdcMint: cusdcMint, vaultUsdc: vaultUSDC })
+        .remainingAccounts([...jupLendingAccounts, ...kaminoAccounts, ...marginfiAccounts, ...klendAccounts, ...saveAccounts])
+        .instruction()
+    );
+    assert.isTrue((await maxDeposit(signer.publicKey, signerAllowlist)).gtn(0));
+    assert.equal((await maxDeposit(owner.publicKey, null)).toString(), "0");
+
+    await program.methods.removeFromAllowlist()
+      .accountsStrict({ config: configPDA, authority: signer.publicKey, allowlist: signerAllowlist })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    await setAccessMode({ permissionless: {} });
+
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.deepEqual(config.accessMode, { permissionless: {} });
+  })
+
+  it("Delisted owner withdraws to themselves", async () => {
+    const [signerAllowlist] = PublicKey.findProgramAddressSync(
+      [Buffer.from("allowlist"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const setAccessMode = (accessMode: any) => program.methods.updateAccessMode(accessMode, Array(32).fill(0))
+      .accountsStrict({ config: configPDA, authority: signer.publicKey })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    await setAccessMode({ allowlist: {} });
+    await program.methods.addToAllowlist()
+      .accountsStrict({
+        config: configPDA,
+        authority: signer.publicKey,
+        wallet: signer.publicKey,
+        allowlist: signerAllowlist,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+    await program.methods.removeFromAllowlist()
+      .accountsStrict({ config: configPDA, authority: signer.publicKey, allowlist: signerAllowlist })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    const withdrawAssetsIx = async (receiverUsdc: PublicKey) => program.methods.withdrawAssets(new BN(1_000_000), new BN(2_000_000), [])
+      .accountsStrict({
+        user: signer.publicKey,
+        config: configPDA,
+        owner: signer.publicKey,
+        ownerPosition: signerPosition,
+        operatorApproval: null,
+        ownerAllowlist: null,
+        receiverUsdc,
+        ownerCusdc: signerCUSDC,
+        vaultUsdc: vaultUSDC,
+        cusdcMint: cusdcMint,
+        usdcMint: usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .remainingAccounts([...jupLendingAccounts, ...kaminoAccounts, ...marginfiAccounts, ...klendAccounts, ...saveAccounts])
+      .signers([signer])
+      .instruction();
+
+    // Paying out to another wallet still needs the owner on the allowlist
+    try {
+      await sendTransaction(await buildVersionedTransaction(await withdrawAssetsIx(ownerUSDC)));
+      assert.fail("a delisted owner shouldn't pay out to another wallet");
+    } catch (error) {
+      assert.include(String(error), "NotAllowlisted");
+    }
+
+    // Their shares aren't frozen, they can always redeem to themselves
+    const usdcBefore = await provider.connection.getTokenAccountBalance(signerUSDC);
+    await sendTransaction(await buildVersionedTransaction(await withdrawAssetsIx(signerUSDC)));
+    const usdcAfter = await provider.connection.getTokenAccountBalance(signerUSDC);
+    assert.equal(new BN(usdcAfter.value.amount).sub(new BN(usdcBefore.value.amount)).toString(), "1000000");
+
+    await setAccessMode({ permissionless: {} });
+  })
+
+  it("Update holding period", async () => {
+    await program.methods.updateHoldingPeriod(new BN(1))
+      .accountsStrict({ config: configPDA, authority: signer.publicKey })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.minHoldingSlots.toNumber(), 1);
+
+    const position = await program.account.userPosition.fetch(signerPosition);
+    assert.equal(position.owner.toBase58(), signer.publicKey.toBase58());
+  })
+
+  it("Deposit for another receiver is locked", async () => {
+    const ownerCUSDC = getAssociatedTokenAddressSync(cusdcMint, owner.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const [ownerPosition] = PublicKey.findProgramAddressSync([Buffer.from("position"), owner.publicKey.toBuffer()], program.programId);
+    const remainingAccounts = [...jupLendingAccounts, ...kaminoAccounts, ...marginfiAccounts, ...klendAccounts, ...saveAccounts];
+
+    const updateHoldingPeriod = (slots: number) => program.methods.updateHoldingPeriod(new BN(slots))
+      .accountsStrict({ config: configPDA, authority: signer.publicKey })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    await updateHoldingPeriod(1_000);
+
+    const depositIx = await program.methods.deposit(new BN(1_000_000), [], [])
+      .accountsStrict({
+        user: signer.publicKey,
+        config: configPDA,
+        userUsdc: signerUSDC,
+        receiver: owner.publicKey,
+        receiverCusdc: ownerCUSDC,
+        receiverPosition: ownerPosition,
+        userAllowlist: null,
+        receiverAllowlist: null,
+        vaultUsdc: vaultUSDC,
+        cusdcMint: cusdcMint,
+        usdcMint: usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .remainingAccounts(remainingAccounts)
+      .instruction();
+    await sendTransaction(await buildVersionedTransaction(depositIx));
+
+    // The lock follows the minted shares to the receiver
+    const minted = (await provider.connection.getTokenAccountBalance(ownerCUSDC)).value.amount;
+    const position = await program.account.userPosition.fetch(ownerPosition);
+    assert.equal(position.lockedShares.toString(), minted);
+
+    const withdrawIx = async () => {
+      const shares = (await provider.connection.getTokenAccountBalance(ownerCUSDC)).value.amount;
+      return program.methods.withdraw(new BN(shares), [])
+        .accountsStrict({
+          config: configPDA,
+          user: owner.publicKey,
+          owner: owner.publicKey,
+          ownerPosition,
+          operatorApproval: null,
+          ownerAllowlist: null,
+          receiverUsdc: ownerUSDC,
+          ownerCusdc: ownerCUSDC,
+          vaultUsdc: vaultUSDC,
+          cusdcMint: cusdcMint,
+          usdcMint: usdcMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+    }
+
+    try {
+      const transaction = await buildVersionedTransaction(await withdrawIx());
+      transaction.sign([owner]);
+      await sendTransaction(transaction);
+      assert.fail("shares minted for another receiver shouldn't be redeemable right away");
+    } catch (error) {
+      assert.include(String(error), "HoldingPeriodActive");
+    }
+
+    // Once the period is back to a single slot the shares are free
+    await updateHoldingPeriod(1);
+    const transaction = await buildVersionedTransaction(await withdrawIx());
+    transaction.sign([owner]);
+    await sendTransaction(transaction);
+    assert.equal((await provider.connection.getTokenAccountBalance(ownerCUSDC)).value.amount, "0");
+  })
+
+  it("Migrate config", async () => {
+    let config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.version, 7);
+
+    // A config created at the current layout has nothing to migrate
+    try {
+      await program.methods.migrateConfig()
+        .accountsStrict({ authority: signer.publicKey, config: configPDA, systemProgram: SystemProgram.programId })
+        .signers([signer])
+        .rpc({ commitment: 'confirmed' });
+      assert.fail("migrate_config should reject a current config");
+    } catch (error) {
+      assert.include(String(error), "ConfigAlreadyMigrated");
+    }
+
+    config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.version, 7);
+  })
+
+  it("Update farm buffer", async () => {
+    let config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.farmLiquidBufferBps, 500);
+
+    await program.methods.updateFarmBuffer(1000)
+      .accountsStrict({ config: configPDA, authority: signer.publicKey })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.farmLiquidBufferBps, 1000);
+  })
+
   it("Update strategy", async () => {
     const accounts = {
       config: configPDA,
@@ -882,13 +1735,146 @@ describe("contract", () => {
   })
 
 
-  it("Rebalance", async () => {
+  it("Update MarginFi allocation", async () => {
+    await program.methods.updateFixedAllocation({ marginfi: {} }, 2000)
+      .accountsStrict({ config: configPDA, authority: signer.publicKey })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    // JupLend and Kamino keep their 70/30 split of the remaining 8000 bps
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.marginfiAllocationBps, 2000);
+    assert.equal(config.juplendAllocationBps, 5600);
+    assert.equal(config.kaminoAllocationBps, 2400);
+  })
+
+  it("Update klend allocation", async () => {
+    await program.methods.updateFixedAllocation({ klend: {} }, 1000)
+      .accountsStrict({ config: configPDA, authority: signer.publicKey })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    // MarginFi keeps its 2000 bps, JupLend and Kamino keep 70/30 of the remaining 7000
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.klendAllocationBps, 1000);
+    assert.equal(config.marginfiAllocationBps, 2000);
+    assert.equal(config.juplendAllocationBps, 4900);
+    assert.equal(config.kaminoAllocationBps, 2100);
+  })
+
+  it("Update Save allocation", async () => {
+    await program.methods.updateFixedAllocation({ save: {} }, 1000)
+      .accountsStrict({ config: configPDA, authority: signer.publicKey })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    // MarginFi and klend keep their 3000 bps, JupLend and Kamino keep 70/30 of the remaining 6000
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.saveAllocationBps, 1000);
+    assert.equal(config.klendAllocationBps, 1000);
+    assert.equal(config.marginfiAllocationBps, 2000);
+    assert.equal(config.juplendAllocationBps, 4200);
+    assert.equal(config.kaminoAllocationBps, 1800);
+  })
+
+
+  it("Update rebalance params", async () => {
+    const accounts = {
+      config: configPDA,
+      authority: signer.publicKey,
+    }
+
+    // 2% drift tolerance, 150 slots between keeper rebalances, 0.01 USDC tip
+    const tx = await program.methods.updateRebalanceParams(200, new BN(150), new BN(10_000))
+      .accountsStrict(accounts)
+      .signers([signer])
+      .rpc({
+        commitment: 'confirmed',
+        skipPreflight: false
+      });
+
+    console.log("Your transaction signature", tx);
+
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.driftThresholdBps, 200);
+    assert.equal(config.rebalanceCooldownSlots.toNumber(), 150);
+    assert.equal(config.keeperTip.toNumber(), 10_000);
+  })
+
+  it("Update allocation mode", async () => {
     const accounts = {
       config: configPDA,
       authority: signer.publicKey,
+    }
+
+    // Lean towards the higher APY, keeping at least 20% in each protocol
+    const tx = await program.methods.updateAllocationMode(
+        { apyDriven: {} },
+        { minBps: 2000, maxBps: 8000 },
+        { minBps: 2000, maxBps: 8000 },
+      )
+      .accountsStrict(accounts)
+      .signers([signer])
+      .rpc({
+        commitment: 'confirmed',
+        skipPreflight: false
+      });
+
+    console.log("Your transaction signature", tx);
+
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.deepEqual(config.allocationMode, { apyDriven: {} });
+    assert.equal(config.juplendBounds.maxBps, 8000);
+  })
+
+  it("Preview allocation", async () => {
+    const previewIx = await program.methods.previewAllocation()
+      .accountsStrict({ config: configPDA, cusdcMint: cusdcMint, vaultUsdc: vaultUSDC })
+      .remainingAccounts([...jupLendingAccounts, ...kaminoAccounts, ...marginfiAccounts, ...klendAccounts, ...saveAccounts])
+      .instruction();
+    const preview = await simulateReturnData(await buildVersionedTransaction(previewIx), "allocationPreview");
+
+    console.log("Preview allocation - JupLend:", preview.juplendBps, "Kamino:", preview.kaminoBps);
+
+    // Only JupLend and Kamino move, the fixed targets are the stored ones
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(preview.marginfiBps, config.marginfiAllocationBps);
+    assert.equal(preview.klendBps, config.klendAllocationBps);
+    assert.equal(preview.saveBps, config.saveAllocationBps);
+    assert.equal(preview.juplendBps + preview.kaminoBps + preview.marginfiBps + preview.klendBps + preview.saveBps, 10000);
+    assert.isTrue(preview.totalAssets.gtn(0));
+  })
+
+  it("Update swap program", async () => {
+    const accounts = {
+      config: configPDA,
+      authority: signer.publicKey,
+      swapProgram: MOCK_SWAP_PROGRAM_ID,
+    }
+
+    const tx = await program.methods.updateSwapProgram()
+      .accountsStrict(accounts)
+      .signers([signer])
+      .rpc({
+        commitment: 'confirmed',
+        skipPreflight: false
+      });
+
+    console.log("Your transaction signature", tx);
+
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.equal(config.swapProgram.toBase58(), MOCK_SWAP_PROGRAM_ID.toBase58());
+  })
+
+  it("Rebalance", async () => {
+    const accounts = {
+      config: configPDA,
+      keeper: signer.publicKey,
       cusdcMint: cusdcMint,
       usdcMint: usdcMint,
       vaultUsdc: vaultUSDC,
+      feeVault: null,
+      keeperUsdc: null,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
@@ -899,13 +1885,26 @@ describe("contract", () => {
       .accountsStrict(accounts)
       .remainingAccounts([
         ...jupLendingAccounts,
-        ...kaminoAccounts
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
       ])
       .instruction();
 
     const transaction = await buildVersionedTransaction(rebalanceIx);
 
     await sendTransaction(transaction);
+
+    // The first rebalance starts the window the Kamino realized APY is measured over
+    const config = await program.account.aggregatorConfig.fetch(configPDA);
+    assert.isTrue(config.kaminoInterestSnapshot.timestamp.toNumber() > 0);
+
+    // The fixed-allocation protocols are priced into total assets and funded to their targets
+    const vaultView = await fetchVaultView();
+    assertNearTarget(vaultView.marginfiBalance, config.marginfiAllocationBps, vaultView.totalAssets);
+    assertNearTarget(vaultView.klendBalance, config.klendAllocationBps, vaultView.totalAssets);
+    assertNearTarget(vaultView.saveBalance, config.saveAllocationBps, vaultView.totalAssets);
   })
 
   it("View", async () => {
@@ -914,45 +1913,248 @@ describe("contract", () => {
       authority: signer.publicKey,
       userCusdc: signerCUSDC,
       cusdcMint: cusdcMint,
+      vaultUsdc: vaultUSDC,
     }
 
     const viewIx = await program.methods.view()
       .accountsStrict(accounts)
       .remainingAccounts([
         ...jupLendingAccounts,
-        ...kaminoAccounts
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
       ])
       .signers([signer])
       .instruction();
       
       const transaction = await buildVersionedTransaction(viewIx);
+      const userView = await simulateReturnData(transaction, "userView");
+
+      console.log("Total assets:", userView.vault.totalAssets.toString());
+      console.log("Share price:", userView.vault.sharePrice.toString());
+      console.log("User shares:", userView.userShares.toString(), "value:", userView.userValue.toString());
+      assert.equal(userView.user.toBase58(), signer.publicKey.toBase58());
+      assert.equal(
+        userView.vault.totalAssets.toString(),
+        userView.vault.juplendBalance.add(userView.vault.kaminoBalance).add(userView.vault.marginfiBalance).add(userView.vault.klendBalance).add(userView.vault.saveBalance).add(userView.vault.idleUsdc).toString()
+      );
+
       await sendTransaction(transaction);
 
 
   })
 
+  it("View vault", async () => {
+    const accounts = {
+      config: configPDA,
+      cusdcMint: cusdcMint,
+      vaultUsdc: vaultUSDC,
+    }
+
+    const viewVaultIx = await program.methods.viewVault()
+      .accountsStrict(accounts)
+      .remainingAccounts([
+        ...jupLendingAccounts,
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
+      ])
+      .instruction();
+
+    const transaction = await buildVersionedTransaction(viewVaultIx);
+    const vaultView = await simulateReturnData(transaction, "vaultView");
+
+    console.log("JupLend APY bps:", vaultView.juplendApyBps.toString(), "Kamino APY bps:", vaultView.kaminoApyBps.toString());
+    assert.isTrue(vaultView.totalShares.gtn(0));
+  })
+
+  it("Preview conversions", async () => {
+    const accounts = {
+      config: configPDA,
+      cusdcMint: cusdcMint,
+      vaultUsdc: vaultUSDC,
+    }
+    const remainingAccounts = [...jupLendingAccounts, ...kaminoAccounts, ...marginfiAccounts, ...klendAccounts, ...saveAccounts];
+    const amount = new BN(1_000_000);
+
+    const previewDeposit = await simulateReturnU64(
+      await program.methods.previewDeposit(amount).accountsStrict(accounts).remainingAccounts(remainingAccounts).instruction()
+    );
+    const convertToShares = await simulateReturnU64(
+      await program.methods.convertToShares(amount).accountsStrict(accounts).remainingAccounts(remainingAccounts).instruction()
+    );
+    const previewMint = await simulateReturnU64(
+      await program.methods.previewMint(previewDeposit).accountsStrict(accounts).remainingAccounts(remainingAccounts).instruction()
+    );
+    const previewRedeem = await simulateReturnU64(
+      await program.methods.previewRedeem(previewDeposit).accountsStrict(accounts).remainingAccounts(remainingAccounts).instruction()
+    );
+    const previewWithdraw = await simulateReturnU64(
+      await program.methods.previewWithdraw(previewRedeem).accountsStrict(accounts).remainingAccounts(remainingAccounts).instruction()
+    );
+
+    console.log("Preview deposit:", previewDeposit.toString(), "mint:", previewMint.toString());
+    console.log("Preview redeem:", previewRedeem.toString(), "withdraw:", previewWithdraw.toString());
+
+    assert.equal(previewDeposit.toString(), convertToShares.toString());
+    // Rounding always favours the vault
+    assert.isTrue(previewMint.lte(amount));
+    assert.isTrue(previewRedeem.lte(amount));
+    assert.isTrue(previewWithdraw.lte(previewDeposit));
+
+    const maxWithdraw = await simulateReturnU64(
+      await program.methods.maxWithdraw()
+        .accountsStrict({ ...accounts, ownerCusdc: signerCUSDC })
+        .remainingAccounts(remainingAccounts)
+        .instruction()
+    );
+    console.log("Max withdraw:", maxWithdraw.toString());
+  })
+
+  it("Operator redeems within allowance", async () => {
+    // The wallet keypair acts as an operator redeeming the signer's cUSDC into its own USDC account
+    const operator = owner;
+    const [operatorApproval] = PublicKey.findProgramAddressSync(
+      [Buffer.from("operator"), signer.publicKey.toBuffer(), operator.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods.approveOperator(new BN(1_000_000))
+      .accountsStrict({
+        owner: signer.publicKey,
+        operator: operator.publicKey,
+        config: configPDA,
+        operatorApproval,
+        ownerCusdc: signerCUSDC,
+        cusdcMint: cusdcMint,
+        shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([signer])
+      .rpc({ commitment: 'confirmed' });
+
+    // The operator's own approval PDA is the delegate, for its allowance only
+    let ownerCusdc = await getAccount(provider.connection, signerCUSDC, 'confirmed', TOKEN_2022_PROGRAM_ID);
+    assert.equal(ownerCusdc.delegate.toBase58(), operatorApproval.toBase58());
+    assert.equal(ownerCusdc.delegatedAmount.toString(), "1000000");
+
+    const withdrawIx = await program.methods.withdraw(new BN(500_000), [])
+      .accountsStrict({
+        config: configPDA,
+        user: operator.publicKey,
+        owner: signer.publicKey,
+        ownerPosition: signerPosition,
+        operatorApproval,
+        ownerAllowlist: null,
+        receiverUsdc: ownerUSDC,
+        ownerCusdc: signerCUSDC,
+        vaultUsdc: vaultUSDC,
+        cusdcMint: cusdcMint,
+        usdcMint: usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .remainingAccounts([
+        ...jupLendingAccounts,
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
+      ])
+      .instruction();
+
+    const transaction = await buildVersionedTransaction(withdrawIx);
+    transaction.sign([operator]);
+    await sendTransaction(transaction);
+
+    const approval = await program.account.operatorApproval.fetch(operatorApproval);
+    assert.equal(approval.allowance.toNumber(), 500_000);
+    ownerCusdc = await getAccount(provider.connection, signerCUSDC, 'confirmed', TOKEN_2022_PROGRAM_ID);
+    assert.equal(ownerCusdc.delegatedAmount.toString(), "500000");
+  })
+
+  it("Withdraw assets", async () => {
+    const accounts = {
+      user: signer.publicKey,
+      config: configPDA,
+      owner: signer.publicKey,
+      ownerPosition: signerPosition,
+      operatorApproval: null,
+      ownerAllowlist: null,
+      receiverUsdc: signerUSDC,
+      ownerCusdc: signerCUSDC,
+      vaultUsdc: vaultUSDC,
+      cusdcMint: cusdcMint,
+      usdcMint: usdcMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      shareTokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
+    };
+
+    const usdcToWithdraw = new BN(5_000_000);
+    const usdcBefore = await provider.connection.getTokenAccountBalance(signerUSDC);
+    const cusdcBefore = await provider.connection.getTokenAccountBalance(signerCUSDC);
+
+    const withdrawIx = await program.methods.withdrawAssets(usdcToWithdraw, new BN(cusdcBefore.value.amount), [])
+      .accountsStrict(accounts)
+      .remainingAccounts([
+        ...jupLendingAccounts,
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
+      ])
+      .signers([signer])
+      .instruction();
+
+    const transaction = await buildVersionedTransaction(withdrawIx);
+    await sendTransaction(transaction);
+
+    const usdcAfter = await provider.connection.getTokenAccountBalance(signerUSDC);
+    assert.equal(
+      new BN(usdcAfter.value.amount).sub(new BN(usdcBefore.value.amount)).toString(),
+      usdcToWithdraw.toString()
+    );
+  })
+
   it("Withdraw", async () => {
     const accounts = {
       config: configPDA,
       user: signer.publicKey,
-      userUsdc: signerUSDC,
-      userCusdc: signerCUSDC,
+      owner: signer.publicKey,
+      ownerPosition: signerPosition,
+      operatorApproval: null,
+      ownerAllowlist: null,
+      receiverUsdc: signerUSDC,
+      ownerCusdc: signerCUSDC,
       vaultUsdc: vaultUSDC,
       cusdcMint: cusdcMint,
       usdcMint: usdcMint,
       tokenProgram: TOKEN_PROGRAM_ID,
+      shareTokenProgram: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
       rent: SYSVAR_RENT_PUBKEY,
     };
     
     const userCUSDCBalance = await provider.connection.getTokenAccountBalance(new PublicKey(signerCUSDC));
+    const vaultViewBefore = await fetchVaultView();
 
-    const withdrawIx = await program.methods.withdraw(new BN(userCUSDCBalance.value.amount))
+    const withdrawIx = await program.methods.withdraw(new BN(userCUSDCBalance.value.amount), [])
       .accountsStrict(accounts)
       .remainingAccounts([
         ...jupLendingAccounts,
-        ...kaminoAccounts
+        ...kaminoAccounts,
+        ...marginfiAccounts,
+        ...klendAccounts,
+        ...saveAccounts
       ])
       .signers([signer])
       .instruction();
@@ -970,6 +2172,12 @@ describe("contract", () => {
 
       assert.isTrue(Number(userUSDCBalance.value.amount) > 0);
 
+      // Redeeming every share pulls from the fixed-allocation protocols too
+      const vaultViewAfter = await fetchVaultView();
+      assert.isTrue(vaultViewAfter.marginfiBalance.lt(vaultViewBefore.marginfiBalance));
+      assert.isTrue(vaultViewAfter.klendBalance.lt(vaultViewBefore.klendBalance));
+      assert.isTrue(vaultViewAfter.saveBalance.lt(vaultViewBefore.saveBalance));
+
       // Wait for final events to propagate
       await new Promise(resolve => setTimeout(resolve, 3000));
 